@@ -0,0 +1,85 @@
+use axum_session::DatabasePool;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dxp_axum_session::{CompressedPool, MemoryPool};
+
+const LEVELS: [i32; 4] = [1, 3, 9, 19];
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("the clock is past 1970")
+        .as_secs() as i64
+}
+
+//about 6 KB of JSON shaped like a serialized axum_session session, repetitive keys and all
+fn session() -> String {
+    let data: Vec<String> = (0..70)
+        .map(|n| {
+            format!(
+                r#""key_{n}":"{{\"user_id\":{n},\"role\":\"member\",\"seen\":\"2024-10-01T12:{:02}:00Z\"}}""#,
+                n % 60
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"id":"0b6f2c1e-6a0e-4d3a-9f38-3f1c5b9a7e21","data":{{{}}},"expires":"2024-10-02T12:00:00Z","destroy":false,"renew":false,"longterm":false,"store":true,"update":true,"requests":1}}"#,
+        data.join(",")
+    )
+}
+
+async fn store_and_load<P: DatabasePool>(pool: &P, session: &str) -> Option<String> {
+    pool.store("a", session, now() + 3600, "sessions")
+        .await
+        .unwrap();
+    pool.load("a", "sessions").await.unwrap()
+}
+
+//what the inner pool holds for `session` at each level, the ratio the latency below buys
+fn ratios(runtime: &tokio::runtime::Runtime, session: &str) {
+    for level in LEVELS {
+        let inner = MemoryPool::new();
+        let pool = CompressedPool::new(inner.clone()).with_compression_level(level);
+        let stored = runtime.block_on(async {
+            pool.store("a", session, now() + 3600, "sessions")
+                .await
+                .unwrap();
+            inner.load("a", "sessions").await.unwrap().unwrap()
+        });
+        println!(
+            "zstd level {level}: {} of {} bytes stored, {:.1}%",
+            stored.len(),
+            session.len(),
+            100.0 * stored.len() as f64 / session.len() as f64
+        );
+    }
+}
+
+//a store and a load through a CompressedPool at each level against the bare MemoryPool
+fn compression(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let session = session();
+    ratios(&runtime, &session);
+
+    let mut group = c.benchmark_group("compression");
+    group.throughput(Throughput::Bytes(session.len() as u64));
+
+    let plain = MemoryPool::new();
+    group.bench_function("uncompressed", |b| {
+        b.to_async(&runtime)
+            .iter(|| store_and_load(&plain, &session))
+    });
+    for level in LEVELS {
+        let pool = CompressedPool::new(MemoryPool::new()).with_compression_level(level);
+        group.bench_with_input(BenchmarkId::new("zstd", level), &pool, |b, pool| {
+            b.to_async(&runtime).iter(|| store_and_load(pool, &session))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compression);
+criterion_main!(benches);
@@ -0,0 +1,583 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+use axum_session::DatabasePool;
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dxp_axum_session::DbPool;
+use sea_orm::{
+    sea_query::{Alias, Expr, OnConflict, Query},
+    ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+};
+use tokio::runtime::Runtime;
+
+const SEEDED: u32 = 1_000_000;
+//sessions of PAYLOAD bytes, the average of the request that asked for the id projection
+const LARGE_SESSIONS: u32 = 10_000;
+const PAYLOAD: u32 = 6_000;
+const EXPIRED_SESSIONS: u32 = 2_000;
+const BATCH: usize = 1_000;
+const INDEXED_SESSIONS: i64 = 100_000;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+//a fresh SQLite file, and Postgres as well when POSTGRES_URL is set
+async fn databases() -> Vec<(&'static str, DatabaseConnection)> {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "dxp-axum-session-bench-{}-{}.db",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let mut databases = vec![(
+        "sqlite",
+        Database::connect(format!("sqlite://{}?mode=rwc", path.display()))
+            .await
+            .unwrap(),
+    )];
+    if let Ok(url) = std::env::var("POSTGRES_URL") {
+        databases.push(("postgres", Database::connect(url).await.unwrap()));
+    }
+    databases
+}
+
+//left over by an interrupted run otherwise, dropped before and after every benchmark
+fn table(prefix: &str) -> String {
+    format!("bench_{prefix}_{}", std::process::id())
+}
+
+//`rows` sessions of `payload` bytes that never expire, inserted by the database itself,
+//store_many() would take minutes for a million
+async fn seed(db: &DatabaseConnection, table: &str, rows: u32, payload: u32) {
+    let last = rows - 1;
+    let sql = match db.get_database_backend() {
+        DbBackend::Postgres => format!(
+            "INSERT INTO {table} (id, expires, session) \
+             SELECT 'session-' || i, NULL, repeat('0', {payload}) \
+             FROM generate_series(0, {last}) AS i"
+        ),
+        _ => format!(
+            "WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < {last}) \
+             INSERT INTO {table} (id, expires, session) \
+             SELECT 'session-' || i, NULL, hex(zeroblob({payload} / 2)) FROM n"
+        ),
+    };
+    db.execute_unprepared(&sql).await.unwrap();
+}
+
+//the COUNT(*) exists() ran before, with the same NULL-expiry condition as the SELECT 1 LIMIT 1
+async fn exists_by_count(db: &DatabaseConnection, id: &str, table: &str) -> bool {
+    let builder = db.get_database_backend();
+    let row = db
+        .query_one(
+            builder.build(
+                Query::select()
+                    .expr_as(Expr::col(Alias::new("id")).count(), Alias::new("count"))
+                    .from(Alias::new(table))
+                    .and_where(Expr::col(Alias::new("id")).eq(id))
+                    .and_where(
+                        Expr::col(Alias::new("expires"))
+                            .is_null()
+                            .or(Expr::col(Alias::new("expires")).gt(Utc::now())),
+                    ),
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    row.try_get::<i64>("", "count").unwrap() > 0
+}
+
+//a hit and a miss on a table of a million sessions
+fn exists(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("exists");
+
+    let mut group = c.benchmark_group("exists");
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+            seed(&db, &table, SEEDED, 2).await;
+        });
+
+        for id in ["session-500000", "missing"] {
+            let parameter = format!("{backend}/{id}");
+            group.bench_with_input(BenchmarkId::new("select_1", &parameter), id, |b, id| {
+                b.to_async(&runtime)
+                    .iter(|| async { pool.exists(id, &table).await.unwrap() })
+            });
+            group.bench_with_input(BenchmarkId::new("count", &parameter), id, |b, id| {
+                b.to_async(&runtime)
+                    .iter(|| exists_by_count(&db, id, &table))
+            });
+        }
+
+        runtime.block_on(drop_table(&db, &table));
+    }
+    group.finish();
+}
+
+//the whole rows get_ids() fetched before, only to keep the ids
+async fn ids_from_rows(db: &DatabaseConnection, table: &str) -> Vec<String> {
+    let builder = db.get_database_backend();
+    let rows = db
+        .query_all(
+            builder.build(
+                Query::select()
+                    .columns([
+                        Alias::new("id"),
+                        Alias::new("expires"),
+                        Alias::new("session"),
+                    ])
+                    .from(Alias::new(table))
+                    .and_where(
+                        Expr::col(Alias::new("expires"))
+                            .is_null()
+                            .or(Expr::col(Alias::new("expires")).gt(Utc::now())),
+                    ),
+            ),
+        )
+        .await
+        .unwrap();
+
+    rows.iter()
+        .map(|row| {
+            std::hint::black_box(row.try_get::<String>("", "session").unwrap());
+            row.try_get::<String>("", "id").unwrap()
+        })
+        .collect()
+}
+
+//ten thousand sessions of 6 KB, the projection leaves 60 MB of payloads in the database
+fn get_ids(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("get_ids");
+
+    let mut group = c.benchmark_group("get_ids");
+    group.sample_size(10);
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+            seed(&db, &table, LARGE_SESSIONS, PAYLOAD).await;
+        });
+
+        group.bench_function(BenchmarkId::new("id_only", backend), |b| {
+            b.to_async(&runtime)
+                .iter(|| async { pool.get_ids(&table).await.unwrap() })
+        });
+        group.bench_function(BenchmarkId::new("whole_rows", backend), |b| {
+            b.to_async(&runtime).iter(|| ids_from_rows(&db, &table))
+        });
+
+        runtime.block_on(drop_table(&db, &table));
+    }
+    group.finish();
+}
+
+//every row expired an hour ago, bound like DbPool binds its timestamps
+async fn expire_all(db: &DatabaseConnection, table: &str) {
+    let builder = db.get_database_backend();
+    db.execute(
+        builder.build(Query::update().table(Alias::new(table)).value(
+            Alias::new("expires"),
+            Utc::now() - chrono::Duration::hours(1),
+        )),
+    )
+    .await
+    .unwrap();
+}
+
+//the cleanup delete_by_expiry() replaced, the expired rows are fetched whole before the delete
+async fn delete_by_expiry_from_rows(db: &DatabaseConnection, table: &str) -> Vec<String> {
+    let builder = db.get_database_backend();
+    let now = Utc::now();
+    let rows = db
+        .query_all(
+            builder.build(
+                Query::select()
+                    .columns([
+                        Alias::new("id"),
+                        Alias::new("expires"),
+                        Alias::new("session"),
+                    ])
+                    .from(Alias::new(table))
+                    .and_where(Expr::col(Alias::new("expires")).lte(now)),
+            ),
+        )
+        .await
+        .unwrap();
+    let ids = rows
+        .iter()
+        .map(|row| {
+            std::hint::black_box(row.try_get::<String>("", "session").unwrap());
+            row.try_get::<String>("", "id").unwrap()
+        })
+        .collect();
+
+    db.execute(
+        builder.build(
+            Query::delete()
+                .from_table(Alias::new(table))
+                .and_where(Expr::col(Alias::new("expires")).lte(now)),
+        ),
+    )
+    .await
+    .unwrap();
+    ids
+}
+
+async fn reseed(db: &DatabaseConnection, table: &str) {
+    seed(db, table, EXPIRED_SESSIONS, PAYLOAD).await;
+    expire_all(db, table).await;
+}
+
+//two thousand expired sessions of 6 KB, seeded again before every timed sweep
+fn delete_by_expiry(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("delete_by_expiry");
+
+    let mut group = c.benchmark_group("delete_by_expiry");
+    group.sample_size(10);
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+        });
+
+        //moved into the futures of iter_custom() below
+        let (pool, db, table) = (&pool, &db, &table);
+
+        group.bench_function(BenchmarkId::new("ids_only", backend), |b| {
+            b.to_async(&runtime).iter_custom(|iters| async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    reseed(db, table).await;
+                    let start = Instant::now();
+                    let ids = pool.delete_by_expiry(table).await.unwrap();
+                    elapsed += start.elapsed();
+                    assert_eq!(ids.len(), EXPIRED_SESSIONS as usize);
+                }
+                elapsed
+            })
+        });
+        group.bench_function(BenchmarkId::new("whole_rows", backend), |b| {
+            b.to_async(&runtime).iter_custom(|iters| async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    reseed(db, table).await;
+                    let start = Instant::now();
+                    let ids = delete_by_expiry_from_rows(db, table).await;
+                    elapsed += start.elapsed();
+                    assert_eq!(ids.len(), EXPIRED_SESSIONS as usize);
+                }
+                elapsed
+            })
+        });
+
+        runtime.block_on(drop_table(db, table));
+    }
+    group.finish();
+}
+
+//a thousand sessions in one store_many() against a store() each, the first round inserts them,
+//every later one updates them
+fn store_many(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("store_many");
+    let ids: Vec<String> = (0..BATCH).map(|n| format!("session-{n}")).collect();
+    let expires = Utc::now().timestamp() + 3600;
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .map(|id| (id.as_str(), "{\"user\":1}", expires))
+        .collect();
+
+    let mut group = c.benchmark_group("store_many");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+        });
+
+        group.bench_function(BenchmarkId::new("store_many", backend), |b| {
+            b.to_async(&runtime)
+                .iter(|| async { pool.store_many(&sessions, &table).await.unwrap() })
+        });
+        group.bench_function(BenchmarkId::new("store_loop", backend), |b| {
+            b.to_async(&runtime).iter(|| async {
+                for &(id, session, expires) in &sessions {
+                    pool.store(id, session, expires, &table).await.unwrap();
+                }
+            })
+        });
+
+        runtime.block_on(drop_table(&db, &table));
+    }
+    group.finish();
+}
+
+//sessions expiring one per second from an hour on, with a 1 KB payload to look up per row
+//when the index lacks the id
+async fn seed_expiring(pool: &DbPool, table: &str) {
+    let now = Utc::now().timestamp();
+    let payload = "0".repeat(1_000);
+    let ids: Vec<String> = (0..INDEXED_SESSIONS)
+        .map(|n| format!("session-{n}"))
+        .collect();
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .zip(now + 3600..)
+        .map(|(id, expires)| (id.as_str(), payload.as_str(), expires))
+        .collect();
+
+    pool.store_many(&sessions, table).await.unwrap();
+}
+
+//get_ids() answered from the (expires, id) index initiate() creates, against the same table
+//with the index on expires alone that it created before
+fn covering_index(c: &mut Criterion) {
+    let runtime = runtime();
+    let covering = table("covering");
+    let expires_only = table("expires_only");
+
+    let mut group = c.benchmark_group("covering_index");
+    group.sample_size(10);
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            for table in [&covering, &expires_only] {
+                drop_table(&db, table).await;
+                pool.initiate(table).await.unwrap();
+                seed_expiring(&pool, table).await;
+            }
+
+            db.execute_unprepared(&format!("DROP INDEX {expires_only}_expires_idx"))
+                .await
+                .unwrap();
+            db.execute_unprepared(&format!(
+                "CREATE INDEX {expires_only}_expires_idx ON {expires_only} (expires)"
+            ))
+            .await
+            .unwrap();
+
+            //an index only scan needs the visibility map VACUUM builds
+            if db.get_database_backend() == DbBackend::Postgres {
+                for table in [&covering, &expires_only] {
+                    db.execute_unprepared(&format!("VACUUM ANALYZE {table}"))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        for (index, table) in [("expires_id", &covering), ("expires", &expires_only)] {
+            group.bench_function(BenchmarkId::new(index, backend), |b| {
+                b.to_async(&runtime)
+                    .iter(|| async { pool.get_ids(table).await.unwrap() })
+            });
+        }
+
+        runtime.block_on(async {
+            drop_table(&db, &covering).await;
+            drop_table(&db, &expires_only).await;
+        });
+    }
+    group.finish();
+}
+
+//the upsert of store() with the RETURNING sea_orm's insert().exec() appended on Postgres
+async fn store_returning(db: &DatabaseConnection, id: &str, session: &str, table: &str) {
+    let builder = db.get_database_backend();
+    let now = Utc::now();
+    let row = db
+        .query_one(
+            builder.build(
+                Query::insert()
+                    .into_table(Alias::new(table))
+                    .columns([
+                        Alias::new("id"),
+                        Alias::new("expires"),
+                        Alias::new("session"),
+                        Alias::new("created_at"),
+                        Alias::new("updated_at"),
+                    ])
+                    .values_panic([
+                        id.into(),
+                        (now + chrono::Duration::hours(1)).into(),
+                        session.into(),
+                        now.into(),
+                        now.into(),
+                    ])
+                    .on_conflict(
+                        OnConflict::column(Alias::new("id"))
+                            .update_columns([
+                                Alias::new("expires"),
+                                Alias::new("session"),
+                                Alias::new("updated_at"),
+                            ])
+                            .to_owned(),
+                    )
+                    .returning_col(Alias::new("id")),
+            ),
+        )
+        .await
+        .unwrap();
+    std::hint::black_box(row);
+}
+
+//store() without RETURNING against the same upsert with it, an update of one session each time
+fn store_returning_overhead(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("returning");
+
+    let mut group = c.benchmark_group("store");
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+        });
+        let expires = Utc::now().timestamp() + 3600;
+
+        group.bench_function(BenchmarkId::new("without_returning", backend), |b| {
+            b.to_async(&runtime).iter(|| async {
+                pool.store("a", "{\"user\":1}", expires, &table)
+                    .await
+                    .unwrap()
+            })
+        });
+        group.bench_function(BenchmarkId::new("returning", backend), |b| {
+            b.to_async(&runtime)
+                .iter(|| store_returning(&db, "a", "{\"user\":1}", &table))
+        });
+
+        runtime.block_on(drop_table(&db, &table));
+    }
+    group.finish();
+}
+
+//the select load() ran before its SQL was cached, its AST built and rendered on every call
+fn load_select(builder: DbBackend, id: &str, table: &str) -> Statement {
+    builder.build(
+        Query::select()
+            .columns([
+                Alias::new("id"),
+                Alias::new("expires"),
+                Alias::new("session"),
+            ])
+            .from(Alias::new(table))
+            .and_where(Expr::col(Alias::new("id")).eq(id))
+            .and_where(
+                Expr::col(Alias::new("expires"))
+                    .is_null()
+                    .or(Expr::col(Alias::new("expires")).gt(Utc::now())),
+            ),
+    )
+}
+
+async fn load_built(db: &DatabaseConnection, id: &str, table: &str) -> Option<String> {
+    db.query_one(load_select(db.get_database_backend(), id, table))
+        .await
+        .unwrap()
+        .map(|row| row.try_get::<String>("", "session").unwrap())
+}
+
+//load() with its cached SQL against building the select per call, and the building alone, the
+//part of every call the cache saves
+fn statement_cache(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("statement_cache");
+
+    let mut group = c.benchmark_group("statement_cache");
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+            pool.store("a", "{\"user\":1}", Utc::now().timestamp() + 3600, &table)
+                .await
+                .unwrap();
+        });
+
+        group.bench_function(BenchmarkId::new("load_cached", backend), |b| {
+            b.to_async(&runtime)
+                .iter(|| async { pool.load("a", &table).await.unwrap() })
+        });
+        group.bench_function(BenchmarkId::new("load_built", backend), |b| {
+            b.to_async(&runtime).iter(|| load_built(&db, "a", &table))
+        });
+        group.bench_function(BenchmarkId::new("build_only", backend), |b| {
+            b.iter(|| load_select(db.get_database_backend(), "a", &table))
+        });
+
+        runtime.block_on(drop_table(&db, &table));
+    }
+    group.finish();
+}
+
+//store() updating one session with payloads from 1 KB to 256 KB, every copy of the payload
+//shows up per byte
+fn store_payload(c: &mut Criterion) {
+    let runtime = runtime();
+    let table = table("store_payload");
+
+    let mut group = c.benchmark_group("store_payload");
+    for (backend, db) in runtime.block_on(databases()) {
+        let pool = DbPool::new(db.clone());
+        runtime.block_on(async {
+            drop_table(&db, &table).await;
+            pool.initiate(&table).await.unwrap();
+        });
+        let expires = Utc::now().timestamp() + 3600;
+
+        for size in [1 << 10, 16 << 10, 256 << 10] {
+            let session = "0".repeat(size);
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new(backend, size), &session, |b, session| {
+                b.to_async(&runtime)
+                    .iter(|| async { pool.store("a", session, expires, &table).await.unwrap() })
+            });
+        }
+
+        runtime.block_on(drop_table(&db, &table));
+    }
+    group.finish();
+}
+
+async fn drop_table(db: &DatabaseConnection, table: &str) {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        format!("DROP TABLE IF EXISTS {table}"),
+    ))
+    .await
+    .unwrap();
+}
+
+criterion_group!(
+    benches,
+    exists,
+    get_ids,
+    delete_by_expiry,
+    store_many,
+    covering_index,
+    store_returning_overhead,
+    statement_cache,
+    store_payload
+);
+criterion_main!(benches);
@@ -0,0 +1,190 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum_session::DatabasePool;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dxp_axum_session::{DashMemoryPool, MemoryPool};
+
+const SESSIONS: usize = 10_000;
+const LOADS_PER_READER: usize = 100;
+const CLEANUP_SESSIONS: i64 = 100_000;
+const EXPIRED: i64 = 1_000;
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("the clock is past 1970")
+        .as_secs() as i64
+}
+
+fn ids() -> Arc<Vec<String>> {
+    Arc::new((0..SESSIONS).map(|n| format!("session-{n}")).collect())
+}
+
+async fn seeded<P: DatabasePool>(pool: P, ids: &[String]) -> Arc<P> {
+    for id in ids {
+        pool.store(id, "{\"user\":1}", now() + 3600, "sessions")
+            .await
+            .unwrap();
+    }
+    Arc::new(pool)
+}
+
+//`readers` tasks load LOADS_PER_READER sessions each from the same pool at once
+async fn read_concurrently<P>(pool: &Arc<P>, ids: &Arc<Vec<String>>, readers: usize)
+where
+    P: DatabasePool + Send + Sync + 'static,
+{
+    let tasks: Vec<_> = (0..readers)
+        .map(|reader| {
+            let (pool, ids) = (pool.clone(), ids.clone());
+            tokio::spawn(async move {
+                for n in 0..LOADS_PER_READER {
+                    let id = &ids[(reader * LOADS_PER_READER + n) % ids.len()];
+                    std::hint::black_box(pool.load(id, "sessions").await.unwrap());
+                }
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+//the RwLock<HashMap> of MemoryPool against the shards of DashMemoryPool, one reader has the
+//lock to itself, with more of them MemoryPool serializes on it
+fn concurrent_loads(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let ids = ids();
+    let memory = runtime.block_on(seeded(MemoryPool::new(), &ids));
+    let dash = runtime.block_on(seeded(DashMemoryPool::new(), &ids));
+
+    let mut group = c.benchmark_group("concurrent_loads");
+    for readers in [1, 4, 16, 64] {
+        group.throughput(Throughput::Elements((readers * LOADS_PER_READER) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("MemoryPool", readers),
+            &readers,
+            |b, &readers| {
+                b.to_async(&runtime)
+                    .iter(|| read_concurrently(&memory, &ids, readers))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("DashMemoryPool", readers),
+            &readers,
+            |b, &readers| {
+                b.to_async(&runtime)
+                    .iter(|| read_concurrently(&dash, &ids, readers))
+            },
+        );
+    }
+    group.finish();
+}
+
+//what is left after a sweep, one session expiring per second from an hour on, so none of them
+//expire while the benchmark runs
+async fn live<P: DatabasePool>(pool: P) -> Arc<P> {
+    let now = now();
+    for n in 0..CLEANUP_SESSIONS - EXPIRED {
+        pool.store(
+            &format!("live-{n}"),
+            "{\"user\":1}",
+            now + 3600 + n,
+            "sessions",
+        )
+        .await
+        .unwrap();
+    }
+    Arc::new(pool)
+}
+
+//EXPIRED sessions with as many distinct expiries, stored again before every sweep
+async fn expire<P: DatabasePool>(pool: &P) {
+    let now = now();
+    for n in 0..EXPIRED {
+        pool.store(
+            &format!("expired-{n}"),
+            "{\"user\":1}",
+            now - 1 - n,
+            "sessions",
+        )
+        .await
+        .unwrap();
+    }
+}
+
+async fn sweep<P: DatabasePool>(pool: &P, iters: u64) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..iters {
+        expire(pool).await;
+
+        let start = Instant::now();
+        let deleted = pool.delete_by_expiry("sessions").await.unwrap();
+        elapsed += start.elapsed();
+
+        assert_eq!(deleted.len(), EXPIRED as usize);
+    }
+    elapsed
+}
+
+//1000 of 100000 sessions expired, MemoryPool takes them off the front of its BTreeMap index,
+//DashMemoryPool has no index and visits every session
+fn cleanup(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let memory = runtime.block_on(live(MemoryPool::new()));
+    let dash = runtime.block_on(live(DashMemoryPool::new()));
+
+    let mut group = c.benchmark_group("delete_by_expiry");
+    group.bench_function("MemoryPool", |b| {
+        b.to_async(&runtime).iter_custom(|iters| {
+            let memory = memory.clone();
+            async move { sweep(&*memory, iters).await }
+        })
+    });
+    group.bench_function("DashMemoryPool", |b| {
+        b.to_async(&runtime).iter_custom(|iters| {
+            let dash = dash.clone();
+            async move { sweep(&*dash, iters).await }
+        })
+    });
+    group.finish();
+}
+
+//store() of one session with payloads from 1 KB to 256 KB, every copy of the payload shows up
+//per byte
+fn store_payload(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let pool = MemoryPool::new();
+    let expires = now() + 3600;
+
+    let mut group = c.benchmark_group("store_payload");
+    for size in [1 << 10, 16 << 10, 256 << 10] {
+        let session = "0".repeat(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(
+            BenchmarkId::new("MemoryPool", size),
+            &session,
+            |b, session| {
+                b.to_async(&runtime)
+                    .iter(|| async { pool.store("a", session, expires, "sessions").await.unwrap() })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_loads, cleanup, store_payload);
+criterion_main!(benches);
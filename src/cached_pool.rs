@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use axum_session::{DatabaseError, DatabasePool};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::{DbPool, MemoryPool, SessionError};
+
+//reads are served from memory when possible, every write goes to the database first
+//the memory layer is best effort, its failures are logged but never fail a request. a session
+//that could not be cached after a write is evicted, so memory never serves an older payload
+#[derive(Clone, Debug)]
+pub struct CachedDbPool {
+    memory: MemoryPool,
+    db: DbPool,
+    //writes that changed the memory layer so far, held while they do. load() only caches what it
+    //read when none landed in between, the database may have changed since
+    writes: Arc<Mutex<u64>>,
+}
+
+impl CachedDbPool {
+    pub fn new(db: DbPool) -> CachedDbPool {
+        CachedDbPool {
+            memory: MemoryPool::new(),
+            db,
+            writes: Arc::default(),
+        }
+    }
+
+    /// Replaces the memory layer, e.g. with one created by `MemoryPool::with_capacity`.
+    pub fn with_memory_pool(mut self, memory: MemoryPool) -> CachedDbPool {
+        self.memory = memory;
+        self
+    }
+
+    /// Moves the expiry of a live session in both layers, see `DbPool::touch`.
+    pub async fn touch(
+        &self,
+        id: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<bool, SessionError> {
+        let touched = self.db.touch(id, expires, table_name).await?;
+        let _written = self.written().await;
+        log_memory_error(self.memory.touch(id, expires, table_name).await);
+
+        Ok(touched)
+    }
+
+    //taken after a database write, before the memory layer follows it
+    async fn written(&self) -> MutexGuard<'_, u64> {
+        let mut writes = self.writes.lock().await;
+        *writes += 1;
+        writes
+    }
+}
+
+fn log_memory_error<T, E: std::fmt::Display>(result: Result<T, E>) {
+    if let Err(err) = result {
+        tracing::warn!("memory layer of CachedDbPool failed: {err}");
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePool for CachedDbPool {
+    #[inline(always)]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.db.initiate(table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let ids = self.db.delete_by_expiry(table_name).await?;
+        let _written = self.written().await;
+
+        for id in &ids {
+            log_memory_error(self.memory.delete_one_by_id(id, table_name).await);
+        }
+        log_memory_error(self.memory.delete_by_expiry(table_name).await);
+
+        Ok(ids)
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        self.db.count(table_name).await
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        self.db.store(id, session, expires, table_name).await?;
+        let _written = self.written().await;
+
+        //the database deleted the session instead, a cached copy would outlive it
+        if self.db.is_empty(session) {
+            log_memory_error(self.memory.delete_one_by_id(id, table_name).await);
+            return Ok(());
+        }
+
+        if let Err(err) = self.memory.store(id, session, expires, table_name).await {
+            log_memory_error(Err::<(), _>(err));
+            //the previous payload must not be served in place of the one just written
+            log_memory_error(self.memory.delete_one_by_id(id, table_name).await);
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        match self.memory.load(id, table_name).await {
+            Ok(Some(session)) => return Ok(Some(session)),
+            result => log_memory_error(result),
+        }
+
+        let seen = *self.writes.lock().await;
+        let Some(model) = self.db.load_model(id, table_name).await? else {
+            return Ok(None);
+        };
+
+        //a write in the meantime may have cached a newer payload or removed the session
+        let writes = self.writes.lock().await;
+        if *writes != seen {
+            return Ok(Some(model.session));
+        }
+
+        //a NULL expires never expires, the memory layer treats i64::MAX the same way
+        let expires = model
+            .expires
+            .map(|expires| expires.timestamp())
+            .unwrap_or(i64::MAX);
+        log_memory_error(
+            self.memory
+                .store(id, &model.session, expires, table_name)
+                .await,
+        );
+        drop(writes);
+
+        Ok(Some(model.session))
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        self.db.delete_one_by_id(id, table_name).await?;
+        let _written = self.written().await;
+        log_memory_error(self.memory.delete_one_by_id(id, table_name).await);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        if let Ok(true) = self.memory.exists(id, table_name).await {
+            return Ok(true);
+        }
+
+        self.db.exists(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.db.delete_all(table_name).await?;
+        let _written = self.written().await;
+        log_memory_error(self.memory.delete_all(table_name).await);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.db.get_ids(table_name).await
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        self.db.auto_handles_expiry()
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use axum_session::{DatabaseError, DatabasePool};
+use tokio::time::Instant;
+
+/// Where a `CircuitBreakerPool` stands, see `CircuitBreakerPool::state`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Every call goes to the inner pool.
+    Closed,
+    /// Too many calls failed in a row, calls fail right away until the cooldown is over.
+    Open,
+    /// The cooldown is over, the next call probes the inner pool and decides.
+    HalfOpen,
+}
+
+//wraps any pool and stops calling it once `threshold` calls in a row failed, so an unreachable
+//database fails requests at once instead of piling them up behind connect timeouts
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerPool<P> {
+    inner: P,
+    breaker: Arc<Breaker>,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: AtomicU32,
+    opened_at: Mutex<Option<Instant>>,
+    //only one call probes a half-open circuit, the others are still turned away
+    probing: AtomicBool,
+}
+
+//lets the next call probe once this one is done, also when its future is dropped mid-probe,
+//e.g. by axum after the client disconnected
+struct Probe<'a>(&'a AtomicBool);
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl<P> CircuitBreakerPool<P> {
+    /// Opens after `threshold` consecutive failures and probes again once `cooldown` has passed.
+    pub fn new(inner: P, threshold: u32, cooldown: Duration) -> CircuitBreakerPool<P> {
+        CircuitBreakerPool {
+            inner,
+            breaker: Arc::new(Breaker {
+                threshold: threshold.max(1),
+                cooldown,
+                failures: AtomicU32::new(0),
+                opened_at: Mutex::new(None),
+                probing: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// The current state, for health checks and monitoring.
+    pub fn state(&self) -> CircuitState {
+        let opened_at = *self
+            .breaker
+            .opened_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.breaker.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    async fn call<T>(
+        &self,
+        operation: impl Future<Output = Result<T, DatabaseError>>,
+    ) -> Result<T, DatabaseError> {
+        let _probe = match self.state() {
+            CircuitState::Closed => None,
+            CircuitState::HalfOpen
+                if self
+                    .breaker
+                    .probing
+                    .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok() =>
+            {
+                Some(Probe(&self.breaker.probing))
+            }
+            CircuitState::Open | CircuitState::HalfOpen => {
+                return Err(DatabaseError::GenericSelectError(format!(
+                    "circuit open after {} consecutive failures, the database is not called \
+                     until the cooldown of {:?} is over",
+                    self.breaker.failures.load(Ordering::Acquire),
+                    self.breaker.cooldown
+                )));
+            }
+        };
+
+        let result = operation.await;
+
+        match &result {
+            //not a sign of an unhealthy database
+            Err(DatabaseError::GenericNotSupportedError(_)) => {}
+            Err(_) => self.failed(),
+            Ok(_) => self.succeeded(),
+        }
+
+        result
+    }
+
+    fn failed(&self) {
+        let failures = self.breaker.failures.fetch_add(1, Ordering::AcqRel) + 1;
+
+        //a failed probe opens the circuit again for another cooldown
+        if failures >= self.breaker.threshold {
+            *self
+                .breaker
+                .opened_at
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(Instant::now());
+        }
+    }
+
+    fn succeeded(&self) {
+        self.breaker.failures.store(0, Ordering::Release);
+        *self
+            .breaker
+            .opened_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> DatabasePool for CircuitBreakerPool<P>
+where
+    P: DatabasePool + Send + Sync,
+{
+    #[inline(always)]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.call(self.inner.initiate(table_name)).await
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.call(self.inner.delete_by_expiry(table_name)).await
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        self.call(self.inner.count(table_name)).await
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        self.call(self.inner.store(id, session, expires, table_name))
+            .await
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        self.call(self.inner.load(id, table_name)).await
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        self.call(self.inner.delete_one_by_id(id, table_name)).await
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        self.call(self.inner.exists(id, table_name)).await
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.call(self.inner.delete_all(table_name)).await
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.call(self.inner.get_ids(table_name)).await
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        self.inner.auto_handles_expiry()
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::Read;
+
+use axum_session::{DatabaseError, DatabasePool};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+//starts compressed payloads, a serialized session never starts with ESC so payloads stored
+//before the pool was wrapped load as they are
+const MARKER: &str = "\u{1b}zstd:";
+
+const DEFAULT_LEVEL: i32 = 3;
+
+//a payload inflating past this is treated as corrupt instead of filling the memory
+const MAX_DECOMPRESSED_LENGTH: u64 = 16 * 1024 * 1024;
+
+//wraps any pool and stores MARKER + base64(zstd(session)), a payload that does not get shorter
+//that way is stored as it is
+#[derive(Clone, Debug)]
+pub struct CompressedPool<P> {
+    inner: P,
+    level: i32,
+}
+
+impl<P> CompressedPool<P> {
+    pub fn new(inner: P) -> CompressedPool<P> {
+        CompressedPool {
+            inner,
+            level: DEFAULT_LEVEL,
+        }
+    }
+
+    /// zstd level from 1 (fastest) to 22 (smallest), 3 by default.
+    pub fn with_compression_level(mut self, level: i32) -> CompressedPool<P> {
+        self.level = level;
+        self
+    }
+
+    fn compress(&self, session: &str) -> Result<Option<String>, DatabaseError> {
+        let compressed = zstd::encode_all(session.as_bytes(), self.level)
+            .map_err(|err| DatabaseError::GenericInsertError(err.to_string()))?;
+
+        let payload = format!("{MARKER}{}", STANDARD.encode(compressed));
+
+        Ok((payload.len() < session.len()).then_some(payload))
+    }
+
+    fn decompress(payload: String) -> Option<String> {
+        let Some(compressed) = payload.strip_prefix(MARKER) else {
+            return Some(payload);
+        };
+
+        let compressed = STANDARD.decode(compressed).ok()?;
+        let mut session = Vec::new();
+
+        zstd::Decoder::new(compressed.as_slice())
+            .ok()?
+            .take(MAX_DECOMPRESSED_LENGTH + 1)
+            .read_to_end(&mut session)
+            .ok()?;
+
+        if session.len() as u64 > MAX_DECOMPRESSED_LENGTH {
+            return None;
+        }
+
+        String::from_utf8(session).ok()
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> DatabasePool for CompressedPool<P>
+where
+    P: DatabasePool + Send + Sync,
+{
+    #[inline(always)]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.initiate(table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.inner.delete_by_expiry(table_name).await
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        self.inner.count(table_name).await
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        match self.compress(session)? {
+            Some(payload) => self.inner.store(id, &payload, expires, table_name).await,
+            None => self.inner.store(id, session, expires, table_name).await,
+        }
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let payload = self.inner.load(id, table_name).await?;
+
+        //a payload that does not decompress loads as None, axum_session then starts a new session
+        Ok(payload.and_then(Self::decompress))
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_one_by_id(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        self.inner.exists(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_all(table_name).await
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.inner.get_ids(table_name).await
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        self.inner.auto_handles_expiry()
+    }
+}
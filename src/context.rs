@@ -0,0 +1,112 @@
+use std::fmt;
+
+use axum_session::DatabaseError;
+
+use crate::error::{ErrorKind, SessionError};
+
+//how much of a session id ends up in an error message, the id is a bearer token so never all of it
+const ID_PREFIX_LENGTH: usize = 8;
+
+//prefixes error messages with the operation, the table and a shortened session id
+//e.g. "load on sessions for session 1a2b3c4d…: connection closed"
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Context<'a> {
+    operation: &'static str,
+    table_name: &'a str,
+    id: Option<&'a str>,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn new(operation: &'static str, table_name: &'a str) -> Context<'a> {
+        Context {
+            operation,
+            table_name,
+            id: None,
+        }
+    }
+
+    pub(crate) fn with_id(mut self, id: &'a str) -> Context<'a> {
+        self.id = Some(id);
+        self
+    }
+
+    #[cfg(feature = "db_pool")]
+    pub(crate) fn db(&self, kind: ErrorKind, source: sea_orm::DbErr) -> SessionError {
+        SessionError::Db {
+            kind,
+            context: self.to_string(),
+            source,
+        }
+    }
+
+    #[cfg(feature = "db_pool")]
+    pub(crate) fn query(
+        &self,
+        kind: ErrorKind,
+        source: sea_orm::sea_query::error::Error,
+    ) -> SessionError {
+        SessionError::Query {
+            kind,
+            context: self.to_string(),
+            source,
+        }
+    }
+
+    pub(crate) fn invalid(&self, kind: ErrorKind, message: impl fmt::Display) -> SessionError {
+        SessionError::InvalidInput {
+            kind,
+            context: self.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[cfg(feature = "db_pool")]
+    pub(crate) fn setup(&self, message: impl fmt::Display) -> SessionError {
+        SessionError::Setup {
+            context: self.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    //adds the context to an argument check shared with the pools that have no SessionError
+    pub(crate) fn wrap(&self, error: DatabaseError) -> SessionError {
+        let kind = ErrorKind::from(&error);
+
+        match error {
+            DatabaseError::GenericAquire(message)
+            | DatabaseError::GenericInsertError(message)
+            | DatabaseError::GenericSelectError(message)
+            | DatabaseError::GenericCreateError(message)
+            | DatabaseError::GenericDeleteError(message)
+            | DatabaseError::GenericNotSupportedError(message) => self.invalid(kind, message),
+        }
+    }
+}
+
+impl fmt::Display for Context<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}", self.operation, self.table_name)?;
+
+        if let Some(id) = self.id {
+            write!(f, " for session {}", ShortId(id))?;
+        }
+
+        Ok(())
+    }
+}
+
+//a session id as it may appear in errors and spans, e.g. "1a2b3c4d…"
+pub(crate) struct ShortId<'a>(pub(crate) &'a str);
+
+impl fmt::Display for ShortId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix: String = self.0.chars().take(ID_PREFIX_LENGTH).collect();
+
+        if prefix.len() < self.0.len() {
+            write!(f, "{prefix}…")
+        } else {
+            //too short to cut, do not print it at all
+            write!(f, "<{} chars>", self.0.chars().count())
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use axum_session::{DatabaseError, DatabasePool};
+use chrono::Utc;
+use dashmap::DashMap;
+
+use crate::{expiry::parse_expires, memory_pool::SessionValue};
+
+//same semantics as MemoryPool, but the entries are sharded so reads never wait on a global lock
+//there is no separate expiry index, a second map could not be updated atomically with the entries
+#[derive(Clone, Debug, Default)]
+pub struct DashMemoryPool {
+    entries: Arc<DashMap<String, SessionValue>>,
+    max_expires: Option<i64>,
+}
+
+impl DashMemoryPool {
+    pub fn new() -> DashMemoryPool {
+        DashMemoryPool::default()
+    }
+
+    /// Expiry timestamps past `max_expires` are treated as never expiring.
+    pub fn with_max_expires(mut self, max_expires: i64) -> DashMemoryPool {
+        self.max_expires = Some(max_expires);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePool for DashMemoryPool {
+    #[inline(always)]
+    async fn initiate(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let now = Utc::now().timestamp();
+        let mut expired_entries = Vec::new();
+
+        self.entries.retain(|id, v| {
+            if v.expires <= now {
+                expired_entries.push(id.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        Ok(expired_entries)
+    }
+
+    #[inline(always)]
+    async fn count(&self, _table_name: &str) -> Result<i64, DatabaseError> {
+        let now = Utc::now().timestamp();
+
+        Ok(self
+            .entries
+            .iter()
+            .filter(|entry| entry.expires > now)
+            .count() as i64)
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        _table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        //never expiring sessions are kept with the largest possible expiry
+        let expiry = parse_expires(expires, self.max_expires)?
+            .map(|dt| dt.timestamp())
+            .unwrap_or(i64::MAX);
+
+        let model = SessionValue {
+            id: id.to_owned(),
+            session: session.to_string(),
+            expires: expiry,
+        };
+
+        self.entries.insert(id.to_owned(), model);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, _table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let now = Utc::now().timestamp();
+
+        if let Some(model) = self.entries.get(id) {
+            if model.expires > now {
+                return Ok(Some(model.session.clone()));
+            }
+        }
+
+        //the entry is expired, the check is repeated under the shard lock in case it was renewed
+        self.entries.remove_if(id, |_, model| model.expires <= now);
+
+        Ok(None)
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, _table_name: &str) -> Result<(), DatabaseError> {
+        self.entries.remove(id);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, _table_name: &str) -> Result<bool, DatabaseError> {
+        let now = Utc::now().timestamp();
+        Ok(self
+            .entries
+            .get(id)
+            .is_some_and(|model| model.expires > now))
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        self.entries.clear();
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let now = Utc::now().timestamp();
+
+        Ok(self
+            .entries
+            .iter()
+            .filter(|entry| entry.expires > now)
+            .map(|entry| entry.key().clone())
+            .collect())
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        false
+    }
+}
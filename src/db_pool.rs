@@ -1,241 +1,4431 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use async_trait::async_trait;
 use axum_session::{DatabaseError, DatabasePool};
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
 use sea_orm::{
-    sea_query::{self, ColumnDef, Index, Table},
-    ActiveValue, ColumnTrait, ColumnType, ConnectionTrait, DatabaseConnection, EntityName,
-    EntityTrait, PaginatorTrait, QueryFilter,
+    sea_query::{self, Alias, ColumnDef, Expr, Index, LockType, Order, Query, SimpleExpr, Table},
+    ColumnType, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr,
+    QueryResult, Statement, StreamTrait, TransactionTrait, Value,
+};
+
+use crate::{
+    clock::Clock,
+    context::Context,
+    entities::sessions,
+    error::{ErrorKind, SessionError},
+    expiry::{
+        bucket_seconds, bucket_start, clamp_expires, max_lifetime, parse_expires, ExpiryHistogram,
+        DEFAULT_MAX_EXPIRES,
+    },
+    measure::{self, Timer},
+    miss_cache::MissCache,
+    page::IdPage,
+    session_id,
+    statement_cache::StatementCache,
+    trace::{record, Traced},
+    write_behind::WriteBehind,
+    write_filter::WriteFilter,
 };
 
-use crate::entities::sessions;
+#[derive(Clone, Debug)]
+pub struct DbPool {
+    pool: DatabaseConnection,
+    //load(), exists(), count() and get_ids() go here when set, see with_replica()
+    replica: Option<DatabaseConnection>,
+    max_expires: Option<i64>,
+    lazy_expiry: bool,
+    id_length: Option<u32>,
+    table_name: Option<String>,
+    epoch_expires: bool,
+    skip_empty: Option<EmptySession>,
+    schema_check: bool,
+    clock: Option<Arc<dyn Clock>>,
+    tombstone_grace: Option<std::time::Duration>,
+    archival: bool,
+    max_lifetime: Option<Duration>,
+    never_expires_after: Option<Duration>,
+    cleanup_lease: Option<std::time::Duration>,
+    expires_index_name: Option<String>,
+    truncate_on_clear: bool,
+    monotonic_expiry: bool,
+    busy_retries: Option<u32>,
+    nul_escape: bool,
+    auto_handles_expiry: bool,
+    acquire_retries: u32,
+    acquire_backoff: Option<std::time::Duration>,
+    #[cfg(feature = "sqlite")]
+    sqlite_returning: Arc<std::sync::OnceLock<bool>>,
+    //SQL of store(), load() and exists(), rendered once per table
+    statements: Arc<StatementCache>,
+    cleanup_chunk_size: Option<usize>,
+    cleanup_batch_limit: Option<usize>,
+    cleanup_chunk_pause: Option<std::time::Duration>,
+    write_filter: Option<Arc<WriteFilter>>,
+    write_behind: Option<Arc<WriteBehind>>,
+    miss_cache: Option<Arc<MissCache>>,
+    #[cfg(feature = "postgres")]
+    jsonb: bool,
+    #[cfg(feature = "postgres")]
+    approximate_count: bool,
+    #[cfg(feature = "postgres")]
+    partitioned: bool,
+    #[cfg(feature = "postgres")]
+    unlogged: bool,
+    #[cfg(feature = "soft_delete")]
+    soft_delete: bool,
+    //set by NotifyingDbPool, announces an id once store() wrote it, see WriteBehind for queued ones
+    #[cfg(feature = "pg_notify")]
+    notify_writes: bool,
+}
+
+/// Audit information about a stored session, see `DbPool::get_session_metadata`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct SessionMeta {
+    pub id: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// None when the session never expires.
+    pub expires: Option<DateTime<Utc>>,
+}
+
+//the options of new() on a disconnected connection, a derived Default would leave the schema
+//check off
+impl Default for DbPool {
+    fn default() -> DbPool {
+        DbPool::new(DatabaseConnection::default())
+    }
+}
+
+//decides whether a payload carries no data, behind an Arc so DbPool stays cheap to clone
+#[derive(Clone)]
+struct EmptySession(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for EmptySession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EmptySession(..)")
+    }
+}
+
+//what axum_session serializes a session without any keys to
+fn is_empty_session(session: &str) -> bool {
+    let session = session.trim();
+    session.is_empty() || session == "{}"
+}
+
+impl DbPool {
+    pub fn new(db: DatabaseConnection) -> DbPool {
+        //https://www.sea-ql.org/SeaORM/docs/install-and-config/connection/
+        //"Under the hood, a sqlx::Pool is created and owned by DatabaseConnection."
+        DbPool {
+            pool: db,
+            replica: None,
+            max_expires: None,
+            lazy_expiry: false,
+            id_length: None,
+            table_name: None,
+            epoch_expires: false,
+            skip_empty: None,
+            schema_check: true,
+            clock: None,
+            tombstone_grace: None,
+            archival: false,
+            max_lifetime: None,
+            never_expires_after: None,
+            cleanup_lease: None,
+            expires_index_name: None,
+            truncate_on_clear: false,
+            monotonic_expiry: false,
+            busy_retries: None,
+            nul_escape: false,
+            auto_handles_expiry: false,
+            acquire_retries: 0,
+            acquire_backoff: None,
+            #[cfg(feature = "sqlite")]
+            sqlite_returning: Arc::default(),
+            statements: Arc::default(),
+            cleanup_chunk_size: None,
+            cleanup_batch_limit: None,
+            cleanup_chunk_pause: None,
+            write_filter: None,
+            write_behind: None,
+            miss_cache: None,
+            #[cfg(feature = "postgres")]
+            jsonb: false,
+            #[cfg(feature = "postgres")]
+            approximate_count: false,
+            #[cfg(feature = "postgres")]
+            partitioned: false,
+            #[cfg(feature = "postgres")]
+            unlogged: false,
+            #[cfg(feature = "soft_delete")]
+            soft_delete: false,
+            #[cfg(feature = "pg_notify")]
+            notify_writes: false,
+        }
+    }
+
+    /// Writes go to `primary`, load(), exists(), count() and get_ids() to `replica`, every other
+    /// read stays on `primary`. A replica lagging behind can miss a session just stored.
+    pub fn with_replica(primary: DatabaseConnection, replica: DatabaseConnection) -> DbPool {
+        DbPool {
+            replica: Some(replica),
+            ..DbPool::new(primary)
+        }
+    }
+
+    pub fn builder(db: DatabaseConnection) -> DbPoolBuilder {
+        DbPoolBuilder::new(db)
+    }
+
+    /// Expiry timestamps past `max_expires` are stored as NULL, i.e. the session never expires.
+    pub fn with_max_expires(mut self, max_expires: i64) -> DbPool {
+        self.max_expires = Some(max_expires);
+        self
+    }
+
+    /// Expiries further than `never_expires_after` from now are stored as NULL, so far-future
+    /// sentinels do not skew the expires index. Defaults to 50 years. Nearer ones are stored as
+    /// they are, on MySQL in a DATETIME column since TIMESTAMP ends in 2038.
+    pub fn with_never_expires_after(mut self, never_expires_after: Duration) -> DbPool {
+        self.never_expires_after = Some(never_expires_after);
+        self
+    }
+
+    /// store() never lets a session live longer than `max_lifetime`, sessions that would never
+    /// expire included. Shorter expiries are kept as they are.
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> DbPool {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+
+    /// When enabled, load() deletes an expired row it comes across in a background task.
+    pub fn with_lazy_expiry(mut self, lazy_expiry: bool) -> DbPool {
+        self.lazy_expiry = lazy_expiry;
+        self
+    }
+
+    /// Width of the id column, longer ids are rejected. Defaults to 128.
+    pub fn with_id_length(mut self, id_length: u32) -> DbPool {
+        self.id_length = Some(id_length);
+        self
+    }
+
+    /// Reads and writes expires as BIGINT unix seconds, the layout of axum_session_sqlx tables.
+    /// The table keeps that layout exactly: initiate() adds no columns to an existing one and
+    /// creates a new one with just id, expires and session. What needs the metadata columns,
+    /// store_with_user(), get_ids_by_user_id(), delete_by_user_id(), get_session_metadata() and
+    /// soft deletes, is not supported with it.
+    pub fn with_epoch_expires(mut self, epoch_expires: bool) -> DbPool {
+        self.epoch_expires = epoch_expires;
+        self
+    }
+
+    /// When enabled, store() with an empty or `{}` payload deletes the row instead of writing it.
+    pub fn with_skip_empty_sessions(mut self, skip_empty: bool) -> DbPool {
+        self.skip_empty = skip_empty.then(|| EmptySession(Arc::new(is_empty_session)));
+        self
+    }
+
+    /// store() skips the write when the payload is the one it last wrote for the id and the
+    /// expiry moved by at most `expiry_slack`, remembering the last `capacity` ids. The expiry
+    /// then lags behind by up to `expiry_slack`, a larger move writes again so renewals land.
+    /// Deletes through this pool are seen, changes made by another instance are not.
+    pub fn with_skip_unchanged(
+        mut self,
+        capacity: usize,
+        expiry_slack: std::time::Duration,
+    ) -> DbPool {
+        self.write_filter = Some(Arc::new(WriteFilter::new(capacity, expiry_slack)));
+        self
+    }
+
+    /// After load() or exists() found no session for an id, both answer from memory for that id
+    /// until `ttl` has passed, remembering up to `capacity` ids. A store() or delete through this
+    /// pool forgets the id at once, a session stored by another instance stays hidden until `ttl`
+    /// is over. A few seconds are enough to shield the database from repeated stale cookies.
+    pub fn with_miss_cache(mut self, capacity: usize, ttl: std::time::Duration) -> DbPool {
+        self.miss_cache = Some(Arc::new(MissCache::new(capacity, ttl)));
+        self
+    }
+
+    /// store() queues the session and returns, a background task writes the queue with
+    /// store_many() every `interval` or once `batch_size` sessions are queued. load(), exists()
+    /// and load_many() see queued sessions, get_ids(), the counts, the id pages and the streams
+    /// write the queue first. With `max_pending` sessions queued store() writes right away again.
+    /// Whatever is still queued when the last clone of the pool is dropped is lost, call flush()
+    /// before. The task is started by the first store() within a tokio runtime and writes with
+    /// the options of the pool that store() was called on, outside a runtime store() writes
+    /// right away.
+    pub fn with_write_behind(
+        mut self,
+        interval: std::time::Duration,
+        batch_size: usize,
+        max_pending: usize,
+    ) -> DbPool {
+        self.write_behind = Some(Arc::new(WriteBehind::new(
+            interval,
+            batch_size,
+            max_pending,
+        )));
+        self
+    }
+
+    /// Writes every session queued by with_write_behind(), e.g. on a graceful shutdown.
+    pub async fn flush(&self) -> Result<(), SessionError> {
+        match &self.write_behind {
+            Some(queue) => self.flush_queue(queue).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Number of writes store() skipped, see with_skip_unchanged().
+    pub fn skipped_writes(&self) -> u64 {
+        self.write_filter
+            .as_ref()
+            .map_or(0, |filter| filter.skipped())
+    }
+
+    /// Like `with_skip_empty_sessions(true)` with a custom test for an empty payload.
+    pub fn with_empty_session_predicate(
+        mut self,
+        is_empty: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> DbPool {
+        self.skip_empty = Some(EmptySession(Arc::new(is_empty)));
+        self
+    }
+
+    /// When disabled, initiate() accepts an existing table whatever its column types. Defaults to true.
+    pub fn with_schema_check(mut self, schema_check: bool) -> DbPool {
+        self.schema_check = schema_check;
+        self
+    }
+
+    /// Takes the current time from `clock` instead of the system time, for tests.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> DbPool {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Remembers deleted ids for `grace` in a `{table_name}_tombstones` table, store() does not
+    /// bring them back so a request still in flight after a logout can not resurrect the session.
+    /// Behind a CachedDbPool, give its MemoryPool the same grace period.
+    pub fn with_tombstones(mut self, grace: std::time::Duration) -> DbPool {
+        self.tombstone_grace = Some(grace);
+        self
+    }
+
+    /// delete_by_expiry() copies the expired rows to a `{table_name}_archive` table before it
+    /// deletes them, stamped with the time in `deleted_at`, see get_archived_session().
+    /// initiate() creates the table. Sessions deleted any other way are not archived.
+    pub fn with_archival(mut self, archival: bool) -> DbPool {
+        self.archival = archival;
+        self
+    }
+
+    /// The last archived payload of a session, see with_archival().
+    pub async fn get_archived_session(
+        &self,
+        id: &str,
+        table_name: &str,
+    ) -> Result<Option<String>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("get_archived_session", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
+
+        let builder = self.pool.get_database_backend();
+
+        let row = self
+            .reader()
+            .query_one(
+                builder.build(
+                    Query::select()
+                        .expr_as(self.session_column(), sessions::Column::Session)
+                        .from(Alias::new(archive_table(table_name)))
+                        .and_where(Expr::col(sessions::Column::Id).eq(id))
+                        .order_by(sessions::Column::DeletedAt, Order::Desc)
+                        .limit(1),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        row.map(|row| row.try_get::<String>("", "session").map(decode_session))
+            .transpose()
+            .map_err(|err| context.db(ErrorKind::Select, err))
+    }
+
+    /// Lets only one of several instances sharing the table run delete_by_expiry at a time, the
+    /// others return no ids. Postgres uses a transaction-level advisory lock keyed by the table
+    /// name, other backends a row in `cleanup_leases` held for `lease`, i.e. one sweep per lease
+    /// across all instances, so keep it a little shorter than the cleanup interval.
+    pub fn with_cleanup_lease(mut self, lease: std::time::Duration) -> DbPool {
+        self.cleanup_lease = Some(lease);
+        self
+    }
+
+    /// delete_by_expiry() removes at most `chunk_size` sessions per statement and transaction,
+    /// going on until fewer are left, so a large backlog does not hold one long transaction.
+    /// Defaults to 10000, capped at the ids one statement can bind, 997 on SQLite.
+    pub fn with_cleanup_chunk_size(mut self, chunk_size: usize) -> DbPool {
+        self.cleanup_chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// delete_by_expiry() removes at most `limit` sessions per call and returns only those, the
+    /// rest is left for the next call. Unlimited by default.
+    pub fn with_cleanup_batch_limit(mut self, limit: usize) -> DbPool {
+        self.cleanup_batch_limit = Some(limit);
+        self
+    }
+
+    /// On Postgres initiate() creates the session column as JSONB and store() writes payloads that
+    /// are valid JSON as such, anything else, e.g. an encrypted payload, as a JSON string. Loads
+    /// return the text either way. Needs a table created with it enabled.
+    #[cfg(feature = "postgres")]
+    pub fn with_jsonb(mut self, jsonb: bool) -> DbPool {
+        self.jsonb = jsonb;
+        self
+    }
+
+    /// On Postgres count() returns the planner's row estimate from `pg_class.reltuples` instead
+    /// of scanning the table. It is only as fresh as the last ANALYZE or autovacuum and includes
+    /// expired rows not swept yet. A table never analyzed and other backends are counted exactly.
+    #[cfg(feature = "postgres")]
+    pub fn with_approximate_count(mut self, approximate_count: bool) -> DbPool {
+        self.approximate_count = approximate_count;
+        self
+    }
+
+    /// On Postgres initiate() creates the table partitioned by the day sessions expire on, with a
+    /// default partition for those that never expire or whose day has no partition yet, see
+    /// ensure_partitions(). delete_by_expiry() drops the partitions of past days instead of
+    /// deleting their rows, unless soft deletes or with_archival() need them row by row.
+    /// Postgres only allows a primary key that includes the expiry, so there is none and DbPool
+    /// keeps the ids unique itself: store() updates or inserts and rotate() checks `new_id`, both
+    /// under an advisory lock on the id. Rows written around DbPool are not checked. Needs a
+    /// table created with it enabled.
+    #[cfg(feature = "postgres")]
+    pub fn with_partitioning(mut self, partitioned: bool) -> DbPool {
+        self.partitioned = partitioned;
+        self
+    }
+
+    /// On Postgres initiate() creates the table UNLOGGED, writes skip the WAL but the sessions are
+    /// lost on a crash and not replicated. The pg_unlogged feature enables it for every pool. A
+    /// partitioned table stays logged, Postgres refuses an unlogged one, and an existing table is
+    /// kept as it is.
+    #[cfg(feature = "postgres")]
+    pub fn with_unlogged(mut self, unlogged: bool) -> DbPool {
+        self.unlogged = unlogged;
+        self
+    }
+
+    /// Creates the missing daily partitions from today up to `days_ahead` days ahead, moving the
+    /// sessions already stored for those days out of the default partition, and returns how many
+    /// were created. Meant to run daily with `days_ahead` past the longest session lifetime.
+    #[cfg(feature = "postgres")]
+    pub async fn ensure_partitions(
+        &self,
+        days_ahead: u32,
+        table_name: &str,
+    ) -> Result<u32, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("ensure_partitions", table_name);
+
+        if !self.partitioned() {
+            return Err(context.invalid(
+                ErrorKind::NotSupported,
+                "only a Postgres pool with with_partitioning() has partitions",
+            ));
+        }
+
+        let today = self.now().date_naive();
+        let mut created = 0;
+
+        for days in 0..=days_ahead {
+            let Some(day) = today.checked_add_days(chrono::Days::new(days.into())) else {
+                break;
+            };
+
+            if self.create_partition(day, table_name, &context).await? {
+                created += 1;
+            }
+        }
+
+        Ok(created)
+    }
+
+    /// delete_one_by_id(), delete_by_expiry() and the other removals set `deleted_at` instead of
+    /// deleting the row, which every read then skips. delete_all() still deletes, purge_deleted()
+    /// removes marked rows for good. store() under a marked id brings the row back.
+    #[cfg(feature = "soft_delete")]
+    pub fn with_soft_delete(mut self, soft_delete: bool) -> DbPool {
+        self.soft_delete = soft_delete;
+        self
+    }
+
+    /// Deletes the rows marked deleted longer than `older_than` ago, returning how many.
+    #[cfg(feature = "soft_delete")]
+    pub async fn purge_deleted(
+        &self,
+        older_than: std::time::Duration,
+        table_name: &str,
+    ) -> Result<u64, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("purge_deleted", table_name);
+        let builder = self.pool.get_database_backend();
+
+        let before = chrono::Duration::from_std(older_than)
+            .ok()
+            .and_then(|older_than| self.now().checked_sub_signed(older_than))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        let result = self
+            .pool
+            .execute(
+                builder.build(
+                    Query::delete()
+                        .from_table(Alias::new(table_name))
+                        .and_where(
+                            Expr::col(sessions::Column::DeletedAt)
+                                .lt(self.to_db_datetime(Some(before))),
+                        ),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// delete_by_expiry() without collecting the ids, in one statement, returning how many expired
+    /// sessions were removed. With with_archival(), with_cleanup_lease(), with_partitioning() or
+    /// with_cleanup_batch_limit() it runs the same sweep as delete_by_expiry() and counts the ids
+    /// instead.
+    pub async fn purge_expired(&self, table_name: &str) -> Result<u64, SessionError> {
+        if self.archival
+            || self.cleanup_lease.is_some()
+            || self.partitioned()
+            || self.cleanup_batch_limit.is_some()
+        {
+            return Ok(self.sweep(table_name).await?.len() as u64);
+        }
+
+        let purged = self
+            .retry_busy(|| self.purge_expired_once(table_name))
+            .await?;
+
+        //the ids are not known, every write is checked again
+        if purged > 0 {
+            if let Some(filter) = &self.write_filter {
+                filter.clear();
+            }
+        }
+
+        Ok(purged)
+    }
+
+    /// Wait between two chunks of delete_by_expiry(), none by default.
+    pub fn with_cleanup_chunk_pause(mut self, pause: std::time::Duration) -> DbPool {
+        self.cleanup_chunk_pause = Some(pause);
+        self
+    }
+
+    /// store() only ever moves an existing session's expiry later, so a request racing with a
+    /// renewal can not shorten it. NULL (never expires) beats any timestamp, the payload is
+    /// always replaced. On MySQL it needs the `mysql` feature, without it initiate() and store()
+    /// fail with GenericNotSupportedError.
+    pub fn with_monotonic_expiry(mut self, monotonic_expiry: bool) -> DbPool {
+        self.monotonic_expiry = monotonic_expiry;
+        self
+    }
+
+    /// How often store(), delete_one_by_id(), delete_by_expiry() and delete_all() are retried
+    /// when SQLite reports the database as busy or locked, waiting 10ms and doubling each time.
+    /// Defaults to 5 on SQLite and 0 elsewhere.
+    pub fn with_busy_retries(mut self, busy_retries: u32) -> DbPool {
+        self.busy_retries = Some(busy_retries);
+        self
+    }
+
+    /// Tells axum_session that expired rows are removed elsewhere, e.g. by a pg_cron job, so it
+    /// does not run its own delete_by_expiry sweep.
+    pub fn with_auto_handles_expiry(mut self, auto_handles_expiry: bool) -> DbPool {
+        self.auto_handles_expiry = auto_handles_expiry;
+        self
+    }
+
+    /// Escapes NUL bytes in session payloads instead of rejecting them on Postgres, whose TEXT
+    /// columns can not hold them. Escaped payloads carry a marker and are restored on load.
+    pub fn with_nul_escape(mut self, nul_escape: bool) -> DbPool {
+        self.nul_escape = nul_escape;
+        self
+    }
+
+    /// delete_all() empties the table with TRUNCATE TABLE on Postgres and MySQL, much faster on
+    /// large tables but it takes an exclusive lock and on MySQL commits any open transaction.
+    /// SQLite has no TRUNCATE and keeps using DELETE.
+    pub fn with_truncate_on_clear(mut self, truncate_on_clear: bool) -> DbPool {
+        self.truncate_on_clear = truncate_on_clear;
+        self
+    }
+
+    /// Name of the index initiate() creates on (expires, id), `{table_name}_expires_idx` by default.
+    pub fn with_expires_index_name(mut self, name: impl Into<String>) -> DbPool {
+        self.expires_index_name = Some(name.into());
+        self
+    }
+
+    /// Counts every row in the table, including expired sessions that were not cleaned up yet.
+    pub async fn count_total(&self, table_name: &str) -> Result<i64, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("count_total", table_name);
+        let builder = self.pool.get_database_backend();
+        self.flush().await?;
+
+        let row = self
+            .pool
+            .query_one(
+                builder.build(
+                    Query::select()
+                        .expr(Expr::col(sessions::Column::Id).count())
+                        .from(Alias::new(table_name)),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        count_from_row(row, &context)
+    }
+
+    /// Deletes the session, returning whether a row was removed. Expired rows not cleaned up yet count.
+    pub async fn remove_session(&self, id: &str, table_name: &str) -> Result<bool, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("delete_one_by_id", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Delete)?;
+        self.forget_writes([id]);
+        self.forget_misses(table_name, [id]);
+        let _flushing = self.unqueue(table_name, [id]).await;
+
+        //the tombstone goes first, a store() after it writes nothing and one that slips in
+        //before the delete is deleted with it
+        if let Some(grace) = self.tombstone_grace {
+            self.bury(&self.pool, &[id], grace, table_name, &context)
+                .await?;
+        }
+
+        self.delete_row(&self.pool, id, table_name, &context).await
+    }
+
+    /// Like store(), also recording the user owning the session for get_ids_by_user_id() and
+    /// delete_by_user_id(). A plain store() afterwards keeps the owner.
+    pub async fn store_with_user(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        user_id: &str,
+        table_name: &str,
+    ) -> Result<(), SessionError> {
+        let context = Context::new("store_with_user", self.table_name(table_name)).with_id(id);
+        self.require_metadata(&context)?;
+        self.forget_writes([id]);
+        let _flushing = self.unqueue(self.table_name(table_name), [id]).await;
+        self.retry_busy(|| self.store_once(id, session, expires, Some(user_id), table_name))
+            .await?;
+        self.forget_misses(self.table_name(table_name), [id]);
+        Ok(())
+    }
+
+    /// The ids of the live sessions stored for `user_id`, e.g. to list a user's devices.
+    pub async fn get_ids_by_user_id(
+        &self,
+        user_id: &str,
+        table_name: &str,
+    ) -> Result<Vec<String>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("get_ids_by_user_id", table_name);
+        self.require_metadata(&context)?;
+        let builder = self.pool.get_database_backend();
+
+        let rows = self
+            .pool
+            .query_all(
+                builder.build(
+                    Query::select()
+                        .column(sessions::Column::Id)
+                        .from(Alias::new(table_name))
+                        .and_where(Expr::col(sessions::Column::UserId).eq(user_id))
+                        .and_where(self.is_live(self.now())),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        rows.iter()
+            .map(|row| row.try_get::<String>("", "id"))
+            .collect::<Result<Vec<_>, DbErr>>()
+            .map_err(|err| context.db(ErrorKind::Select, err))
+    }
+
+    /// Deletes every session stored for `user_id`, expired or not, returning how many. Always
+    /// removes the rows for good, soft deletes would keep the data an erasure has to get rid of.
+    pub async fn delete_by_user_id(
+        &self,
+        user_id: &str,
+        table_name: &str,
+    ) -> Result<u64, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("delete_by_user_id", table_name);
+        self.require_metadata(&context)?;
+        let builder = self.pool.get_database_backend();
+        let by_user = Expr::col(sessions::Column::UserId).eq(user_id);
+
+        //which ids belong to the user is not known here
+        if let Some(filter) = &self.write_filter {
+            filter.clear();
+        }
+
+        //a queued session of the user would come back after the delete
+        self.flush().await?;
+
+        //the ids are needed for the tombstones only
+        if let Some(grace) = self.tombstone_grace {
+            let rows = self
+                .pool
+                .query_all(
+                    builder.build(
+                        Query::select()
+                            .column(sessions::Column::Id)
+                            .from(Alias::new(table_name))
+                            .and_where(by_user.clone()),
+                    ),
+                )
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+            let ids = rows
+                .iter()
+                .map(|row| row.try_get::<String>("", "id"))
+                .collect::<Result<Vec<_>, DbErr>>()
+                .map_err(|err| context.db(ErrorKind::Select, err))?;
+            let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+            for chunk in ids.chunks(self.insert_rows()) {
+                self.bury(&self.pool, chunk, grace, table_name, &context)
+                    .await?;
+            }
+        }
+
+        let result = self
+            .pool
+            .execute(
+                builder.build(
+                    Query::delete()
+                        .from_table(Alias::new(table_name))
+                        .and_where(by_user),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes every session in `ids`, returning how many rows were removed. Unknown ids are
+    /// skipped, expired rows not cleaned up yet count.
+    pub async fn delete_many_by_ids(
+        &self,
+        ids: &[&str],
+        table_name: &str,
+    ) -> Result<u64, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("delete_many_by_ids", table_name);
+
+        for id in ids {
+            self.check_id(id, &context.with_id(id), ErrorKind::Delete)?;
+        }
+
+        self.forget_writes(ids.iter().copied());
+        let _flushing = self.unqueue(table_name, ids.iter().copied()).await;
+        let mut deleted = 0;
+
+        //sized for the tombstone insert, the largest statement per chunk
+        for chunk in ids.chunks(self.insert_rows()) {
+            if let Some(grace) = self.tombstone_grace {
+                self.bury(&self.pool, chunk, grace, table_name, &context)
+                    .await?;
+            }
+
+            let result = self
+                .pool
+                .execute(self.removal(
+                    table_name,
+                    Expr::col(sessions::Column::Id).is_in(chunk.iter().copied()),
+                    false,
+                ))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+            deleted += result.rows_affected();
+        }
+
+        Ok(deleted)
+    }
+
+    /// Moves a live session to `new_id` in one transaction, payload and expiry included, e.g. to
+    /// rotate the id after a login. False when `old_id` has no live session, a session already
+    /// stored under `new_id` fails the insert.
+    pub async fn rotate(
+        &self,
+        old_id: &str,
+        new_id: &str,
+        table_name: &str,
+    ) -> Result<bool, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("rotate", table_name).with_id(old_id);
+        self.check_id(old_id, &context, ErrorKind::Insert)?;
+        self.check_id(new_id, &context, ErrorKind::Insert)?;
+        self.forget_writes([old_id, new_id]);
+        //the copy is made in the database, a queued payload has to be there first
+        self.flush().await?;
+
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+
+        let txn = self
+            .pool
+            .begin()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        //no primary key turns a taken id away, see with_partitioning()
+        #[cfg(feature = "postgres")]
+        if self.partitioned() {
+            self.advisory_lock(
+                &txn,
+                &format!("{table_name}:{new_id}"),
+                ErrorKind::Insert,
+                &context,
+            )
+            .await?;
+
+            let taken = txn
+                .query_one(
+                    builder.build(
+                        Query::select()
+                            .expr(Expr::val(1))
+                            .from(Alias::new(table_name))
+                            .and_where(Expr::col(sessions::Column::Id).eq(new_id))
+                            .limit(1),
+                    ),
+                )
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+            if taken.is_some() {
+                return Err(context.invalid(
+                    ErrorKind::Insert,
+                    "a session is already stored under the new id",
+                ));
+            }
+        }
+
+        //copied within the database, the payload stays as stored, escaped or not
+        let mut copied = Query::select();
+        copied
+            .expr(Expr::val(new_id))
+            .column(sessions::Column::Expires)
+            .column(sessions::Column::Session);
+
+        if self.metadata() {
+            copied
+                .column(sessions::Column::CreatedAt)
+                .expr(Expr::val(self.to_db_datetime(Some(now))))
+                .column(sessions::Column::UserId);
+        }
+
+        let copy = Query::insert()
+            .into_table(Alias::new(table_name))
+            .columns(
+                COLUMNS.into_iter().chain(
+                    self.metadata_columns()
+                        .iter()
+                        .copied()
+                        .chain(self.metadata().then_some(sessions::Column::UserId)),
+                ),
+            )
+            .select_from(
+                copied
+                    .from(Alias::new(table_name))
+                    .and_where(Expr::col(sessions::Column::Id).eq(old_id))
+                    .and_where(self.is_live(now))
+                    .to_owned(),
+            )
+            .map_err(|err| context.query(ErrorKind::Insert, err))?
+            .to_owned();
+
+        let copied = txn
+            .execute(builder.build(&copy))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        if copied.rows_affected() == 0 {
+            txn.rollback()
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Insert, err))?;
+            return Ok(false);
+        }
+
+        //the old id must not come back through a request still using it
+        if let Some(grace) = self.tombstone_grace {
+            self.bury(&txn, &[old_id], grace, table_name, &context)
+                .await?;
+        }
+
+        self.delete_row(&txn, old_id, table_name, &context).await?;
+
+        txn.commit()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        self.forget_misses(table_name, [new_id]);
+        Ok(true)
+    }
+
+    /// Moves the expiry of a live session without sending its payload, false when there is none.
+    pub async fn touch(
+        &self,
+        id: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<bool, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("touch", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Insert)?;
+        self.forget_writes([id]);
+        self.flush().await?;
+        let expires = self.parse_expires(expires, &context)?;
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+
+        let mut update = Query::update();
+        update
+            .table(Alias::new(table_name))
+            .value(sessions::Column::Expires, self.to_db_datetime(expires))
+            .and_where(Expr::col(sessions::Column::Id).eq(id))
+            .and_where(self.is_live(now));
+
+        if self.metadata() {
+            update.value(sessions::Column::UpdatedAt, self.to_db_datetime(Some(now)));
+        }
+
+        let result = self
+            .pool
+            .execute(builder.build(&update))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Upserts every `(id, session, expires)` with as few statements as the backend's bind
+    /// parameter limit allows. Every entry is checked before anything is written.
+    pub async fn store_many(
+        &self,
+        sessions: &[(&str, &str, i64)],
+        table_name: &str,
+    ) -> Result<(), SessionError> {
+        let table_name = self.table_name(table_name);
+        self.forget_writes(sessions.iter().map(|&(id, _, _)| id));
+        let _flushing = self
+            .unqueue(table_name, sessions.iter().map(|&(id, _, _)| id))
+            .await;
+
+        self.write_many(sessions, table_name).await?;
+        self.forget_misses(table_name, sessions.iter().map(|&(id, _, _)| id));
+        Ok(())
+    }
+
+    //store_many() without touching the write-behind queue, which also writes through it
+    async fn write_many(
+        &self,
+        sessions: &[(&str, &str, i64)],
+        table_name: &str,
+    ) -> Result<(), SessionError> {
+        //a row can only be upserted once per statement on Postgres, the last one wins like with store()
+        let mut seen = HashSet::new();
+        let mut latest = Vec::with_capacity(sessions.len());
+        let mut empty = Vec::new();
+        for &(id, session, expires) in sessions.iter().rev() {
+            if !seen.insert(id) {
+                continue;
+            }
+
+            if self.is_empty(session) {
+                empty.push(id);
+            } else {
+                latest.push((id, session, expires));
+            }
+        }
+
+        let builder = self.pool.get_database_backend();
+        let context = Context::new("store_many", table_name);
+        self.check_monotonic(&context)?;
+
+        //a logged out session stays dead, see with_tombstones()
+        if self.tombstone_grace.is_some() {
+            let ids: Vec<&str> = latest.iter().map(|&(id, _, _)| id).collect();
+            let mut buried = HashSet::new();
+
+            for chunk in ids.chunks(self.in_list_length()) {
+                buried.extend(
+                    self.buried(chunk.iter().copied(), table_name, &context)
+                        .await?,
+                );
+            }
+            latest.retain(|&(id, _, _)| !buried.contains(id));
+        }
+
+        let now = self.to_db_datetime(Some(self.now()));
+
+        //the same checks and conversions as store(), all done before the first write
+        let mut rows = Vec::with_capacity(latest.len());
+        for (id, session, expires) in latest.into_iter().rev() {
+            let context = context.with_id(id);
+            self.check_id(id, &context, ErrorKind::Insert)?;
+            let expires = self.parse_expires(expires, &context)?;
+            let session = self.encode_session(session, &context)?;
+
+            rows.push((
+                id,
+                self.to_db_datetime(expires),
+                self.session_value(session),
+            ));
+        }
+
+        //empty sessions are deleted rather than written, see with_skip_empty_sessions()
+        for chunk in empty.chunks(self.in_list_length()) {
+            self.pool
+                .execute(self.removal(
+                    table_name,
+                    Expr::col(sessions::Column::Id).is_in(chunk.iter().copied()),
+                    false,
+                ))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+        }
+
+        //in one transaction, written entirely or not at all like the statements below
+        #[cfg(feature = "postgres")]
+        let rows = if self.partitioned() {
+            self.store_partitioned(rows, None, table_name, &context)
+                .await?;
+            Vec::new()
+        } else {
+            rows
+        };
+
+        let insert_rows = self.insert_rows();
+        let mut rows = rows.into_iter().peekable();
+
+        //each chunk is a single statement, so it is written entirely or not at all
+        while rows.peek().is_some() {
+            let mut insert = Query::insert();
+            insert.into_table(Alias::new(table_name)).columns(
+                COLUMNS
+                    .into_iter()
+                    .chain(self.metadata_columns().iter().copied()),
+            );
+
+            for (id, expires, session) in rows.by_ref().take(insert_rows) {
+                let metadata = self.metadata_columns().iter().map(|_| now.clone().into());
+
+                insert
+                    .values(
+                        [id.into(), expires.into(), session.into()]
+                            .into_iter()
+                            .chain(metadata),
+                    )
+                    .map_err(|err| context.query(ErrorKind::Insert, err))?;
+            }
+
+            insert.on_conflict(self.upsert(table_name));
+
+            self.pool
+                .execute(builder.build(&insert))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Insert, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads several sessions in one query, ids that are missing or expired are left out.
+    pub async fn load_many(
+        &self,
+        ids: &[&str],
+        table_name: &str,
+    ) -> Result<HashMap<String, String>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("load_many", table_name);
+
+        for id in ids {
+            self.check_id(id, &context.with_id(id), ErrorKind::Select)?;
+        }
+
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+        let mut sessions = HashMap::with_capacity(ids.len());
+
+        //one query per chunk, a long IN list would run past the bind parameter limit
+        for chunk in ids.chunks(self.in_list_length()) {
+            let rows = self
+                .pool
+                .query_all(
+                    builder.build(
+                        Query::select()
+                            .column(sessions::Column::Id)
+                            .expr_as(self.session_column(), sessions::Column::Session)
+                            .from(Alias::new(table_name))
+                            .and_where(Expr::col(sessions::Column::Id).is_in(chunk.iter().copied()))
+                            .and_where(self.is_live(now)),
+                    ),
+                )
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+            for row in &rows {
+                let session = || -> Result<(String, String), DbErr> {
+                    Ok((
+                        row.try_get("", "id")?,
+                        decode_session(row.try_get("", "session")?),
+                    ))
+                };
+
+                let (id, session) = session().map_err(|err| context.db(ErrorKind::Select, err))?;
+                sessions.insert(id, session);
+            }
+        }
+
+        //a queued store() is newer than its row
+        for &id in ids {
+            match self.queued(id, table_name) {
+                Some(Some(session)) => {
+                    sessions.insert(id.to_owned(), session);
+                }
+                Some(None) => {
+                    sessions.remove(id);
+                }
+                None => {}
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    /// The live session ids one by one, for jobs going through more than fits in memory. The
+    /// connection is taken on the first poll and held until the stream is dropped, errors are
+    /// yielded as items.
+    pub fn stream_ids<'a>(
+        &'a self,
+        table_name: &'a str,
+    ) -> impl Stream<Item = Result<String, SessionError>> + Send + 'a {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("stream_ids", table_name);
+
+        let query = Query::select()
+            .column(sessions::Column::Id)
+            .from(Alias::new(table_name))
+            .and_where(self.is_live(self.now()))
+            .to_owned();
+
+        self.stream_rows(query, context).map(move |row| {
+            row.and_then(|row| {
+                row.try_get::<String>("", "id")
+                    .map_err(|err| context.db(ErrorKind::Select, err))
+            })
+        })
+    }
+
+    /// Like stream_ids(), yielding `(id, session, expires)` of each live session, expires is
+    /// None for a session that never expires.
+    pub fn stream_sessions<'a>(
+        &'a self,
+        table_name: &'a str,
+    ) -> impl Stream<Item = Result<(String, String, Option<DateTime<Utc>>), SessionError>> + Send + 'a
+    {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("stream_sessions", table_name);
+
+        let query = Query::select()
+            .columns(KEY_COLUMNS)
+            .expr_as(self.session_column(), sessions::Column::Session)
+            .from(Alias::new(table_name))
+            .and_where(self.is_live(self.now()))
+            .to_owned();
+
+        self.stream_rows(query, context).map(move |row| {
+            let model = self.model_from_row(&row?, &context)?;
+            Ok((model.id, model.session, model.expires))
+        })
+    }
+
+    //runs the query on the first poll, so neither a connection nor an error is taken up front
+    fn stream_rows<'a>(
+        &'a self,
+        query: sea_query::SelectStatement,
+        context: Context<'a>,
+    ) -> impl Stream<Item = Result<QueryResult, SessionError>> + Send + 'a {
+        let statement = self.pool.get_database_backend().build(&query);
+
+        stream::once(async move {
+            //queued sessions are live as well
+            self.flush().await?;
+
+            self.pool
+                .stream(statement)
+                .await
+                .map(|rows| rows.map_err(move |err| context.db(ErrorKind::Select, err)))
+                .map_err(|err| context.db(ErrorKind::Select, err))
+        })
+        .try_flatten()
+    }
+
+    /// One page of live session ids, ordered by id so consecutive pages neither overlap nor skip.
+    pub async fn get_ids_page(
+        &self,
+        limit: u64,
+        offset: u64,
+        table_name: &str,
+    ) -> Result<Vec<String>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("get_ids_page", table_name);
+        let builder = self.pool.get_database_backend();
+        self.flush().await?;
+
+        let rows = self
+            .pool
+            .query_all(
+                builder.build(
+                    Query::select()
+                        .column(sessions::Column::Id)
+                        .from(Alias::new(table_name))
+                        .and_where(self.is_live(self.now()))
+                        .order_by(sessions::Column::Id, Order::Asc)
+                        .limit(limit)
+                        .offset(offset),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        ids_from_rows(&rows, &context)
+    }
+
+    /// The live session ids following `after` in id order, at most `limit` of them. Unlike
+    /// get_ids_page() the position is kept by the cursor, ids stored or deleted meanwhile do not
+    /// shift later pages. A `limit` of 0 is taken as 1, an empty page could never move on.
+    pub async fn get_ids_after(
+        &self,
+        after: Option<&str>,
+        limit: u64,
+        table_name: &str,
+    ) -> Result<IdPage, SessionError> {
+        let limit = limit.max(1);
+        let table_name = self.table_name(table_name);
+        let context = Context::new("get_ids_after", table_name);
+        let builder = self.pool.get_database_backend();
+        self.flush().await?;
+
+        let mut query = Query::select()
+            .column(sessions::Column::Id)
+            .from(Alias::new(table_name))
+            .and_where(self.is_live(self.now()))
+            .order_by(sessions::Column::Id, Order::Asc)
+            //one extra row tells whether there is another page
+            .limit(limit.saturating_add(1))
+            .to_owned();
+
+        if let Some(after) = after {
+            query.and_where(Expr::col(sessions::Column::Id).gt(after));
+        }
+
+        let rows = self
+            .pool
+            .query_all(builder.build(&query))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        Ok(IdPage::new(ids_from_rows(&rows, &context)?, limit))
+    }
+
+    /// Number of live sessions, the total to page through with `get_ids_page`.
+    pub async fn get_ids_count(&self, table_name: &str) -> Result<u64, SessionError> {
+        Ok(self.count_live(table_name).await? as u64)
+    }
+
+    /// load() together with the stored expiry in the same query, None as the expiry for a session
+    /// that never expires. See `remaining_ttl` for the time left.
+    pub async fn load_with_expiry(
+        &self,
+        id: &str,
+        table_name: &str,
+    ) -> Result<Option<(String, Option<DateTime<Utc>>)>, SessionError> {
+        let loaded = match self.queued_entry(id, table_name) {
+            Some(loaded) => loaded,
+            None if self.known_miss(id, table_name) => None,
+            None => {
+                let since = self.miss_generation();
+                let loaded = self
+                    .retry_acquire(|| self.load_model(id, table_name))
+                    .await?
+                    .map(|model| (model.session, model.expires));
+
+                if loaded.is_none() {
+                    self.remember_miss(id, table_name, since);
+                }
+                loaded
+            }
+        };
+
+        measure::load_result("db", loaded.is_some());
+        Ok(loaded)
+    }
+
+    /// When a live session was created and last stored, None if there is no such session.
+    /// Rows written before the metadata columns existed have no created_at or updated_at.
+    pub async fn get_session_metadata(
+        &self,
+        id: &str,
+        table_name: &str,
+    ) -> Result<Option<SessionMeta>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("get_session_metadata", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
+        self.require_metadata(&context)?;
+
+        let builder = self.pool.get_database_backend();
+
+        let row = self
+            .pool
+            .query_one(
+                builder.build(
+                    Query::select()
+                        .columns([sessions::Column::Id, sessions::Column::Expires])
+                        .columns(METADATA_COLUMNS)
+                        .from(Alias::new(table_name))
+                        .and_where(Expr::col(sessions::Column::Id).eq(id))
+                        .and_where(self.is_live(self.now())),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let meta = || -> Result<SessionMeta, DbErr> {
+            Ok(SessionMeta {
+                id: row.try_get("", "id")?,
+                created_at: self.read_db_datetime(&row, "created_at")?,
+                updated_at: self.read_db_datetime(&row, "updated_at")?,
+                expires: self.read_db_datetime(&row, "expires")?,
+            })
+        };
+
+        meta()
+            .map(Some)
+            .map_err(|err| context.db(ErrorKind::Select, err))
+    }
+
+    /// Live sessions grouped into `bucket` long slices of the next `horizon`, counted by the database.
+    pub async fn expiry_histogram(
+        &self,
+        bucket: Duration,
+        horizon: Duration,
+        table_name: &str,
+    ) -> Result<ExpiryHistogram, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("expiry_histogram", table_name);
+        let bucket_seconds = bucket_seconds(bucket).map_err(|err| context.wrap(err))?;
+        self.flush().await?;
+
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+        let epoch = now.timestamp();
+        let end = now
+            .checked_add_signed(horizon)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        //only integers are formatted into the expression, the bucket index is (expires - now) / bucket
+        let expires_epoch = match (self.epoch_expires, builder) {
+            (true, _) => "expires",
+            (false, DbBackend::Postgres) => "CAST(EXTRACT(EPOCH FROM expires) AS BIGINT)",
+            //UNIX_TIMESTAMP() would read the DATETIME in the connection's time_zone
+            (false, DbBackend::MySql) => "TIMESTAMPDIFF(SECOND, '1970-01-01 00:00:00', expires)",
+            (false, DbBackend::Sqlite) => "CAST(strftime('%s', expires) AS INTEGER)",
+        };
+        let index = match builder {
+            DbBackend::MySql => format!("({expires_epoch} - {epoch}) DIV {bucket_seconds}"),
+            DbBackend::Postgres | DbBackend::Sqlite => {
+                format!("({expires_epoch} - {epoch}) / {bucket_seconds}")
+            }
+        };
+
+        let rows = self
+            .pool
+            .query_all(
+                builder.build(
+                    Query::select()
+                        .expr_as(Expr::cust(index), Alias::new("bucket"))
+                        .expr_as(
+                            Expr::col(sessions::Column::Id).count(),
+                            Alias::new("sessions"),
+                        )
+                        .from(Alias::new(table_name))
+                        .and_where(
+                            Expr::col(sessions::Column::Expires).gt(self.to_db_datetime(Some(now))),
+                        )
+                        .and_where(
+                            Expr::col(sessions::Column::Expires).lt(self.to_db_datetime(Some(end))),
+                        )
+                        .and_where_option(self.not_deleted())
+                        .group_by_col(Alias::new("bucket"))
+                        .order_by(Alias::new("bucket"), Order::Asc),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        let mut buckets = Vec::with_capacity(rows.len());
+        for row in rows {
+            let index: i64 = row
+                .try_get("", "bucket")
+                .map_err(|err| context.db(ErrorKind::Select, err))?;
+            let sessions: i64 = row
+                .try_get("", "sessions")
+                .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+            if let Some(start) = bucket_start(epoch, bucket_seconds, index) {
+                buckets.push((start, sessions as u64));
+            }
+        }
+
+        let row = self
+            .pool
+            .query_one(
+                builder.build(
+                    Query::select()
+                        .expr(Expr::col(sessions::Column::Id).count())
+                        .from(Alias::new(table_name))
+                        .and_where(Expr::col(sessions::Column::Expires).is_null())
+                        .and_where_option(self.not_deleted()),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        Ok(ExpiryHistogram {
+            buckets,
+            never_expires: count_from_row(row, &context)? as u64,
+        })
+    }
+
+    async fn count_live(&self, table_name: &str) -> Result<i64, SessionError> {
+        //queued sessions are live as well
+        self.flush().await?;
+
+        let table_name = self.table_name(table_name);
+        let context = Context::new("count", table_name);
+        let builder = self.pool.get_database_backend();
+
+        #[cfg(feature = "postgres")]
+        if self.approximate_count && builder == DbBackend::Postgres {
+            if let Some(count) = self.count_estimate(table_name, &context).await? {
+                return Ok(count);
+            }
+        }
+
+        let row = self
+            .reader()
+            .query_one(
+                builder.build(
+                    Query::select()
+                        .expr(Expr::col(sessions::Column::Id).count())
+                        .from(Alias::new(table_name))
+                        .and_where(self.is_live(self.now())),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        // let (count,) = sqlx::query_as(
+        //     &r#"SELECT COUNT(*) FROM %%TABLE_NAME%%"#.replace("%%TABLE_NAME%%", table_name),
+        // )
+        // .fetch_one(&self.pool)
+        // .await
+        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+
+        count_from_row(row, &context)
+    }
+
+    //None when the table was never analyzed, reltuples is -1 until then
+    #[cfg(feature = "postgres")]
+    async fn count_estimate(
+        &self,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<Option<i64>, SessionError> {
+        let row = self
+            .reader()
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT reltuples::BIGINT FROM pg_class WHERE oid = to_regclass($1)",
+                [quoted(table_name).into()],
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        let estimate = row
+            .map(|row| row.try_get_by_index::<i64>(0))
+            .transpose()
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        Ok(estimate.filter(|estimate| *estimate >= 0))
+    }
+
+    //initiate(), run again by retry_acquire() when no connection could be acquired
+    async fn create_schema(&self, table_name: &str) -> Result<(), SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("initiate", table_name);
+        let builder = self.pool.get_database_backend();
+
+        self.check_monotonic(&context)?;
+
+        //deleted_at would have to be added to the table, see with_epoch_expires()
+        if self.soft_delete() && !self.metadata() {
+            return Err(
+                context.setup("soft deletes need a deleted_at column, the epoch layout has none")
+            );
+        }
+
+        let mut id = ColumnDef::new_with_type(
+            sessions::Column::Id,
+            ColumnType::String(sea_query::StringLen::N(self.id_length())),
+        );
+        id.not_null();
+
+        //a partitioned table only takes a primary key that includes the expiry, see
+        //with_partitioning()
+        if !self.partitioned() {
+            //left to the backend to name, the migration does the same so both end up with the
+            //same constraint
+            id.primary_key();
+        }
+
+        let mut create_table = Table::create();
+        create_table
+            .if_not_exists()
+            .table(Alias::new(table_name))
+            .col(id)
+            //NULL for sessions that never expire
+            .col(ColumnDef::new_with_type(
+                sessions::Column::Expires,
+                self.timestamp_type(),
+            ))
+            .col(
+                ColumnDef::new_with_type(
+                    sessions::Column::Session,
+                    if self.jsonb() {
+                        ColumnType::JsonBinary
+                    } else {
+                        ColumnType::Text
+                    },
+                )
+                .not_null(),
+            );
+
+        for &column in self.metadata_columns() {
+            create_table.col(self.metadata_column(column));
+        }
+
+        let create_table = builder.build(&create_table);
+
+        //sea_query has no PARTITION BY
+        #[cfg(feature = "postgres")]
+        let create_table = if self.partitioned() {
+            Statement {
+                sql: format!(r#"{} PARTITION BY RANGE ("expires")"#, create_table.sql),
+                ..create_table
+            }
+        } else {
+            create_table
+        };
+
+        //nothing written to it survives a crash, in exchange writes skip the WAL. sea_query has no
+        //UNLOGGED either
+        #[cfg(feature = "postgres")]
+        let create_table = if self.unlogged() {
+            Statement {
+                sql: create_table
+                    .sql
+                    .replacen("CREATE TABLE", "CREATE UNLOGGED TABLE", 1),
+                ..create_table
+            }
+        } else {
+            create_table
+        };
+
+        self.pool
+            .execute(create_table)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        #[cfg(feature = "postgres")]
+        if self.partitioned() {
+            self.create_default_partition(table_name, &context).await?;
+        }
+
+        let columns = self.column_types(table_name, &context).await?;
+
+        //tables created before the metadata columns existed get them added, stamped with the
+        //current time for existing rows. the epoch layout is left as it is
+        let mut added = Query::update();
+        let mut backfill = false;
+        for &column in self.metadata_columns() {
+            if !columns.contains_key(&sea_query::Iden::to_string(&column)) {
+                let add_column = builder.build(
+                    Table::alter()
+                        .table(Alias::new(table_name))
+                        .add_column(self.metadata_column(column)),
+                );
+
+                self.pool
+                    .execute(add_column)
+                    .traced()
+                    .await
+                    .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+                added.value(column, self.to_db_datetime(Some(self.now())));
+                backfill = true;
+            }
+        }
+
+        //the default fills them everywhere but on SQLite, see metadata_column()
+        if builder == DbBackend::Sqlite && backfill {
+            self.pool
+                .execute(builder.build(added.table(Alias::new(table_name))))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+        }
+
+        //NULL for sessions stored without store_with_user()
+        if self.metadata() && !columns.contains_key("user_id") {
+            let add_column =
+                builder.build(Table::alter().table(Alias::new(table_name)).add_column(
+                    ColumnDef::new_with_type(
+                        sessions::Column::UserId,
+                        ColumnType::String(sea_query::StringLen::None),
+                    ),
+                ));
+
+            self.pool
+                .execute(add_column)
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+        }
+
+        if self.soft_delete() && !columns.contains_key("deleted_at") {
+            let add_column =
+                builder.build(Table::alter().table(Alias::new(table_name)).add_column(
+                    ColumnDef::new_with_type(sessions::Column::DeletedAt, self.timestamp_type()),
+                ));
+
+            self.pool
+                .execute(add_column)
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+        }
+
+        if self.schema_check {
+            self.check_schema(table_name, &columns, &context)?;
+        }
+
+        let create_index = builder.build(
+            &Index::create()
+                .if_not_exists()
+                .name(
+                    self.expires_index_name
+                        .clone()
+                        .unwrap_or_else(|| format!("{table_name}_expires_idx")),
+                )
+                .table(Alias::new(table_name))
+                //with the id in it, the sweep and get_ids() are answered from the index alone
+                .col(sessions::Column::Expires)
+                .col(sessions::Column::Id)
+                .to_owned(),
+        );
+
+        self.pool
+            .execute(create_index)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        if self.metadata() {
+            let create_user_index = builder.build(
+                &Index::create()
+                    .if_not_exists()
+                    .name(format!("{table_name}_user_id_idx"))
+                    .table(Alias::new(table_name))
+                    .col(sessions::Column::UserId)
+                    .to_owned(),
+            );
+
+            self.pool
+                .execute(create_user_index)
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+        }
+
+        //what loads and stores find an id with in place of the primary key
+        if self.partitioned() {
+            let create_id_index = builder.build(
+                &Index::create()
+                    .if_not_exists()
+                    .name(format!("{table_name}_id_idx"))
+                    .table(Alias::new(table_name))
+                    .col(sessions::Column::Id)
+                    .to_owned(),
+            );
+
+            self.pool
+                .execute(create_id_index)
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+        }
+
+        if self.tombstone_grace.is_some() {
+            self.create_expiring_table(&tombstone_table(table_name), &context)
+                .await?;
+        }
+
+        if self.archival {
+            self.create_archive_table(table_name, &context).await?;
+        }
+
+        if self.cleanup_lease.is_some() && builder != DbBackend::Postgres {
+            self.create_expiring_table(CLEANUP_LEASES, &context).await?;
+        }
+
+        // use sea_orm_migration::{MigrationTrait, SchemaManager};
+        // let manager = SchemaManager::new(&self.pool);
+        // crate::migration::Migration
+        //     .up(&manager)
+        //     .await
+        //     .map_err(|err| DatabaseError::GenericCreateError(err.to_string()))?;
+
+        // sqlx::query(
+        //     &r#"
+        //     CREATE TABLE IF NOT EXISTS %%TABLE_NAME%% (
+        //         "id" VARCHAR(128) NOT NULL PRIMARY KEY,
+        //         "expires" BIGINT NULL,
+        //         "session" TEXT NOT NULL
+        //     )
+        // "#
+        //     .replace("%%TABLE_NAME%%", table_name),
+        // )
+        // .execute(&self.pool)
+        // .await
+        // .map_err(|err| DatabaseError::GenericCreateError(err.to_string()))?;
+
+        Ok(())
+    }
+
+    //holds the sessions that never expire or expire on a day without a partition of its own
+    #[cfg(feature = "postgres")]
+    async fn create_default_partition(
+        &self,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<(), SessionError> {
+        let row = self
+            .pool
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT relkind::TEXT AS relkind FROM pg_class WHERE oid = to_regclass($1)",
+                [quoted(table_name).into()],
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        let relkind = row
+            .map(|row| row.try_get::<String>("", "relkind"))
+            .transpose()
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        //CREATE TABLE IF NOT EXISTS kept a table created without partitions
+        if relkind.as_deref() != Some("p") {
+            return Err(context.setup(
+                "the table is not partitioned, with_partitioning() needs a table created with it",
+            ));
+        }
+
+        self.pool
+            .execute(Statement::from_string(
+                DbBackend::Postgres,
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {} PARTITION OF {} DEFAULT",
+                    quoted(&default_partition(table_name)),
+                    quoted(table_name)
+                ),
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        Ok(())
+    }
+
+    //false when the partition of `day` exists already. the sessions stored for the day before it
+    //did are in the default partition, which Postgres requires to hold none of them on attach
+    #[cfg(feature = "postgres")]
+    async fn create_partition(
+        &self,
+        day: chrono::NaiveDate,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<bool, SessionError> {
+        let partition = day_partition(table_name, day);
+        let start = day.and_time(chrono::NaiveTime::MIN).and_utc();
+        let end = start + Duration::days(1);
+
+        let txn = self
+            .pool
+            .begin()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        //two instances creating it at once would both move the sessions
+        self.advisory_lock(&txn, &partition, ErrorKind::Create, context)
+            .await?;
+
+        if self
+            .relation_exists(&txn, &partition, ErrorKind::Create, context)
+            .await?
+        {
+            txn.commit()
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+            return Ok(false);
+        }
+
+        let statements = [
+            Statement::from_string(
+                DbBackend::Postgres,
+                format!(
+                    "CREATE TABLE {} (LIKE {} INCLUDING DEFAULTS)",
+                    quoted(&partition),
+                    quoted(table_name)
+                ),
+            ),
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"WITH moved AS (DELETE FROM {} WHERE "expires" >= $1 AND "expires" < $2 RETURNING *) INSERT INTO {} SELECT * FROM moved"#,
+                    quoted(&default_partition(table_name)),
+                    quoted(&partition)
+                ),
+                [
+                    self.to_db_datetime(Some(start)),
+                    self.to_db_datetime(Some(end)),
+                ],
+            ),
+            //the bounds can not be bind parameters
+            Statement::from_string(
+                DbBackend::Postgres,
+                format!(
+                    "ALTER TABLE {} ATTACH PARTITION {} FOR VALUES FROM ({}) TO ({})",
+                    quoted(table_name),
+                    quoted(&partition),
+                    self.partition_bound(start),
+                    self.partition_bound(end)
+                ),
+            ),
+        ];
+
+        for statement in statements {
+            txn.execute(statement)
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Create, err))?;
+        }
+
+        txn.commit()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        Ok(true)
+    }
+
+    //a partition bound as an SQL literal of the expires column's type
+    #[cfg(feature = "postgres")]
+    fn partition_bound(&self, datetime: DateTime<Utc>) -> String {
+        if self.epoch_expires {
+            datetime.timestamp().to_string()
+        } else {
+            format!("'{}'", datetime.format("%Y-%m-%d %H:%M:%S+00"))
+        }
+    }
+
+    //the partitions of days that are over only hold expired sessions, dropping them replaces
+    //deleting their rows. returns the ids they held, at most `limit`
+    #[cfg(feature = "postgres")]
+    async fn drop_expired_partitions(
+        &self,
+        table_name: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<String>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("delete_by_expiry", table_name);
+
+        let rows = self
+            .pool
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT c.relname::TEXT AS name FROM pg_inherits i \
+                 JOIN pg_class c ON c.oid = i.inhrelid WHERE i.inhparent = to_regclass($1)",
+                [quoted(table_name).into()],
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        let prefix = format!("{table_name}_p");
+        let today = self.now().date_naive();
+        let mut ids = Vec::new();
+
+        for row in rows {
+            let name = row
+                .try_get::<String>("", "name")
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+            let over = name
+                .strip_prefix(&prefix)
+                .and_then(|day| chrono::NaiveDate::parse_from_str(day, "%Y%m%d").ok())
+                .is_some_and(|day| day < today);
+
+            let remaining = limit.map(|limit| limit.saturating_sub(ids.len()));
+
+            if over {
+                ids.extend(self.drop_partition(&name, remaining, &context).await?);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    //a partition holding more than `limit` sessions is kept, the sweep deletes them in chunks
+    #[cfg(feature = "postgres")]
+    async fn drop_partition(
+        &self,
+        partition: &str,
+        limit: Option<usize>,
+        context: &Context<'_>,
+    ) -> Result<Vec<String>, SessionError> {
+        let txn = self
+            .pool
+            .begin()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        //another instance may have dropped it since it was listed
+        self.advisory_lock(&txn, partition, ErrorKind::Delete, context)
+            .await?;
+
+        if !self
+            .relation_exists(&txn, partition, ErrorKind::Delete, context)
+            .await?
+        {
+            txn.commit()
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+            return Ok(Vec::new());
+        }
+
+        //locked first so no write lands in it between reading the ids and the drop
+        txn.execute(Statement::from_string(
+            DbBackend::Postgres,
+            format!("LOCK TABLE {} IN ACCESS EXCLUSIVE MODE", quoted(partition)),
+        ))
+        .traced()
+        .await
+        .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        let rows = txn
+            .query_all(Statement::from_string(
+                DbBackend::Postgres,
+                format!(r#"SELECT "id" FROM {}"#, quoted(partition)),
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        let ids = ids_from_rows(&rows, context)?;
+
+        if limit.is_some_and(|limit| ids.len() > limit) {
+            txn.rollback()
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+            return Ok(Vec::new());
+        }
+
+        txn.execute(Statement::from_string(
+            DbBackend::Postgres,
+            format!("DROP TABLE {}", quoted(partition)),
+        ))
+        .traced()
+        .await
+        .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        txn.commit()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        Ok(ids)
+    }
+
+    //store() and store_many() on a partitioned table, which has no unique id to upsert against.
+    //the lock per id keeps two stores of a new session from both inserting it, taken in id
+    //order so two batches can not wait on each other
+    #[cfg(feature = "postgres")]
+    async fn store_partitioned(
+        &self,
+        mut rows: Vec<(&str, Value, Value)>,
+        user_id: Option<&str>,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<(), SessionError> {
+        let builder = self.pool.get_database_backend();
+        let now = self.to_db_datetime(Some(self.now()));
+        rows.sort_unstable_by_key(|&(id, _, _)| id);
+
+        let txn = self
+            .pool
+            .begin()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        for (id, expires, session) in rows {
+            let context = context.with_id(id);
+            self.advisory_lock(
+                &txn,
+                &format!("{table_name}:{id}"),
+                ErrorKind::Insert,
+                &context,
+            )
+            .await?;
+
+            let mut update = Query::update();
+            update
+                .table(Alias::new(table_name))
+                .value(
+                    sessions::Column::Expires,
+                    if self.monotonic_expiry {
+                        later_of(
+                            Expr::col(sessions::Column::Expires).into(),
+                            Expr::val(expires.clone()).into(),
+                        )
+                    } else {
+                        Expr::val(expires.clone()).into()
+                    },
+                )
+                .value(sessions::Column::Session, session.clone())
+                .and_where(Expr::col(sessions::Column::Id).eq(id));
+
+            if self.metadata() {
+                update.value(sessions::Column::UpdatedAt, now.clone());
+            }
+
+            if let Some(user_id) = user_id {
+                update.value(sessions::Column::UserId, user_id);
+            }
+
+            //a marked row is stored again like a new one would be
+            if self.soft_delete() {
+                update.value(sessions::Column::DeletedAt, self.to_db_datetime(None));
+            }
+
+            //a logged out session stays dead, see with_tombstones()
+            if self.tombstone_grace.is_some() {
+                update.and_where(self.unburied(self.unburied_values(id), table_name));
+            }
+
+            let updated = txn
+                .execute(builder.build(&update))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+            if updated.rows_affected() > 0 {
+                continue;
+            }
+
+            let row = [id.into(), expires.into(), session.into()]
+                .into_iter()
+                .chain(self.metadata_columns().iter().map(|_| now.clone().into()))
+                .chain(user_id.map(SimpleExpr::from));
+
+            let mut insert = Query::insert();
+            insert.into_table(Alias::new(table_name)).columns(
+                COLUMNS
+                    .into_iter()
+                    .chain(self.metadata_columns().iter().copied())
+                    .chain(user_id.map(|_| sessions::Column::UserId)),
+            );
+
+            if self.tombstone_grace.is_some() {
+                insert.select_from(self.unburied_row(row, self.unburied_values(id), table_name))
+            } else {
+                insert.values(row)
+            }
+            .map_err(|err| context.query(ErrorKind::Insert, err))?;
+
+            txn.execute(builder.build(&insert))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Insert, err))?;
+        }
+
+        txn.commit()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        Ok(())
+    }
+
+    //held until the transaction ends
+    #[cfg(feature = "postgres")]
+    async fn advisory_lock(
+        &self,
+        txn: &DatabaseTransaction,
+        key: &str,
+        kind: ErrorKind,
+        context: &Context<'_>,
+    ) -> Result<(), SessionError> {
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_advisory_xact_lock(hashtext($1))",
+            [key.into()],
+        ))
+        .traced()
+        .await
+        .map_err(|err| context.db(kind, err))?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn relation_exists(
+        &self,
+        txn: &DatabaseTransaction,
+        name: &str,
+        kind: ErrorKind,
+        context: &Context<'_>,
+    ) -> Result<bool, SessionError> {
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT to_regclass($1) IS NOT NULL AS present",
+                [quoted(name).into()],
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(kind, err))?;
+
+        row.map(|row| row.try_get::<bool>("", "present"))
+            .transpose()
+            .map(|present| present.unwrap_or(false))
+            .map_err(|err| context.db(kind, err))
+    }
+
+    async fn live_exists(&self, id: &str, table_name: &str) -> Result<bool, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("exists", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
+
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+
+        let statement = self.statements.statement(
+            builder,
+            ("exists", self.statement_options(false)),
+            table_name,
+            vec![
+                1.into(),
+                id.into(),
+                self.to_db_datetime(Some(now)),
+                1u64.into(),
+            ],
+            |_| {
+                Ok(builder.build(
+                    //SELECT 1 ... LIMIT 1, the lookup stops at the first match instead of aggregating
+                    Query::select()
+                        .expr(Expr::val(1))
+                        .from(Alias::new(table_name))
+                        .and_where(Expr::col(sessions::Column::Id).eq(id))
+                        .and_where(self.is_live(now))
+                        .limit(1),
+                ))
+            },
+        )?;
+
+        let row = self
+            .reader()
+            .query_one(statement)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        // let result: Option<(i64,)> = sqlx::query_as(
+        //     &r#"
+        //     SELECT COUNT(*) FROM %%TABLE_NAME%%
+        //     WHERE id = $1 AND (expires IS NULL OR expires > $2)
+        // "#
+        //     .replace("%%TABLE_NAME%%", table_name),
+        // )
+        // .bind(id)
+        // .bind(Utc::now().timestamp())
+        // .fetch_optional(&self.pool)
+        // .await
+        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+
+        // Ok(result.map(|(o,)| o).unwrap_or(0) > 0)
+        Ok(row.is_some())
+    }
+
+    async fn live_ids(&self, table_name: &str) -> Result<Vec<String>, SessionError> {
+        //queued sessions are live as well
+        self.flush().await?;
+
+        let table_name = self.table_name(table_name);
+        let context = Context::new("get_ids", table_name);
+        let builder = self.pool.get_database_backend();
+
+        let rows = self
+            .reader()
+            .query_all(
+                builder.build(
+                    //only the id, the session payloads can be large
+                    Query::select()
+                        .column(sessions::Column::Id)
+                        .from(Alias::new(table_name))
+                        .and_where(self.is_live(self.now())),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        let result = ids_from_rows(&rows, &context)?;
+
+        // let result: Vec<(String,)> = sqlx::query_as(
+        //     &r#"
+        //     SELECT id FROM %%TABLE_NAME%%
+        //     WHERE (expires IS NULL OR expires > $1)
+        // "#
+        //     .replace("%%TABLE_NAME%%", table_name),
+        // )
+        // .bind(Utc::now().timestamp())
+        // .fetch_all(&self.pool)
+        // .await
+        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+
+        // let result: Vec<String> = result.into_iter().map(|(s,)| s).collect();
+
+        Ok(result)
+    }
+
+    async fn ping(&self) -> Result<(), SessionError> {
+        let context = Context::new("connect", self.table_name(crate::TABLE_NAME));
+        let builder = self.pool.get_database_backend();
+
+        self.pool
+            .query_one(builder.build(Query::select().expr(Expr::val(1))))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Acquire, err))?;
+
+        Ok(())
+    }
+
+    //runs a write again while it fails with a busy database, see with_busy_retries(), or while
+    //no connection could be acquired, see DbPoolBuilder::max_retries()
+    async fn retry_busy<T, F, Fut>(&self, write: F) -> Result<T, SessionError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, SessionError>>,
+    {
+        let busy_retries =
+            self.busy_retries
+                .unwrap_or(if self.pool.get_database_backend() == DbBackend::Sqlite {
+                    DEFAULT_SQLITE_BUSY_RETRIES
+                } else {
+                    0
+                });
+
+        self.retry(busy_retries, write).await
+    }
+
+    //runs a read again while no connection could be acquired, see DbPoolBuilder::max_retries()
+    async fn retry_acquire<T, F, Fut>(&self, read: F) -> Result<T, SessionError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, SessionError>>,
+    {
+        self.retry(0, read).await
+    }
+
+    async fn retry<T, F, Fut>(
+        &self,
+        mut busy_retries: u32,
+        mut operation: F,
+    ) -> Result<T, SessionError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, SessionError>>,
+    {
+        let mut acquire_retries = self.acquire_retries;
+        let mut busy_delay = BUSY_RETRY_DELAY;
+        let mut acquire_delay = self.acquire_backoff.unwrap_or(DEFAULT_ACQUIRE_BACKOFF);
+
+        loop {
+            match operation().await {
+                Err(SessionError::Db { source, .. }) if busy_retries > 0 && is_busy(&source) => {
+                    busy_retries -= 1;
+                    tokio::time::sleep(busy_delay).await;
+                    busy_delay = busy_delay.saturating_mul(2);
+                }
+                //the pool was exhausted or the database did not accept a connection in time,
+                //both may pass, any other error is returned right away
+                Err(SessionError::Db {
+                    source: DbErr::ConnectionAcquire(_),
+                    ..
+                }) if acquire_retries > 0 => {
+                    acquire_retries -= 1;
+                    tokio::time::sleep(acquire_delay).await;
+                    acquire_delay = acquire_delay.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    //one attempt at store(), see retry_busy()
+    async fn store_once(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        user_id: Option<&str>,
+        table_name: &str,
+    ) -> Result<(), SessionError> {
+        let table_name = self.table_name(table_name);
+        //https://www.sea-ql.org/SeaORM/docs/basic-crud/update/
+        //https://www.sea-ql.org/SeaORM/docs/basic-crud/insert/
+
+        let context = Context::new("store", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Insert)?;
+        self.check_monotonic(&context)?;
+
+        //an empty session is not worth a row, drop the one written while it still had data
+        if self.is_empty(session) {
+            self.delete_row(&self.pool, id, table_name, &context)
+                .await?;
+            return Ok(());
+        }
+
+        //should be seconds since 1970-01-01 00:00:00 UTC
+        let expires = self.parse_expires(expires, &context)?;
+        let session = self.encode_session(session, &context)?;
+
+        #[cfg(feature = "postgres")]
+        if self.partitioned() {
+            let row = (
+                id,
+                self.to_db_datetime(expires),
+                self.session_value(session),
+            );
+            self.store_partitioned(vec![row], user_id, table_name, &context)
+                .await?;
+            return Ok(());
+        }
+
+        //the upsert is rendered for the connected backend, ON DUPLICATE KEY UPDATE on MySQL
+        //and ON CONFLICT (id) DO UPDATE on Postgres and SQLite
+        let builder = self.pool.get_database_backend();
+        let now = self.to_db_datetime(Some(self.now()));
+
+        let mut values: Vec<Value> = vec![
+            id.into(),
+            self.to_db_datetime(expires),
+            self.session_value(session),
+        ];
+        values.extend(self.metadata_columns().iter().map(|_| now.clone()));
+
+        //only written when given, a plain store() keeps the owner set before
+        if let Some(user_id) = user_id {
+            values.push(user_id.into());
+        }
+
+        let inserted = values.len();
+
+        //the row is only selected when the id has no tombstone, see with_tombstones()
+        let unburied = self.tombstone_grace.map(|_| self.unburied_values(id));
+        values.extend(unburied.iter().flatten().cloned());
+
+        //the upsert binds the NULL that clears deleted_at after the inserted row
+        if self.soft_delete() {
+            values.push(self.to_db_datetime(None));
+        }
+
+        //only runs when the SQL is not cached yet, the payload is copied for sea_query then
+        let build = |values: &[Value]| {
+            let mut upsert = self.upsert(table_name);
+
+            if user_id.is_some() {
+                upsert.update_column(sessions::Column::UserId);
+            }
+
+            let row = values.iter().take(inserted).cloned().map(SimpleExpr::from);
+            let mut insert = Query::insert();
+            insert.into_table(Alias::new(table_name)).columns(
+                COLUMNS
+                    .into_iter()
+                    .chain(self.metadata_columns().iter().copied())
+                    .chain(user_id.map(|_| sessions::Column::UserId)),
+            );
+
+            if let Some(unburied) = unburied.clone() {
+                insert.select_from(self.unburied_row(row, unburied, table_name))
+            } else {
+                insert.values(row)
+            }
+            .map_err(|err| context.query(ErrorKind::Insert, err))?;
+
+            Ok(builder.build(insert.on_conflict(upsert)))
+        };
+
+        let statement = self.statements.statement(
+            builder,
+            ("store", self.statement_options(user_id.is_some())),
+            table_name,
+            values,
+            build,
+        )?;
+
+        //executed as a plain statement on purpose, Entity::insert().exec() reports
+        //DbErr::RecordNotInserted when the upsert leaves an identical row untouched
+        self.pool
+            .execute(statement)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        //     sqlx::query(
+        //         &r#"
+        //     INSERT INTO %%TABLE_NAME%%
+        //         (id, session, expires) SELECT $1, $2, $3
+        //     ON CONFLICT(id) DO UPDATE SET
+        //         expires = EXCLUDED.expires,
+        //         session = EXCLUDED.session
+        // "#
+        //         .replace("%%TABLE_NAME%%", table_name),
+        //     )
+        //     .bind(id)
+        //     .bind(session)
+        //     .bind(expires)
+        //     .execute(&self.pool)
+        //     .await
+        //     .map_err(|err| DatabaseError::GenericInsertError(err.to_string()))?;
+        Ok(())
+    }
+
+    //one attempt at purge_expired()
+    async fn purge_expired_once(&self, table_name: &str) -> Result<u64, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("purge_expired", table_name);
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+
+        let result = self
+            .pool
+            .execute(self.removal(table_name, self.is_expired(now), false))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        if self.tombstone_grace.is_some() {
+            self.pool
+                .execute(
+                    builder.build(
+                        Query::delete()
+                            .from_table(Alias::new(tombstone_table(table_name)))
+                            .and_where(self.is_expired(now)),
+                    ),
+                )
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+        }
+
+        Ok(result.rows_affected())
+    }
+
+    //delete_by_expiry(), one transaction per chunk so a large backlog does not hold a long
+    //transaction, see with_cleanup_chunk_size()
+    async fn sweep(&self, table_name: &str) -> Result<Vec<String>, SessionError> {
+        //without RETURNING the chunk's ids are bound in an IN list, see sweep_chunk()
+        let chunk_size = self
+            .cleanup_chunk_size
+            .unwrap_or(DEFAULT_CLEANUP_CHUNK_SIZE)
+            .clamp(1, self.in_list_length());
+        let mut result = Vec::new();
+        let mut first = true;
+
+        //whole partitions first, the chunks then only find what expired today or in the default
+        //partition
+        #[cfg(feature = "postgres")]
+        if self.partitioned() && !self.soft_delete() && !self.archival {
+            result = self
+                .drop_expired_partitions(table_name, self.cleanup_batch_limit)
+                .await?;
+        }
+
+        loop {
+            //what is left of the batch limit, see with_cleanup_batch_limit()
+            let limit = match self.cleanup_batch_limit {
+                Some(batch_limit) => chunk_size.min(batch_limit.saturating_sub(result.len())),
+                None => chunk_size,
+            };
+
+            if limit == 0 {
+                break;
+            }
+
+            let Some(ids) = self
+                .retry_busy(|| self.sweep_chunk(table_name, limit, first))
+                .await?
+            else {
+                break;
+            };
+
+            let done = ids.len() < limit;
+            result.extend(ids);
+            first = false;
+
+            if done {
+                break;
+            }
+
+            if let Some(pause) = self.cleanup_chunk_pause {
+                tokio::time::sleep(pause).await;
+            }
+        }
+
+        self.forget_writes(result.iter().map(String::as_str));
+        Ok(result)
+    }
+
+    //deletes up to `limit` expired sessions, None when another instance holds the cleanup lease
+    async fn sweep_chunk(
+        &self,
+        table_name: &str,
+        limit: usize,
+        first: bool,
+    ) -> Result<Option<Vec<String>>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("delete_by_expiry", table_name);
+        //a NULL expires means the session never expires, so it is neither returned nor deleted
+        let builder = self.pool.get_database_backend();
+        let now = self.now();
+
+        let txn = self
+            .pool
+            .begin()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        //the lease row outlives the transaction, the advisory lock on Postgres is taken again for
+        //every chunk and the sweep stops once another instance got it in between
+        if let Some(lease) = self.cleanup_lease {
+            if (first || builder == DbBackend::Postgres)
+                && !self
+                    .claim_cleanup(&txn, lease, table_name, &context)
+                    .await?
+            {
+                txn.rollback()
+                    .traced()
+                    .await
+                    .map_err(|err| context.db(ErrorKind::Delete, err))?;
+                return Ok(None);
+            }
+        }
+
+        let mut chunk = Query::select()
+            .column(sessions::Column::Id)
+            .from(Alias::new(table_name))
+            .and_where(self.is_expired(now))
+            .and_where_option(self.not_deleted())
+            .limit(limit as u64)
+            .to_owned();
+
+        //a renewal can not slip in between the select and the delete, which would skip the
+        //renewed session but still return its id. SQLite has no row locks and holds the whole
+        //database for the write instead
+        if self.archival || builder == DbBackend::MySql {
+            chunk.lock(LockType::Update);
+        }
+
+        //Postgres and SQLite 3.35+ delete and return the ids in one statement, MySQL has no
+        //RETURNING and selects the ids first, deleting only those inside the same transaction.
+        //archival always selects first, the copy has to go in before the delete
+        let result = if !self.archival && self.supports_returning(&txn, &context).await? {
+            let rows = txn
+                .query_all(
+                    self.removal(
+                        table_name,
+                        Expr::col(sessions::Column::Id)
+                            .in_subquery(chunk)
+                            .and(self.is_expired(now)),
+                        true,
+                    ),
+                )
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+            ids_from_rows(&rows, &context)?
+        } else {
+            let rows = txn
+                .query_all(builder.build(&chunk))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+            let result = ids_from_rows(&rows, &context)?;
+
+            if self.archival && !result.is_empty() {
+                txn.execute(self.archive(table_name, &result, now, &context)?)
+                    .traced()
+                    .await
+                    .map_err(|err| context.db(ErrorKind::Delete, err))?;
+            }
+
+            //only delete what was selected, the expires check stays for SQLite, which locks no rows
+            if !result.is_empty() {
+                txn.execute(
+                    self.removal(
+                        table_name,
+                        Expr::col(sessions::Column::Id)
+                            .is_in(result.clone())
+                            .and(self.is_expired(now)),
+                        false,
+                    ),
+                )
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+            }
+
+            result
+        };
+
+        if first && self.tombstone_grace.is_some() {
+            txn.execute(
+                builder.build(
+                    Query::delete()
+                        .from_table(Alias::new(tombstone_table(table_name)))
+                        .and_where(self.is_expired(now)),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+        }
+
+        txn.commit()
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        // sqlx::query(
+        //     &r#"DELETE FROM %%TABLE_NAME%% WHERE expires < $1"#
+        //         .replace("%%TABLE_NAME%%", table_name),
+        // )
+        // .bind(Utc::now().timestamp())
+        // .execute(&self.pool)
+        // .await
+        // .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+
+        Ok(Some(result))
+    }
+
+    //whether DELETE ... RETURNING can be used, on SQLite only with the sqlite feature
+    async fn supports_returning(
+        &self,
+        txn: &DatabaseTransaction,
+        context: &Context<'_>,
+    ) -> Result<bool, SessionError> {
+        match self.pool.get_database_backend() {
+            DbBackend::Postgres => Ok(true),
+            DbBackend::MySql => Ok(false),
+            #[cfg(feature = "sqlite")]
+            DbBackend::Sqlite => self.sqlite_returning(txn, context).await,
+            #[cfg(not(feature = "sqlite"))]
+            DbBackend::Sqlite => {
+                let _ = (txn, context);
+                Ok(false)
+            }
+        }
+    }
+
+    //the SQLite version is asked for once per pool
+    #[cfg(feature = "sqlite")]
+    async fn sqlite_returning(
+        &self,
+        txn: &DatabaseTransaction,
+        context: &Context<'_>,
+    ) -> Result<bool, SessionError> {
+        if let Some(supported) = self.sqlite_returning.get() {
+            return Ok(*supported);
+        }
+
+        let row = txn
+            .query_one(Statement::from_string(
+                DbBackend::Sqlite,
+                "SELECT sqlite_version()",
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        let version = row
+            .map(|row| row.try_get_by_index::<String>(0))
+            .transpose()
+            .map_err(|err| context.db(ErrorKind::Select, err))?
+            .unwrap_or_default();
+
+        let mut parts = version
+            .split('.')
+            .map(|part| part.parse::<u32>().unwrap_or(0));
+        let major = parts.next().unwrap_or(0);
+        let minor = parts.next().unwrap_or(0);
+
+        //RETURNING arrived in 3.35.0
+        Ok(*self
+            .sqlite_returning
+            .get_or_init(|| (major, minor) >= (3, 35)))
+    }
+
+    //one attempt at delete_all()
+    async fn clear(&self, table_name: &str) -> Result<(), SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("delete_all", table_name);
+        let builder = self.pool.get_database_backend();
+
+        if let Some(filter) = &self.write_filter {
+            filter.clear();
+        }
+
+        let _flushing = match &self.write_behind {
+            Some(queue) => {
+                let flushing = queue.flushing.lock().await;
+                queue.remove_table(table_name);
+                Some(flushing)
+            }
+            None => None,
+        };
+
+        let statement = if self.truncate_on_clear && builder != DbBackend::Sqlite {
+            builder.build(Table::truncate().table(Alias::new(table_name)))
+        } else {
+            builder.build(Query::delete().from_table(Alias::new(table_name)))
+        };
+
+        self.pool
+            .execute(statement)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        // sqlx::query(&r#"DELETE FROM %%TABLE_NAME%%"#.replace("%%TABLE_NAME%%", table_name))
+        //     .execute(&self.pool)
+        //     .await
+        //     .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+        Ok(())
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock
+            .as_ref()
+            .map_or_else(Utc::now, |clock| clock.now())
+    }
+
+    async fn delete_row(
+        &self,
+        db: &impl ConnectionTrait,
+        id: &str,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<bool, SessionError> {
+        let result = db
+            .execute(self.removal(table_name, Expr::col(sessions::Column::Id).eq(id), false))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    //false outside a tokio runtime, store() then writes right away
+    fn start_write_behind(&self, queue: &Arc<WriteBehind>) -> bool {
+        if queue.started() {
+            return true;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return false;
+        };
+
+        if !queue.start() {
+            return true;
+        }
+
+        let weak = Arc::downgrade(queue);
+        let interval = queue.interval;
+
+        //a clone without the queue, the task must not keep the pools using it alive
+        let writer = DbPool {
+            write_filter: None,
+            write_behind: None,
+            ..self.clone()
+        };
+
+        runtime.spawn(async move {
+            while let Some(queue) = weak.upgrade() {
+                //a timeout only means the interval is over
+                let _ = tokio::time::timeout(interval, queue.batch_ready()).await;
+
+                //left queued, the next flush tries again
+                if let Err(err) = writer.flush_queue(&queue).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("write-behind flush failed: {err}");
+
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = err;
+                }
+            }
+        });
+
+        true
+    }
+
+    //writes what is queued right now, see with_write_behind()
+    async fn flush_queue(&self, queue: &WriteBehind) -> Result<(), SessionError> {
+        let _flushing = queue.flushing.lock().await;
+
+        let mut tables: HashMap<String, Vec<(String, String, i64)>> = HashMap::new();
+        for ((table_name, id), (session, expires)) in queue.snapshot() {
+            tables
+                .entry(table_name)
+                .or_default()
+                .push((id, session, expires));
+        }
+
+        for (table_name, sessions) in tables {
+            let batch: Vec<(&str, &str, i64)> = sessions
+                .iter()
+                .map(|(id, session, expires)| (id.as_str(), session.as_str(), *expires))
+                .collect();
+
+            self.retry_busy(|| self.write_many(&batch, &table_name))
+                .await?;
+            queue.written(&table_name, &sessions);
+
+            #[cfg(feature = "pg_notify")]
+            if queue
+                .notify_writes
+                .load(std::sync::atomic::Ordering::Acquire)
+            {
+                for (id, _, _) in &sessions {
+                    crate::notifying_pool::notify(&self.pool, id, &table_name, ErrorKind::Insert)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    //drops the queued store() calls of ids about to be written or deleted right away, the guard
+    //keeps a flush from writing them back until the caller is done
+    async fn unqueue<'a>(
+        &self,
+        table_name: &str,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        let queue = self.write_behind.as_ref()?;
+        let flushing = queue.flushing.lock().await;
+        queue.remove(table_name, ids);
+        Some(flushing)
+    }
+
+    //a store() still in the write-behind queue, Some(None) when it leaves no live session
+    fn queued(&self, id: &str, table_name: &str) -> Option<Option<String>> {
+        self.queued_entry(id, table_name)
+            .map(|entry| entry.map(|(session, _)| session))
+    }
+
+    //queued() with the expiry the row will be written with
+    fn queued_entry(
+        &self,
+        id: &str,
+        table_name: &str,
+    ) -> Option<Option<(String, Option<DateTime<Utc>>)>> {
+        let table_name = self.table_name(table_name);
+        let (session, expires) = self.write_behind.as_ref()?.get(table_name, id)?;
+
+        let Ok(expires) = self.parse_expires(expires, &Context::new("load", table_name)) else {
+            return Some(None);
+        };
+        let live = expires.is_none_or(|expires| expires > self.now());
+
+        Some((live && !self.is_empty(&session)).then_some((session, expires)))
+    }
+
+    //the checks store_once() would fail on, made before a store() is queued
+    fn check_store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), SessionError> {
+        let context = Context::new("store", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Insert)?;
+        self.check_monotonic(&context)?;
+        self.parse_expires(expires, &context)?;
+        self.encode_session(session, &context)?;
+        Ok(())
+    }
+
+    //the primary, for pools layered on top that run their own statements
+    #[cfg(feature = "pg_notify")]
+    pub(crate) fn connection(&self) -> &DatabaseConnection {
+        &self.pool
+    }
+
+    #[cfg(feature = "pg_notify")]
+    pub(crate) fn with_write_notifications(mut self) -> DbPool {
+        if let Some(queue) = &self.write_behind {
+            queue
+                .notify_writes
+                .store(true, std::sync::atomic::Ordering::Release);
+        }
+
+        self.notify_writes = true;
+        self
+    }
+
+    //where the reads with_replica() moves off the primary run
+    fn reader(&self) -> &DatabaseConnection {
+        self.replica.as_ref().unwrap_or(&self.pool)
+    }
+
+    //whether load() found nothing for the id a moment ago, see with_miss_cache()
+    fn known_miss(&self, id: &str, table_name: &str) -> bool {
+        self.miss_cache
+            .as_ref()
+            .is_some_and(|misses| misses.contains(self.table_name(table_name), id))
+    }
+
+    //taken before the query a miss is then remembered for
+    fn miss_generation(&self) -> u64 {
+        self.miss_cache
+            .as_ref()
+            .map_or(0, |misses| misses.generation())
+    }
+
+    fn remember_miss(&self, id: &str, table_name: &str, since: u64) {
+        if let Some(misses) = &self.miss_cache {
+            misses.insert(self.table_name(table_name), id, since);
+        }
+    }
+
+    //called once the write is visible, a load that missed before it then does not cache the miss
+    fn forget_misses<'a>(&self, table_name: &str, ids: impl IntoIterator<Item = &'a str>) {
+        if let Some(misses) = &self.miss_cache {
+            misses.remove(table_name, ids);
+        }
+    }
+
+    //drops what with_skip_unchanged() remembers of ids whose rows change without store()
+    fn forget_writes<'a>(&self, ids: impl IntoIterator<Item = &'a str>) {
+        if let Some(filter) = &self.write_filter {
+            for id in ids {
+                filter.forget(id);
+            }
+        }
+    }
+
+    async fn bury(
+        &self,
+        db: &impl ConnectionTrait,
+        ids: &[&str],
+        grace: std::time::Duration,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<(), SessionError> {
+        let builder = self.pool.get_database_backend();
+
+        let until = self.deadline(grace);
+
+        let mut insert = Query::insert();
+        insert
+            .into_table(Alias::new(tombstone_table(table_name)))
+            .columns([sessions::Column::Id, sessions::Column::Expires]);
+
+        for &id in ids {
+            insert
+                .values([id.into(), self.to_db_datetime(until).into()])
+                .map_err(|err| context.query(ErrorKind::Insert, err))?;
+        }
+
+        let insert = insert
+            .on_conflict(
+                sea_query::OnConflict::column(sessions::Column::Id)
+                    .update_column(sessions::Column::Expires)
+                    .to_owned(),
+            )
+            .to_owned();
+
+        db.execute(builder.build(&insert))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Insert, err))?;
+
+        Ok(())
+    }
+
+    //whether this instance runs the sweep, see with_cleanup_lease()
+    async fn claim_cleanup(
+        &self,
+        txn: &DatabaseTransaction,
+        lease: std::time::Duration,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<bool, SessionError> {
+        let builder = self.pool.get_database_backend();
+
+        if builder == DbBackend::Postgres {
+            //released by the commit or rollback, an instance that finds it taken skips the sweep
+            let row = txn
+                .query_one(Statement::from_sql_and_values(
+                    builder,
+                    "SELECT pg_try_advisory_xact_lock(hashtext($1)) AS claimed",
+                    [table_name.into()],
+                ))
+                .traced()
+                .await
+                .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+            return row
+                .map(|row| row.try_get::<bool>("", "claimed"))
+                .transpose()
+                .map(|claimed| claimed.unwrap_or(false))
+                .map_err(|err| context.db(ErrorKind::Delete, err));
+        }
+
+        let now = self.now();
+
+        //the lease row is created already expired, so whoever gets here first can claim it
+        let insert = Query::insert()
+            .into_table(Alias::new(CLEANUP_LEASES))
+            .columns([sessions::Column::Id, sessions::Column::Expires])
+            .values([table_name.into(), self.to_db_datetime(Some(now)).into()])
+            .map_err(|err| context.query(ErrorKind::Delete, err))?
+            .on_conflict(
+                sea_query::OnConflict::column(sessions::Column::Id)
+                    .do_nothing_on([sessions::Column::Id])
+                    .to_owned(),
+            )
+            .to_owned();
+
+        txn.execute(builder.build(&insert))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        //a single conditional update, two instances can not both see the old lease as expired
+        let claimed = txn
+            .execute(
+                builder.build(
+                    Query::update()
+                        .table(Alias::new(CLEANUP_LEASES))
+                        .value(
+                            sessions::Column::Expires,
+                            self.to_db_datetime(self.deadline(lease)),
+                        )
+                        .and_where(Expr::col(sessions::Column::Id).eq(table_name))
+                        .and_where(self.is_expired(now)),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Delete, err))?;
+
+        Ok(claimed.rows_affected() > 0)
+    }
+
+    //which of the ids have a tombstone, checked before store_many() writes them
+    async fn buried<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<Vec<String>, SessionError> {
+        let builder = self.pool.get_database_backend();
+
+        let rows = self
+            .pool
+            .query_all(
+                builder.build(
+                    Query::select()
+                        .column(sessions::Column::Id)
+                        .from(Alias::new(tombstone_table(table_name)))
+                        .and_where(Expr::col(sessions::Column::Id).is_in(ids))
+                        .and_where(
+                            Expr::col(sessions::Column::Expires)
+                                .gt(self.to_db_datetime(Some(self.now()))),
+                        ),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        ids_from_rows(&rows, context)
+    }
+
+    //true unless the id has a tombstone, so a write under it is refused in the same statement.
+    //binds what unburied_values() returned
+    fn unburied(&self, [id, now]: [Value; 2], table_name: &str) -> SimpleExpr {
+        Expr::exists(
+            Query::select()
+                .expr(Expr::cust("1"))
+                .from(Alias::new(tombstone_table(table_name)))
+                .and_where(Expr::col(sessions::Column::Id).eq(id))
+                .and_where(Expr::col(sessions::Column::Expires).gt(now))
+                .to_owned(),
+        )
+        .not()
+    }
+
+    fn unburied_values(&self, id: &str) -> [Value; 2] {
+        [id.into(), self.to_db_datetime(Some(self.now()))]
+    }
+
+    //the row to insert as a SELECT that is empty when the id has a tombstone
+    fn unburied_row(
+        &self,
+        row: impl IntoIterator<Item = SimpleExpr>,
+        unburied: [Value; 2],
+        table_name: &str,
+    ) -> sea_query::SelectStatement {
+        let mut select = Query::select();
+        select
+            .exprs(row)
+            .and_where(self.unburied(unburied, table_name));
+
+        //MySQL only takes a WHERE after a FROM
+        if self.pool.get_database_backend() == DbBackend::MySql {
+            select.from(Dual);
+        }
+
+        select
+    }
+
+    //the expiry store() writes, None (NULL) when the session never expires
+    fn parse_expires(
+        &self,
+        expires: i64,
+        context: &Context,
+    ) -> Result<Option<DateTime<Utc>>, SessionError> {
+        let now = self.now();
+        let never_after = now.checked_add_signed(
+            self.never_expires_after
+                .unwrap_or(DEFAULT_NEVER_EXPIRES_AFTER),
+        );
+
+        let expires = parse_expires(expires, self.max_expires)
+            .map_err(|err| context.wrap(err))?
+            .filter(|expires| never_after.is_none_or(|never_after| *expires <= never_after));
+
+        Ok(clamp_expires(expires, now, self.max_lifetime))
+    }
+
+    //what store() overwrites when the id is already taken, created_at is kept
+    fn upsert(&self, table_name: &str) -> sea_query::OnConflict {
+        let mut on_conflict = sea_query::OnConflict::column(sessions::Column::Id);
+
+        if self.monotonic_upsert() {
+            on_conflict.value(sessions::Column::Expires, self.later_expiry(table_name));
+        } else {
+            on_conflict.update_column(sessions::Column::Expires);
+        }
+
+        //a marked row is stored again like a new one would be
+        if self.soft_delete() {
+            on_conflict.value(
+                sessions::Column::DeletedAt,
+                Expr::val(self.to_db_datetime(None)),
+            );
+        }
+
+        if self.metadata() {
+            on_conflict.update_column(sessions::Column::UpdatedAt);
+        }
+
+        on_conflict
+            .update_column(sessions::Column::Session)
+            .to_owned()
+    }
+
+    //the later of the stored and the incoming expiry, see with_monotonic_expiry()
+    fn later_expiry(&self, table_name: &str) -> SimpleExpr {
+        let stored: SimpleExpr =
+            Expr::col((Alias::new(table_name), sessions::Column::Expires)).into();
+        let incoming: SimpleExpr = match self.pool.get_database_backend() {
+            #[cfg(feature = "mysql")]
+            DbBackend::MySql => Expr::cust("VALUES(`expires`)"),
+            _ => Expr::col((Alias::new("excluded"), sessions::Column::Expires)).into(),
+        };
+
+        later_of(stored, incoming)
+    }
+
+    //now + after, capped at the latest timestamp every backend can store
+    fn deadline(&self, after: std::time::Duration) -> Option<DateTime<Utc>> {
+        Duration::from_std(after)
+            .ok()
+            .and_then(|after| self.now().checked_add_signed(after))
+            .filter(|deadline| deadline.timestamp() <= DEFAULT_MAX_EXPIRES)
+            .or_else(|| DateTime::from_timestamp(DEFAULT_MAX_EXPIRES, 0))
+    }
+
+    //the copy of the expiring rows delete_by_expiry() makes, see with_archival()
+    fn archive(
+        &self,
+        table_name: &str,
+        ids: &[String],
+        now: DateTime<Utc>,
+        context: &Context<'_>,
+    ) -> Result<Statement, SessionError> {
+        let builder = self.pool.get_database_backend();
+
+        let insert = Query::insert()
+            .into_table(Alias::new(archive_table(table_name)))
+            .columns(
+                self.archive_columns()
+                    .iter()
+                    .copied()
+                    .chain([sessions::Column::DeletedAt]),
+            )
+            .select_from(
+                Query::select()
+                    .columns(self.archive_columns().iter().copied())
+                    .expr(Expr::val(self.to_db_datetime(Some(now))))
+                    .from(Alias::new(table_name))
+                    .and_where(
+                        Expr::col(sessions::Column::Id)
+                            .is_in(ids.iter().map(String::as_str))
+                            .and(self.is_expired(now)),
+                    )
+                    .and_where_option(self.not_deleted())
+                    .to_owned(),
+            )
+            .map_err(|err| context.query(ErrorKind::Delete, err))?
+            .to_owned();
+
+        Ok(builder.build(&insert))
+    }
+
+    //the sessions columns plus deleted_at, which says when the row was archived. never UNLOGGED,
+    //the archive is kept for audits and has to survive a crash
+    async fn create_archive_table(
+        &self,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<(), SessionError> {
+        let builder = self.pool.get_database_backend();
+        let archive = archive_table(table_name);
+
+        let mut deleted_at =
+            ColumnDef::new_with_type(sessions::Column::DeletedAt, self.timestamp_type());
+        deleted_at.not_null();
+
+        //archive() always writes it, the default is for rows copied in by hand
+        if !self.epoch_expires {
+            deleted_at.default(Expr::current_timestamp());
+        }
+
+        let create_table = builder.build(
+            &Table::create()
+                .if_not_exists()
+                .table(Alias::new(&archive))
+                //not unique, an id that comes back after it expired is archived again
+                .col(
+                    ColumnDef::new_with_type(
+                        sessions::Column::Id,
+                        ColumnType::String(sea_query::StringLen::N(self.id_length())),
+                    )
+                    .not_null(),
+                )
+                .col(ColumnDef::new_with_type(
+                    sessions::Column::Expires,
+                    self.timestamp_type(),
+                ))
+                .col(
+                    ColumnDef::new_with_type(
+                        sessions::Column::Session,
+                        if self.jsonb() {
+                            ColumnType::JsonBinary
+                        } else {
+                            ColumnType::Text
+                        },
+                    )
+                    .not_null(),
+                )
+                .col(ColumnDef::new_with_type(
+                    sessions::Column::CreatedAt,
+                    self.timestamp_type(),
+                ))
+                .col(ColumnDef::new_with_type(
+                    sessions::Column::UpdatedAt,
+                    self.timestamp_type(),
+                ))
+                .col(ColumnDef::new_with_type(
+                    sessions::Column::UserId,
+                    ColumnType::String(sea_query::StringLen::None),
+                ))
+                .col(deleted_at)
+                .to_owned(),
+        );
+
+        self.pool
+            .execute(create_table)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        let create_index = builder.build(
+            &Index::create()
+                .if_not_exists()
+                .name(format!("{archive}_id_idx"))
+                .table(Alias::new(&archive))
+                .col(sessions::Column::Id)
+                .to_owned(),
+        );
+
+        self.pool
+            .execute(create_index)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        Ok(())
+    }
+
+    //a table keyed by id with an expiry, for the tombstones and the cleanup leases
+    async fn create_expiring_table(
+        &self,
+        name: &str,
+        context: &Context<'_>,
+    ) -> Result<(), SessionError> {
+        let builder = self.pool.get_database_backend();
+
+        let create = builder.build(
+            &Table::create()
+                .if_not_exists()
+                .table(Alias::new(name))
+                .col(
+                    ColumnDef::new_with_type(
+                        sessions::Column::Id,
+                        ColumnType::String(sea_query::StringLen::N(self.id_length())),
+                    )
+                    .not_null(),
+                )
+                .col(
+                    ColumnDef::new_with_type(sessions::Column::Expires, self.timestamp_type())
+                        .not_null(),
+                )
+                .primary_key(
+                    Index::create()
+                        .name(format!("{name}_idx"))
+                        .col(sessions::Column::Id)
+                        .primary(),
+                )
+                .to_owned(),
+        );
+
+        self.pool
+            .execute(create)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Create, err))?;
+
+        Ok(())
+    }
+
+    //whether the session column is JSONB, see with_jsonb()
+    fn jsonb(&self) -> bool {
+        #[cfg(feature = "postgres")]
+        {
+            self.jsonb && self.pool.get_database_backend() == DbBackend::Postgres
+        }
+
+        #[cfg(not(feature = "postgres"))]
+        {
+            false
+        }
+    }
+
+    //whether the upsert of store() keeps the later expiry, see with_monotonic_expiry()
+    fn monotonic_upsert(&self) -> bool {
+        #[cfg(feature = "mysql")]
+        {
+            self.monotonic_expiry
+        }
+
+        #[cfg(not(feature = "mysql"))]
+        {
+            self.monotonic_expiry && self.pool.get_database_backend() != DbBackend::MySql
+        }
+    }
+
+    //monotonic_upsert() can not keep its promise, store() must not quietly write the given expiry
+    fn check_monotonic(&self, context: &Context<'_>) -> Result<(), SessionError> {
+        if self.monotonic_expiry && !self.monotonic_upsert() {
+            return Err(context.invalid(
+                ErrorKind::NotSupported,
+                "monotonic expiry on MySQL needs the mysql feature",
+            ));
+        }
+
+        Ok(())
+    }
+
+    //whether the table is partitioned by expiry, see with_partitioning()
+    fn partitioned(&self) -> bool {
+        #[cfg(feature = "postgres")]
+        {
+            self.partitioned && self.pool.get_database_backend() == DbBackend::Postgres
+        }
+
+        #[cfg(not(feature = "postgres"))]
+        {
+            false
+        }
+    }
+
+    //whether initiate() creates the table UNLOGGED, see with_unlogged()
+    #[cfg(feature = "postgres")]
+    fn unlogged(&self) -> bool {
+        (self.unlogged || cfg!(feature = "pg_unlogged"))
+            && self.pool.get_database_backend() == DbBackend::Postgres
+            && !self.partitioned()
+    }
+
+    //the payload as it is bound for the session column
+    fn session_value(&self, session: Cow<'_, str>) -> Value {
+        #[cfg(feature = "postgres")]
+        if self.jsonb() {
+            let json = session
+                .parse::<sea_orm::JsonValue>()
+                .unwrap_or_else(|_| sea_orm::JsonValue::String(session.into_owned()));
+            return Value::Json(Some(Box::new(json)));
+        }
+
+        session.into()
+    }
+
+    //everything besides the table name that changes the SQL of a cached statement
+    fn statement_options(&self, user_id: bool) -> u8 {
+        u8::from(self.jsonb())
+            | u8::from(self.soft_delete()) << 1
+            | u8::from(self.monotonic_upsert()) << 2
+            | u8::from(user_id) << 3
+            | u8::from(self.tombstone_grace.is_some()) << 4
+            | u8::from(self.metadata()) << 5
+    }
+
+    //the session column as text, #>> '{}' turns a JSONB value back into it, a JSON string
+    //without its quotes
+    fn session_column(&self) -> SimpleExpr {
+        if self.jsonb() {
+            return Expr::cust(r#""session" #>> '{}'"#);
+        }
+
+        Expr::col(sessions::Column::Session).into()
+    }
+
+    fn encode_session<'a>(
+        &self,
+        session: &'a str,
+        context: &Context,
+    ) -> Result<Cow<'a, str>, SessionError> {
+        let has_nul = session.contains('\0');
+
+        if self.nul_escape && (has_nul || session.starts_with(NUL_ESCAPE_MARKER)) {
+            let escaped = session.replace('\\', "\\\\").replace('\0', "\\0");
+            return Ok(Cow::Owned(format!("{NUL_ESCAPE_MARKER}{escaped}")));
+        }
+
+        if has_nul && self.pool.get_database_backend() == DbBackend::Postgres {
+            return Err(context.invalid(
+                ErrorKind::Insert,
+                "session payload contains a NUL byte, which a Postgres TEXT column can not \
+                 hold, fix the data put into the session or enable with_nul_escape(true)",
+            ));
+        }
+
+        Ok(Cow::Borrowed(session))
+    }
+
+    //whether store() deletes rather than writes the payload, see with_skip_empty_sessions()
+    pub(crate) fn is_empty(&self, session: &str) -> bool {
+        self.skip_empty
+            .as_ref()
+            .is_some_and(|EmptySession(is_empty)| is_empty(session))
+    }
+
+    //bind parameters one statement may use, SQLite before 3.32 allows 999
+    fn bind_limit(&self) -> usize {
+        match self.pool.get_database_backend() {
+            DbBackend::Sqlite => 999,
+            DbBackend::Postgres | DbBackend::MySql => 65_535,
+        }
+    }
+
+    //most ids one IN list binds, the expiry check and the timestamp of a soft delete or an
+    //archived copy take up to two more parameters
+    fn in_list_length(&self) -> usize {
+        (self.bind_limit() - 2).min(10_000)
+    }
+
+    //most rows one multi-row insert holds, every row binds all of its columns
+    fn insert_rows(&self) -> usize {
+        self.bind_limit() / (COLUMNS.len() + self.metadata_columns().len())
+    }
+
+    //the epoch layout is axum_session_sqlx's, which has none of the metadata columns, see
+    //with_epoch_expires()
+    fn metadata(&self) -> bool {
+        !self.epoch_expires
+    }
+
+    //created_at or updated_at the way the metadata migration adds them, NOT NULL and defaulting
+    //to the current time. SQLite can not add a column with a non-constant default, there both
+    //stay nullable and initiate() fills them in for existing rows
+    fn metadata_column(&self, column: sessions::Column) -> ColumnDef {
+        let mut column_def = ColumnDef::new_with_type(column, self.timestamp_type());
+
+        if self.pool.get_database_backend() != DbBackend::Sqlite {
+            column_def.not_null().default(Expr::current_timestamp());
+        }
+
+        column_def
+    }
+
+    fn metadata_columns(&self) -> &'static [sessions::Column] {
+        if self.metadata() {
+            &METADATA_COLUMNS
+        } else {
+            &[]
+        }
+    }
+
+    //what with_archival() copies of an expiring row
+    fn archive_columns(&self) -> &'static [sessions::Column] {
+        if self.metadata() {
+            &ARCHIVE_COLUMNS
+        } else {
+            &COLUMNS
+        }
+    }
+
+    fn require_metadata(&self, context: &Context<'_>) -> Result<(), SessionError> {
+        if self.metadata() {
+            return Ok(());
+        }
+
+        Err(context.invalid(
+            ErrorKind::NotSupported,
+            "the epoch layout has no created_at, updated_at or user_id column",
+        ))
+    }
+
+    //a table name set through the builder wins over the one axum_session passes in
+    fn table_name<'a>(&'a self, table_name: &'a str) -> &'a str {
+        self.table_name.as_deref().unwrap_or(table_name)
+    }
+
+    fn id_length(&self) -> u32 {
+        self.id_length.unwrap_or(DEFAULT_ID_LENGTH)
+    }
+
+    //an over-long id is an error on Postgres and silently truncated by MySQL in some modes
+    //the length limit is the one of the id column, see DbPoolBuilder::id_max_length
+    fn check_id(&self, id: &str, context: &Context, kind: ErrorKind) -> Result<(), SessionError> {
+        session_id::validate(id, self.id_length() as usize)
+            .map_err(|err| context.invalid(kind, err))
+    }
+
+    //the live row behind load(), also used by pools layered on top that need the expiry
+    pub(crate) async fn load_model(
+        &self,
+        id: &str,
+        table_name: &str,
+    ) -> Result<Option<sessions::Model>, SessionError> {
+        let table_name = self.table_name(table_name);
+        let context = Context::new("load", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
+
+        let now = self.now();
+
+        if self.lazy_expiry {
+            return self.load_lazy(id, table_name, now, &context).await;
+        }
+
+        let builder = self.pool.get_database_backend();
+
+        let statement = self.statements.statement(
+            builder,
+            ("load", self.statement_options(false)),
+            table_name,
+            vec![id.into(), self.to_db_datetime(Some(now))],
+            |_| {
+                Ok(builder.build(
+                    Query::select()
+                        .columns(KEY_COLUMNS)
+                        .expr_as(self.session_column(), sessions::Column::Session)
+                        .from(Alias::new(table_name))
+                        .and_where(Expr::col(sessions::Column::Id).eq(id))
+                        .and_where(self.is_live(now)),
+                ))
+            },
+        )?;
+
+        let row = self
+            .reader()
+            .query_one(statement)
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        row.map(|row| self.model_from_row(&row, &context))
+            .transpose()
+    }
+
+    //like load_model() but the row is fetched regardless of its expiry so a dead row can be removed
+    async fn load_lazy(
+        &self,
+        id: &str,
+        table_name: &str,
+        now: DateTime<Utc>,
+        context: &Context<'_>,
+    ) -> Result<Option<sessions::Model>, SessionError> {
+        let builder = self.pool.get_database_backend();
+
+        let row = self
+            .reader()
+            .query_one(
+                builder.build(
+                    Query::select()
+                        .columns(KEY_COLUMNS)
+                        .expr_as(self.session_column(), sessions::Column::Session)
+                        .from(Alias::new(table_name))
+                        .and_where(Expr::col(sessions::Column::Id).eq(id))
+                        .and_where_option(self.not_deleted()),
+                ),
+            )
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        let Some(model) = row
+            .map(|row| self.model_from_row(&row, context))
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+
+        if model.expires.is_none_or(|expires| expires > now) {
+            return Ok(Some(model));
+        }
+
+        //fire and forget, the expires check keeps a session renewed in the meantime
+        self.forget_writes([id]);
+        let delete = self.removal(
+            table_name,
+            Expr::col(sessions::Column::Id)
+                .eq(id)
+                .and(self.is_expired(now)),
+            false,
+        );
+
+        //without a tokio runtime to spawn on, load() waits for the delete instead
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            let _ = self.pool.execute(delete).traced().await;
+            return Ok(None);
+        };
+
+        let pool = self.pool.clone();
+        runtime.spawn(async move {
+            let _ = pool.execute(delete).traced().await;
+        });
+
+        Ok(None)
+    }
+
+    //every timestamp written goes through here and is read back through read_db_datetime(),
+    //expires and the metadata columns are stored as a UTC timestamp or, in epoch mode, as BIGINT.
+    //on MySQL that is a DATETIME, which the connection's time_zone does not shift
+    fn to_db_datetime(&self, datetime: Option<DateTime<Utc>>) -> Value {
+        if self.epoch_expires {
+            datetime.map(|datetime| datetime.timestamp()).into()
+        } else {
+            datetime.into()
+        }
+    }
+
+    fn read_db_datetime(
+        &self,
+        row: &QueryResult,
+        column: &str,
+    ) -> Result<Option<DateTime<Utc>>, DbErr> {
+        if self.epoch_expires {
+            Ok(row
+                .try_get::<Option<i64>>("", column)?
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0)))
+        } else {
+            row.try_get::<Option<DateTime<Utc>>>("", column)
+        }
+    }
+
+    //expires IS NULL OR expires > now, a NULL expires means the session never expires
+    fn is_live(&self, now: DateTime<Utc>) -> SimpleExpr {
+        let live = Expr::col(sessions::Column::Expires)
+            .is_null()
+            .or(Expr::col(sessions::Column::Expires).gt(self.to_db_datetime(Some(now))));
+
+        match self.not_deleted() {
+            Some(not_deleted) => live.and(not_deleted),
+            None => live,
+        }
+    }
+
+    //with soft deletes a marked row is gone for every read, see with_soft_delete()
+    fn not_deleted(&self) -> Option<SimpleExpr> {
+        self.soft_delete()
+            .then(|| Expr::col(sessions::Column::DeletedAt).is_null())
+    }
+
+    fn soft_delete(&self) -> bool {
+        #[cfg(feature = "soft_delete")]
+        {
+            self.soft_delete
+        }
+
+        #[cfg(not(feature = "soft_delete"))]
+        {
+            false
+        }
+    }
+
+    //deletes the rows matching `condition` or, with soft deletes, marks those not marked yet
+    fn removal(&self, table_name: &str, condition: SimpleExpr, returning: bool) -> Statement {
+        let builder = self.pool.get_database_backend();
+
+        if self.soft_delete() {
+            let mut update = Query::update();
+            update
+                .table(Alias::new(table_name))
+                .value(
+                    sessions::Column::DeletedAt,
+                    self.to_db_datetime(Some(self.now())),
+                )
+                .and_where(condition)
+                .and_where_option(self.not_deleted());
+
+            if returning {
+                update.returning_col(sessions::Column::Id);
+            }
+
+            return builder.build(&update);
+        }
+
+        let mut delete = Query::delete();
+        delete
+            .from_table(Alias::new(table_name))
+            .and_where(condition);
+
+        if returning {
+            delete.returning_col(sessions::Column::Id);
+        }
+
+        builder.build(&delete)
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> SimpleExpr {
+        Expr::col(sessions::Column::Expires).lte(self.to_db_datetime(Some(now)))
+    }
+
+    //the model for a row selected with COLUMNS, the metadata columns are not part of it
+    fn model_from_row(
+        &self,
+        row: &QueryResult,
+        context: &Context,
+    ) -> Result<sessions::Model, SessionError> {
+        let model = || -> Result<sessions::Model, DbErr> {
+            Ok(sessions::Model {
+                id: row.try_get("", "id")?,
+                expires: self.read_db_datetime(row, "expires")?,
+                session: decode_session(row.try_get("", "session")?),
+                created_at: None,
+                updated_at: None,
+                deleted_at: None,
+                user_id: None,
+            })
+        };
+
+        model().map_err(|err| context.db(ErrorKind::Select, err))
+    }
+
+    //MySQL TIMESTAMP ends at 2038-01-19 03:14:07 UTC, DATETIME has no time zone and holds the
+    //UTC values to_db_datetime() writes up to the year 9999
+    fn timestamp_type(&self) -> ColumnType {
+        if self.epoch_expires {
+            ColumnType::BigInteger
+        } else if self.pool.get_database_backend() == DbBackend::MySql {
+            ColumnType::DateTime
+        } else {
+            ColumnType::TimestampWithTimeZone
+        }
+    }
+
+    //the lowercase type of every column as reported by the backend, empty if the table does not exist
+    async fn column_types(
+        &self,
+        table_name: &str,
+        context: &Context<'_>,
+    ) -> Result<HashMap<String, String>, SessionError> {
+        let backend = self.pool.get_database_backend();
+
+        let sql = match backend {
+            DbBackend::Postgres => {
+                "SELECT column_name AS column_name, data_type AS data_type \
+                 FROM information_schema.columns \
+                 WHERE table_schema = current_schema() AND table_name = $1"
+            }
+            DbBackend::MySql => {
+                "SELECT column_name AS column_name, data_type AS data_type \
+                 FROM information_schema.columns \
+                 WHERE table_schema = DATABASE() AND table_name = ?"
+            }
+            DbBackend::Sqlite => {
+                "SELECT name AS column_name, type AS data_type FROM pragma_table_info(?)"
+            }
+        };
+
+        let rows = self
+            .pool
+            .query_all(Statement::from_sql_and_values(
+                backend,
+                sql,
+                [table_name.into()],
+            ))
+            .traced()
+            .await
+            .map_err(|err| context.db(ErrorKind::Select, err))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<String>("", "column_name")?.to_lowercase(),
+                    row.try_get::<String>("", "data_type")?.to_lowercase(),
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, DbErr>>()
+            .map_err(|err| context.db(ErrorKind::Select, err))
+    }
+
+    //names every column whose type can not hold what DbPool reads and writes
+    fn check_schema(
+        &self,
+        table_name: &str,
+        columns: &HashMap<String, String>,
+        context: &Context,
+    ) -> Result<(), SessionError> {
+        fn is_string(data_type: &str) -> bool {
+            data_type.contains("char") || data_type.contains("text")
+        }
+
+        fn is_text(data_type: &str) -> bool {
+            data_type.contains("text") || data_type.contains("clob")
+        }
+
+        fn is_timestamp(data_type: &str) -> bool {
+            data_type.contains("timestamp") || data_type.contains("datetime")
+        }
+
+        //MySQL TIMESTAMP ends in 2038, sessions expiring later could not be stored
+        fn is_datetime(data_type: &str) -> bool {
+            data_type == "datetime"
+        }
+
+        fn is_integer(data_type: &str) -> bool {
+            data_type.contains("int")
+        }
+
+        //the type named in the error and the test a column's type has to pass
+        type Expected = (&'static str, fn(&str) -> bool);
+
+        let timestamp: Expected = if self.epoch_expires {
+            ("BIGINT", is_integer)
+        } else if self.pool.get_database_backend() == DbBackend::MySql {
+            ("DATETIME", is_datetime)
+        } else {
+            ("a timestamp", is_timestamp)
+        };
+
+        let session: Expected = if self.jsonb() {
+            ("JSONB", |data_type| data_type == "jsonb")
+        } else {
+            ("TEXT", is_text)
+        };
+
+        let expected: [(&str, Expected, bool); 7] = [
+            ("id", ("VARCHAR", is_string), true),
+            ("expires", timestamp, true),
+            ("session", session, true),
+            ("created_at", timestamp, false),
+            ("updated_at", timestamp, false),
+            ("deleted_at", timestamp, false),
+            ("user_id", ("VARCHAR", is_string), false),
+        ];
+
+        let mut mismatches = Vec::new();
+        for (column, (expected_type, matches), required) in expected {
+            match columns.get(column).map(String::as_str) {
+                Some(data_type) if !matches(data_type) => {
+                    let hint = match data_type {
+                        //older versions created expires as DATE
+                        "date" => " (DATE truncates every expiry to midnight)",
+                        "timestamp" if self.pool.get_database_backend() == DbBackend::MySql => {
+                            " (TIMESTAMP ends at 2038-01-19 03:14:07 UTC)"
+                        }
+                        data_type if is_integer(data_type) && !self.epoch_expires => {
+                            " (use with_epoch_expires(true) for unix seconds)"
+                        }
+                        _ => "",
+                    };
+
+                    mismatches.push(format!(
+                        "column {column} is {data_type}, expected {expected_type}{hint}"
+                    ));
+                }
+                None if required => mismatches.push(format!(
+                    "column {column} is missing, expected {expected_type}"
+                )),
+                _ => {}
+            }
+        }
+
+        if mismatches.is_empty() {
+            return Ok(());
+        }
 
-#[derive(Clone, Debug, Default)]
-pub struct DbPool {
-    pool: DatabaseConnection,
+        Err(context.setup(format!(
+            "table {table_name} does not have the expected schema: {}, \
+                 alter the table or drop it and let it be recreated, \
+                 or skip this check with with_schema_check(false)",
+            mismatches.join("; ")
+        )))
+    }
 }
 
-impl DbPool {
-    pub fn new(db: DatabaseConnection) -> DbPool {
-        //https://www.sea-ql.org/SeaORM/docs/install-and-config/connection/
-        //"Under the hood, a sqlx::Pool is created and owned by DatabaseConnection."
-        DbPool { pool: db }
+const DEFAULT_ID_LENGTH: u32 = 128;
+
+//50 years
+const DEFAULT_NEVER_EXPIRES_AFTER: Duration = Duration::days(50 * 365);
+
+//COLUMNS without the payload, which is selected with session_column()
+const KEY_COLUMNS: [sessions::Column; 2] = [sessions::Column::Id, sessions::Column::Expires];
+
+const COLUMNS: [sessions::Column; 3] = [
+    sessions::Column::Id,
+    sessions::Column::Expires,
+    sessions::Column::Session,
+];
+
+//written by store(), created_at only when the row is inserted
+const METADATA_COLUMNS: [sessions::Column; 2] =
+    [sessions::Column::CreatedAt, sessions::Column::UpdatedAt];
+
+//copied by with_archival(), every sessions column but deleted_at
+const ARCHIVE_COLUMNS: [sessions::Column; 6] = [
+    sessions::Column::Id,
+    sessions::Column::Expires,
+    sessions::Column::Session,
+    sessions::Column::CreatedAt,
+    sessions::Column::UpdatedAt,
+    sessions::Column::UserId,
+];
+
+//shared by every table, one row per table name
+const CLEANUP_LEASES: &str = "cleanup_leases";
+
+const DEFAULT_SQLITE_BUSY_RETRIES: u32 = 5;
+
+const BUSY_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(10);
+
+const DEFAULT_CLEANUP_CHUNK_SIZE: usize = 10_000;
+
+const DEFAULT_ACQUIRE_BACKOFF: std::time::Duration = std::time::Duration::from_millis(100);
+
+//SQLITE_BUSY (5) and SQLITE_LOCKED (6), without the sqlx feature of sea-orm the error is only
+//reachable through its message
+fn is_busy(err: &DbErr) -> bool {
+    let message = err.to_string();
+    message.contains("database is locked")
+        || message.contains("database table is locked")
+        || message.contains("(code: 5)")
+        || message.contains("(code: 6)")
+}
+
+//starts payloads escaped by with_nul_escape(), a serialized session never starts with ESC
+const NUL_ESCAPE_MARKER: &str = "\u{1b}nul:";
+
+//undoes encode_session(), also with the escape turned off again so older rows stay readable
+fn decode_session(session: String) -> String {
+    let Some(escaped) = session.strip_prefix(NUL_ESCAPE_MARKER) else {
+        return session;
+    };
+
+    let mut decoded = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => decoded.push('\0'),
+            Some(c) => decoded.push(c),
+            None => decoded.push('\\'),
+        }
     }
+
+    decoded
 }
 
-//https://github.com/AscendingCreations/AxumSession/blob/main/examples/middleware_layer/src/main.rs
-//https://github.com/AscendingCreations/AxumSession/blob/main/databases/sqlx/src/sqlite.rs
+//MySQL's dummy table, unquoted unlike an Alias
+struct Dual;
 
-#[async_trait]
-impl DatabasePool for DbPool {
-    #[inline(always)]
-    async fn initiate(&self, _table_name: &str) -> Result<(), DatabaseError> {
-        let builder = self.pool.get_database_backend();
+impl sea_query::Iden for Dual {
+    fn prepare(&self, s: &mut dyn std::fmt::Write, _: sea_query::Quote) {
+        self.unquoted(s);
+    }
 
-        let create_table = builder.build(
-            &Table::create()
-                .if_not_exists()
-                .table(sessions::Entity.table_ref())
-                .col(
-                    ColumnDef::new_with_type(
-                        sessions::Column::Id,
-                        ColumnType::String(sea_query::StringLen::N(128)),
-                    )
-                    .not_null(),
-                )
-                .col(
-                    ColumnDef::new_with_type(sessions::Column::Expires, ColumnType::Date)
-                        .not_null(),
-                )
-                .col(
-                    ColumnDef::new_with_type(sessions::Column::Session, ColumnType::Text)
-                        .not_null(),
-                )
-                .primary_key(
-                    Index::create()
-                        .name("sessions_idx")
-                        .col(sessions::Column::Id)
-                        .primary(),
-                )
-                .to_owned(),
-        );
+    fn unquoted(&self, s: &mut dyn std::fmt::Write) {
+        let _ = s.write_str("DUAL");
+    }
+}
 
-        self.pool
-            .execute(create_table)
-            .await
-            .map_err(|err| DatabaseError::GenericCreateError(err.to_string()))?;
+fn tombstone_table(table_name: &str) -> String {
+    format!("{table_name}_tombstones")
+}
 
-        let create_index = builder.build(
-            &Index::create()
-                .if_not_exists()
-                .name("sessions_expires_idx")
-                .table(sessions::Entity.table_ref())
-                .col(sessions::Column::Expires)
-                .to_owned(),
-        );
+//NULL when either never expires, otherwise the later one, see with_monotonic_expiry()
+fn later_of(stored: SimpleExpr, incoming: SimpleExpr) -> SimpleExpr {
+    Expr::case(
+        Expr::expr(incoming.clone())
+            .is_null()
+            .or(Expr::expr(stored.clone()).is_null()),
+        SimpleExpr::Keyword(sea_query::Keyword::Null),
+    )
+    .case(Expr::expr(incoming.clone()).gt(stored.clone()), incoming)
+    .finally(stored)
+    .into()
+}
 
-        self.pool
-            .execute(create_index)
-            .await
-            .map_err(|err| DatabaseError::GenericCreateError(err.to_string()))?;
+//quoted like sea_query quotes it, to_regclass() would fold an unquoted name to lowercase
+#[cfg(feature = "postgres")]
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
 
-        // use sea_orm_migration::{MigrationTrait, SchemaManager};
-        // let manager = SchemaManager::new(&self.pool);
-        // crate::migration::Migration
-        //     .up(&manager)
-        //     .await
-        //     .map_err(|err| DatabaseError::GenericCreateError(err.to_string()))?;
+#[cfg(feature = "postgres")]
+fn default_partition(table_name: &str) -> String {
+    format!("{table_name}_default")
+}
 
-        // sqlx::query(
-        //     &r#"
-        //     CREATE TABLE IF NOT EXISTS %%TABLE_NAME%% (
-        //         "id" VARCHAR(128) NOT NULL PRIMARY KEY,
-        //         "expires" BIGINT NULL,
-        //         "session" TEXT NOT NULL
-        //     )
-        // "#
-        //     .replace("%%TABLE_NAME%%", table_name),
-        // )
-        // .execute(&self.pool)
-        // .await
-        // .map_err(|err| DatabaseError::GenericCreateError(err.to_string()))?;
+//the partition of the sessions expiring on `day`, see with_partitioning()
+#[cfg(feature = "postgres")]
+fn day_partition(table_name: &str, day: chrono::NaiveDate) -> String {
+    format!("{table_name}_p{}", day.format("%Y%m%d"))
+}
 
-        Ok(())
+fn archive_table(table_name: &str) -> String {
+    format!("{table_name}_archive")
+}
+
+fn ids_from_rows(rows: &[QueryResult], context: &Context) -> Result<Vec<String>, SessionError> {
+    rows.iter()
+        .map(|row| row.try_get::<String>("", "id"))
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|err| context.db(ErrorKind::Select, err))
+}
+
+fn count_from_row(row: Option<QueryResult>, context: &Context) -> Result<i64, SessionError> {
+    row.map(|row| row.try_get_by_index::<i64>(0))
+        .transpose()
+        .map(|count| count.unwrap_or(0))
+        .map_err(|err| context.db(ErrorKind::Select, err))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DbPoolBuildError {
+    EmptyTableName,
+    InvalidTableName(String),
+    /// The connection did not answer `SELECT 1`, see `DbPoolBuilder::validate_on_create`.
+    Unreachable(String),
+}
+
+impl std::fmt::Display for DbPoolBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbPoolBuildError::EmptyTableName => write!(f, "the table name must not be empty"),
+            DbPoolBuildError::InvalidTableName(name) => write!(
+                f,
+                "invalid table name {name:?}, only ASCII letters, digits and _ are allowed"
+            ),
+            DbPoolBuildError::Unreachable(message) => {
+                write!(f, "the database is not reachable: {message}")
+            }
+        }
     }
+}
 
-    #[inline(always)]
-    async fn delete_by_expiry(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
-        let results = sessions::Entity::find()
-            .filter(
-                sessions::Column::Expires
-                    .is_null()
-                    .or(sessions::Column::Expires.lt(Utc::now())),
-            )
-            .all(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+impl std::error::Error for DbPoolBuildError {}
 
-        // let result: Vec<(String,)> = sqlx::query_as(
-        //     &r#"
-        //     SELECT id FROM %%TABLE_NAME%%
-        //     WHERE (expires IS NULL OR expires < $1)
-        // "#
-        //     .replace("%%TABLE_NAME%%", table_name),
-        // )
-        // .bind(Utc::now().timestamp())
-        // .fetch_all(&self.pool)
-        // .await
-        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+#[derive(Clone, Debug)]
+pub struct DbPoolBuilder {
+    pool: DbPool,
+    table_name: Option<String>,
+    validate_on_create: bool,
+}
 
-        // let result: Vec<String> = result.into_iter().map(|(s,)| s).collect();
+impl DbPoolBuilder {
+    pub fn new(db: DatabaseConnection) -> DbPoolBuilder {
+        DbPoolBuilder {
+            pool: DbPool::new(db),
+            table_name: None,
+            validate_on_create: false,
+        }
+    }
 
-        let result = results.iter().map(|model| model.id.clone()).collect();
+    /// Table used for every operation, regardless of the table name axum_session passes in.
+    pub fn table_name(mut self, table_name: impl Into<String>) -> DbPoolBuilder {
+        self.table_name = Some(table_name.into());
+        self
+    }
 
-        sessions::Entity::delete_many()
-            .filter(sessions::Column::Expires.lt(Utc::now()))
-            .exec(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+    /// Length of the id column initiate() creates and store() checks ids against, 128 by default.
+    pub fn id_max_length(mut self, id_max_length: u32) -> DbPoolBuilder {
+        self.pool.id_length = Some(id_max_length);
+        self
+    }
 
-        // sqlx::query(
-        //     &r#"DELETE FROM %%TABLE_NAME%% WHERE expires < $1"#
-        //         .replace("%%TABLE_NAME%%", table_name),
-        // )
-        // .bind(Utc::now().timestamp())
-        // .execute(&self.pool)
-        // .await
-        // .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+    /// Caps every expiry store() writes at now + `max_session_ttl`, see `DbPool::with_max_lifetime`.
+    pub fn max_session_ttl(mut self, max_session_ttl: std::time::Duration) -> DbPoolBuilder {
+        self.pool.max_lifetime = Some(max_lifetime(max_session_ttl));
+        self
+    }
 
-        Ok(result)
+    /// How often an operation is retried when no connection could be acquired, 0 by default.
+    /// Any other error is returned right away.
+    pub fn max_retries(mut self, max_retries: u32) -> DbPoolBuilder {
+        self.pool.acquire_retries = max_retries;
+        self
+    }
+
+    /// Wait before the first retry, doubled for each one after it. Defaults to 100ms.
+    pub fn retry_backoff(mut self, retry_backoff: std::time::Duration) -> DbPoolBuilder {
+        self.pool.acquire_backoff = Some(retry_backoff);
+        self
+    }
+
+    /// Archives expired sessions before delete_by_expiry() deletes them, see `DbPool::with_archival`.
+    pub fn archival(mut self, archival: bool) -> DbPoolBuilder {
+        self.pool.archival = archival;
+        self
+    }
+
+    /// Makes connect() run `SELECT 1` before it returns the pool, so a dead connection is found
+    /// at startup instead of on the first request.
+    pub fn validate_on_create(mut self, validate_on_create: bool) -> DbPoolBuilder {
+        self.validate_on_create = validate_on_create;
+        self
+    }
+
+    /// build(), followed by the check of validate_on_create() under the retries of max_retries().
+    pub async fn connect(self) -> Result<DbPool, DbPoolBuildError> {
+        let validate_on_create = self.validate_on_create;
+        let pool = self.build()?;
+
+        if validate_on_create {
+            pool.retry_acquire(|| pool.ping())
+                .await
+                .map_err(|err| DbPoolBuildError::Unreachable(err.to_string()))?;
+        }
+
+        Ok(pool)
+    }
+
+    /// Checks the table name, the connection is only checked by connect().
+    pub fn build(self) -> Result<DbPool, DbPoolBuildError> {
+        let mut pool = self.pool;
+
+        if let Some(table_name) = self.table_name {
+            if table_name.is_empty() {
+                return Err(DbPoolBuildError::EmptyTableName);
+            }
+
+            if !table_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                return Err(DbPoolBuildError::InvalidTableName(table_name));
+            }
+
+            pool.table_name = Some(table_name);
+        }
+
+        Ok(pool)
+    }
+}
+
+//https://github.com/AscendingCreations/AxumSession/blob/main/examples/middleware_layer/src/main.rs
+//https://github.com/AscendingCreations/AxumSession/blob/main/databases/sqlx/src/sqlite.rs
+
+#[async_trait]
+impl DatabasePool for DbPool {
+    #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %table_name))
+    )]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        let _timer = Timer::start("db", "initiate");
+        Ok(self
+            .retry_acquire(|| self.create_schema(table_name))
+            .await?)
     }
 
     #[inline(always)]
-    async fn count(&self, _table_name: &str) -> Result<i64, DatabaseError> {
-        let count = sessions::Entity::find()
-            .count(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %table_name, deleted = tracing::field::Empty)
+        )
+    )]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let _timer = Timer::start("db", "delete_by_expiry");
+        let ids = self.sweep(table_name).await?;
 
-        // let (count,) = sqlx::query_as(
-        //     &r#"SELECT COUNT(*) FROM %%TABLE_NAME%%"#.replace("%%TABLE_NAME%%", table_name),
-        // )
-        // .fetch_one(&self.pool)
-        // .await
-        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+        record!("deleted", ids.len());
+        Ok(ids)
+    }
+
+    #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %table_name))
+    )]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        let _timer = Timer::start("db", "count");
+        let count = self.retry_acquire(|| self.count_live(table_name)).await?;
 
-        return Ok(count as i64);
+        measure::active_sessions("db", count);
+        Ok(count)
     }
 
     //https://github.com/AscendingCreations/AxumSession/blob/main/src/session_data.rs
     //   pub(crate) expires: DateTime<Utc>,
 
     #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                table_name = %table_name,
+                session_id = %crate::context::ShortId(id),
+                skipped = tracing::field::Empty,
+            )
+        )
+    )]
     async fn store(
         &self,
         id: &str,
         session: &str,
         expires: i64,
-        _table_name: &str,
+        table_name: &str,
     ) -> Result<(), DatabaseError> {
-        //https://www.sea-ql.org/SeaORM/docs/basic-crud/update/
-        //https://www.sea-ql.org/SeaORM/docs/basic-crud/insert/
+        let _timer = Timer::start("db", "store");
 
-        //should be seconds since 1970-01-01 00:00:00 UTC
-        let expires = chrono::DateTime::from_timestamp(expires, 0)
-            .map(|expires| Utc.from_utc_datetime(&expires.naive_utc()));
+        if let Some(filter) = &self.write_filter {
+            let skip = filter.unchanged(id, session, expires, self.table_name(table_name));
+
+            record!("skipped", skip);
+            if skip {
+                measure::skipped_write("db");
+                return Ok(());
+            }
+        }
 
-        let model = sessions::ActiveModel {
-            id: ActiveValue::set(id.to_owned()),
-            session: ActiveValue::set(session.to_string()),
-            expires: ActiveValue::set(expires),
+        let queued = match &self.write_behind {
+            Some(queue) if self.start_write_behind(queue) => {
+                let table_name = self.table_name(table_name);
+                self.check_store(id, session, expires, table_name)?;
+                queue.push(table_name, id, session, expires)
+            }
+            _ => false,
         };
 
-        sessions::Entity::insert(model.clone())
-            .on_conflict(
-                sea_query::OnConflict::column(sessions::Column::Id)
-                    .update_columns([sessions::Column::Expires, sessions::Column::Session])
-                    .to_owned(),
-            )
-            .exec(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericInsertError(err.to_string()))?;
+        if !queued {
+            self.retry_busy(|| self.store_once(id, session, expires, None, table_name))
+                .await?;
+
+            //a queued session is announced by the flush writing it
+            #[cfg(feature = "pg_notify")]
+            if self.notify_writes {
+                crate::notifying_pool::notify(&self.pool, id, table_name, ErrorKind::Insert)
+                    .await?;
+            }
+        }
+
+        self.forget_misses(self.table_name(table_name), [id]);
+
+        //only once it landed, a failed write must not be skipped when retried
+        if let Some(filter) = &self.write_filter {
+            filter.record(id, session, expires, self.table_name(table_name));
+        }
 
-        //     sqlx::query(
-        //         &r#"
-        //     INSERT INTO %%TABLE_NAME%%
-        //         (id, session, expires) SELECT $1, $2, $3
-        //     ON CONFLICT(id) DO UPDATE SET
-        //         expires = EXCLUDED.expires,
-        //         session = EXCLUDED.session
-        // "#
-        //         .replace("%%TABLE_NAME%%", table_name),
-        //     )
-        //     .bind(id)
-        //     .bind(session)
-        //     .bind(expires)
-        //     .execute(&self.pool)
-        //     .await
-        //     .map_err(|err| DatabaseError::GenericInsertError(err.to_string()))?;
         Ok(())
     }
 
     #[inline(always)]
-    async fn load(&self, id: &str, _table_name: &str) -> Result<Option<String>, DatabaseError> {
-        let maybe_model = sessions::Entity::find()
-            .filter(sessions::Column::Id.eq(id))
-            .filter(
-                sessions::Column::Expires
-                    .is_null()
-                    .or(sessions::Column::Expires.gt(Utc::now())),
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                table_name = %table_name,
+                session_id = %crate::context::ShortId(id),
+                hit = tracing::field::Empty,
             )
-            .one(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+        )
+    )]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let _timer = Timer::start("db", "load");
+        let session = match self.queued(id, table_name) {
+            Some(session) => session,
+            None if self.known_miss(id, table_name) => None,
+            None => {
+                let since = self.miss_generation();
+                let session = self
+                    .retry_acquire(|| self.load_model(id, table_name))
+                    .await?
+                    .map(|model| model.session);
 
-        if let Some(model) = maybe_model {
-            Ok(Some(model.session.to_string()))
-        } else {
-            Ok(None)
-        }
+                if session.is_none() {
+                    self.remember_miss(id, table_name, since);
+                }
+                session
+            }
+        };
+
+        record!("hit", session.is_some());
+        measure::load_result("db", session.is_some());
+        Ok(session)
 
         // let result: Option<(String,)> = sqlx::query_as(
         //     &r#"
@@ -254,12 +4444,18 @@ impl DatabasePool for DbPool {
     }
 
     #[inline(always)]
-    async fn delete_one_by_id(&self, id: &str, _table_name: &str) -> Result<(), DatabaseError> {
-        sessions::Entity::delete_many()
-            .filter(sessions::Column::Id.eq(id))
-            .exec(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %table_name, session_id = %crate::context::ShortId(id))
+        )
+    )]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        let _timer = Timer::start("db", "delete_one_by_id");
+        self.retry_busy(|| self.remove_session(id, table_name))
+            .await?;
 
         // sqlx::query(
         //     &r#"DELETE FROM %%TABLE_NAME%% WHERE id = $1"#.replace("%%TABLE_NAME%%", table_name),
@@ -272,78 +4468,58 @@ impl DatabasePool for DbPool {
     }
 
     #[inline(always)]
-    async fn exists(&self, id: &str, _table_name: &str) -> Result<bool, DatabaseError> {
-        let count = sessions::Entity::find()
-            .filter(sessions::Column::Id.eq(id))
-            .filter(sessions::Column::Expires.gt(Utc::now()))
-            .count(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %table_name, session_id = %crate::context::ShortId(id))
+        )
+    )]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        let _timer = Timer::start("db", "exists");
 
-        // let result: Option<(i64,)> = sqlx::query_as(
-        //     &r#"
-        //     SELECT COUNT(*) FROM %%TABLE_NAME%%
-        //     WHERE id = $1 AND (expires IS NULL OR expires > $2)
-        // "#
-        //     .replace("%%TABLE_NAME%%", table_name),
-        // )
-        // .bind(id)
-        // .bind(Utc::now().timestamp())
-        // .fetch_optional(&self.pool)
-        // .await
-        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
+        if let Some(session) = self.queued(id, table_name) {
+            return Ok(session.is_some());
+        }
 
-        // Ok(result.map(|(o,)| o).unwrap_or(0) > 0)
-        Ok(count > 0)
-    }
+        if self.known_miss(id, table_name) {
+            return Ok(false);
+        }
 
-    #[inline(always)]
-    async fn delete_all(&self, _table_name: &str) -> Result<(), DatabaseError> {
-        sessions::Entity::delete_many()
-            .exec(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+        let since = self.miss_generation();
+        let exists = self
+            .retry_acquire(|| self.live_exists(id, table_name))
+            .await?;
 
-        // sqlx::query(&r#"DELETE FROM %%TABLE_NAME%%"#.replace("%%TABLE_NAME%%", table_name))
-        //     .execute(&self.pool)
-        //     .await
-        //     .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
-        Ok(())
+        if !exists {
+            self.remember_miss(id, table_name, since);
+        }
+        Ok(exists)
     }
 
     #[inline(always)]
-    async fn get_ids(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
-        let results = sessions::Entity::find()
-            .filter(
-                sessions::Column::Expires
-                    .is_null()
-                    .or(sessions::Column::Expires.gt(Utc::now())),
-            )
-            .all(&self.pool)
-            .await
-            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
-
-        let result = results.iter().map(|model| model.id.clone()).collect();
-
-        // let result: Vec<(String,)> = sqlx::query_as(
-        //     &r#"
-        //     SELECT id FROM %%TABLE_NAME%%
-        //     WHERE (expires IS NULL OR expires > $1)
-        // "#
-        //     .replace("%%TABLE_NAME%%", table_name),
-        // )
-        // .bind(Utc::now().timestamp())
-        // .fetch_all(&self.pool)
-        // .await
-        // .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))?;
-
-        // let result: Vec<String> = result.into_iter().map(|(s,)| s).collect();
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %table_name))
+    )]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        let _timer = Timer::start("db", "delete_all");
+        Ok(self.retry_busy(|| self.clear(table_name)).await?)
+    }
 
-        Ok(result)
+    #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %table_name))
+    )]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let _timer = Timer::start("db", "get_ids");
+        Ok(self.retry_acquire(|| self.live_ids(table_name)).await?)
     }
 
     #[inline(always)]
     fn auto_handles_expiry(&self) -> bool {
-        false
+        self.auto_handles_expiry
     }
 }
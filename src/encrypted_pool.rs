@@ -0,0 +1,171 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use axum_session::{DatabaseError, DatabasePool};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+const NONCE_LENGTH: usize = 12;
+
+//wraps any pool and stores base64(nonce || ciphertext) instead of the plain session,
+//a payload that fails to decrypt loads as None so axum_session simply starts a new session.
+//the table name and the id are authenticated with it, a ciphertext moved to another session
+//does not decrypt
+#[derive(Clone)]
+pub struct EncryptedPool<P> {
+    inner: P,
+    cipher: Aes256Gcm,
+    //older keys, only tried for decryption so sessions survive a key rotation
+    decryption_ciphers: Vec<Aes256Gcm>,
+}
+
+impl<P: std::fmt::Debug> std::fmt::Debug for EncryptedPool<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedPool")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> EncryptedPool<P> {
+    pub fn new(inner: P, key: &[u8; 32]) -> EncryptedPool<P> {
+        EncryptedPool {
+            inner,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+            decryption_ciphers: Vec::new(),
+        }
+    }
+
+    /// Adds a previous key that is still accepted when loading, new sessions always use the current key.
+    pub fn with_decryption_key(mut self, key: &[u8; 32]) -> EncryptedPool<P> {
+        self.decryption_ciphers
+            .push(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)));
+        self
+    }
+
+    fn encrypt(&self, table_name: &str, id: &str, session: &str) -> Result<String, DatabaseError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = associated_data(table_name, id);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: session.as_bytes(),
+                    aad: &aad,
+                },
+            )
+            .map_err(|err| DatabaseError::GenericInsertError(err.to_string()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        Ok(STANDARD.encode(payload))
+    }
+
+    fn decrypt(&self, table_name: &str, id: &str, payload: &str) -> Option<String> {
+        let payload = STANDARD.decode(payload).ok()?;
+
+        if payload.len() < NONCE_LENGTH {
+            return None;
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+        let nonce = Nonce::from_slice(nonce);
+        let aad = associated_data(table_name, id);
+
+        std::iter::once(&self.cipher)
+            .chain(&self.decryption_ciphers)
+            .find_map(|cipher| {
+                cipher
+                    .decrypt(
+                        nonce,
+                        Payload {
+                            msg: ciphertext,
+                            aad: &aad,
+                        },
+                    )
+                    .ok()
+            })
+            .and_then(|plaintext| String::from_utf8(plaintext).ok())
+    }
+}
+
+//length prefixed, so no two (table, id) give the same bytes
+fn associated_data(table_name: &str, id: &str) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(16 + table_name.len() + id.len());
+
+    for part in [table_name, id] {
+        aad.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        aad.extend_from_slice(part.as_bytes());
+    }
+
+    aad
+}
+
+#[async_trait::async_trait]
+impl<P> DatabasePool for EncryptedPool<P>
+where
+    P: DatabasePool + Send + Sync,
+{
+    #[inline(always)]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.initiate(table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.inner.delete_by_expiry(table_name).await
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        self.inner.count(table_name).await
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        let payload = self.encrypt(table_name, id, session)?;
+
+        self.inner.store(id, &payload, expires, table_name).await
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let payload = self.inner.load(id, table_name).await?;
+
+        Ok(payload.and_then(|payload| self.decrypt(table_name, id, &payload)))
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_one_by_id(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        //a session that load() can not decrypt does not exist either
+        Ok(self.load(id, table_name).await?.is_some())
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_all(table_name).await
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.inner.get_ids(table_name).await
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        self.inner.auto_handles_expiry()
+    }
+}
@@ -9,6 +9,10 @@ pub struct Model {
     pub expires: Option<DateTime<Utc>>,
     #[sea_orm(column_type = "Text")]
     pub session: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub user_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use axum_session::DatabaseError;
+
+/// The `DatabaseError` a `SessionError` becomes once it crosses the `DatabasePool` boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Acquire,
+    Insert,
+    Select,
+    Create,
+    Delete,
+    NotSupported,
+}
+
+impl ErrorKind {
+    fn database_error(self, message: String) -> DatabaseError {
+        match self {
+            ErrorKind::Acquire => DatabaseError::GenericAquire(message),
+            ErrorKind::Insert => DatabaseError::GenericInsertError(message),
+            ErrorKind::Select => DatabaseError::GenericSelectError(message),
+            ErrorKind::Create => DatabaseError::GenericCreateError(message),
+            ErrorKind::Delete => DatabaseError::GenericDeleteError(message),
+            ErrorKind::NotSupported => DatabaseError::GenericNotSupportedError(message),
+        }
+    }
+}
+
+impl From<&DatabaseError> for ErrorKind {
+    fn from(error: &DatabaseError) -> ErrorKind {
+        match error {
+            DatabaseError::GenericAquire(_) => ErrorKind::Acquire,
+            DatabaseError::GenericInsertError(_) => ErrorKind::Insert,
+            DatabaseError::GenericSelectError(_) => ErrorKind::Select,
+            DatabaseError::GenericCreateError(_) => ErrorKind::Create,
+            DatabaseError::GenericDeleteError(_) => ErrorKind::Delete,
+            DatabaseError::GenericNotSupportedError(_) => ErrorKind::NotSupported,
+        }
+    }
+}
+
+/// Errors of `DbPool` and `MemoryPool`, the underlying error is kept so callers can match on it.
+/// The `DatabasePool` methods turn it into a `DatabaseError`, the inherent methods return it as is.
+/// The database variants only exist with the `db_pool` feature, so a match needs a wildcard arm.
+/// There is no variant for a poisoned lock, the pools recover the data of one, nor for
+/// serialization, the payloads are passed through as the strings axum_session hands over.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SessionError {
+    /// The database, or the connection to it, failed.
+    #[cfg(feature = "db_pool")]
+    Db {
+        kind: ErrorKind,
+        context: String,
+        source: sea_orm::DbErr,
+    },
+    /// A statement could not be built.
+    #[cfg(feature = "db_pool")]
+    Query {
+        kind: ErrorKind,
+        context: String,
+        source: sea_orm::sea_query::error::Error,
+    },
+    /// The arguments were rejected, e.g. a negative expiry or an id too long for the table.
+    InvalidInput {
+        kind: ErrorKind,
+        context: String,
+        message: String,
+    },
+    /// The table or the connection does not fit the pool, found while running initiate().
+    Setup { context: String, message: String },
+}
+
+impl SessionError {
+    /// The `DatabaseError` variant this error is reported as.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "db_pool")]
+            SessionError::Db { kind, .. } | SessionError::Query { kind, .. } => *kind,
+            SessionError::InvalidInput { kind, .. } => *kind,
+            SessionError::Setup { .. } => ErrorKind::Create,
+        }
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "db_pool")]
+            SessionError::Db {
+                context, source, ..
+            } => write!(f, "{context}: {source}"),
+            #[cfg(feature = "db_pool")]
+            SessionError::Query {
+                context, source, ..
+            } => write!(f, "{context}: {source}"),
+            SessionError::InvalidInput {
+                context, message, ..
+            }
+            | SessionError::Setup { context, message } => write!(f, "{context}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "db_pool")]
+            SessionError::Db { source, .. } => Some(source),
+            #[cfg(feature = "db_pool")]
+            SessionError::Query { source, .. } => Some(source),
+            SessionError::InvalidInput { .. } | SessionError::Setup { .. } => None,
+        }
+    }
+}
+
+impl From<SessionError> for DatabaseError {
+    fn from(error: SessionError) -> DatabaseError {
+        error.kind().database_error(error.to_string())
+    }
+}
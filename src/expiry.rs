@@ -0,0 +1,88 @@
+use axum_session::DatabaseError;
+use chrono::{DateTime, Utc};
+
+//a session is expired once expires <= now, every pool uses the same boundary so a row is
+//never in between, neither loadable nor deletable
+
+//9999-12-31 23:59:59 UTC, the last second every supported backend can store
+pub(crate) const DEFAULT_MAX_EXPIRES: i64 = 253_402_300_799;
+
+//turns the seconds since 1970-01-01 00:00:00 UTC handed to store() into an expiry
+//negative values are rejected, values beyond max_expires mean the session never expires (None)
+pub(crate) fn parse_expires(
+    expires: i64,
+    max_expires: Option<i64>,
+) -> Result<Option<DateTime<Utc>>, DatabaseError> {
+    if expires < 0 {
+        return Err(DatabaseError::GenericInsertError(format!(
+            "invalid session expiry {expires}, the timestamp must not be negative"
+        )));
+    }
+
+    if expires > max_expires.unwrap_or(DEFAULT_MAX_EXPIRES) {
+        return Ok(None);
+    }
+
+    Ok(DateTime::from_timestamp(expires, 0))
+}
+
+//a std Duration as max_lifetime, one past what chrono can hold becomes the longest it can
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub(crate) fn max_lifetime(ttl: std::time::Duration) -> chrono::Duration {
+    chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX)
+}
+
+//caps an expiry at now + max_lifetime, a session that would never expire gets the cap too
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub(crate) fn clamp_expires(
+    expires: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    max_lifetime: Option<chrono::Duration>,
+) -> Option<DateTime<Utc>> {
+    let Some(cap) = max_lifetime.and_then(|max_lifetime| now.checked_add_signed(max_lifetime))
+    else {
+        return expires;
+    };
+
+    Some(expires.map_or(cap, |expires| expires.min(cap)))
+}
+
+/// Time left until `expires` as of `now`, zero once it passed and None for a session that never
+/// expires, e.g. for the expiry returned by `DbPool::load_with_expiry`.
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub fn remaining_ttl(
+    expires: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<std::time::Duration> {
+    expires.map(|expires| (expires - now).to_std().unwrap_or_default())
+}
+
+/// Live sessions grouped by when they expire, see `DbPool::expiry_histogram`.
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpiryHistogram {
+    /// Start of each bucket with the sessions expiring in it, empty buckets are left out.
+    pub buckets: Vec<(DateTime<Utc>, u64)>,
+    /// Sessions that never expire.
+    pub never_expires: u64,
+}
+
+//the bucket length in seconds, buckets shorter than a second can not be told apart
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub(crate) fn bucket_seconds(bucket: chrono::Duration) -> Result<i64, DatabaseError> {
+    match bucket.num_seconds() {
+        seconds if seconds > 0 => Ok(seconds),
+        _ => Err(DatabaseError::GenericSelectError(format!(
+            "invalid histogram bucket {bucket}, it must be at least one second long"
+        ))),
+    }
+}
+
+//bucket index counted from now, bucket i holds [now + i * bucket, now + (i + 1) * bucket)
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub(crate) fn bucket_start(now: i64, bucket_seconds: i64, index: i64) -> Option<DateTime<Utc>> {
+    index
+        .checked_mul(bucket_seconds)
+        .and_then(|offset| now.checked_add(offset))
+        .and_then(|start| DateTime::from_timestamp(start, 0))
+}
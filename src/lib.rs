@@ -7,13 +7,45 @@
     clippy::print_stdout
 )]
 
-//sea_orm does not support setting the table name dynamically
+//the default table name, DbPool uses whatever table name axum_session passes in
 pub const TABLE_NAME: &str = "sessions";
 
+#[cfg(any(feature = "db_pool", feature = "memory_pool", feature = "redis_pool"))]
+mod expiry;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+mod context;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+mod page;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+mod trace;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+mod measure;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub mod error;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub mod clock;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+mod session_id;
+
 #[cfg(feature = "db_pool")]
 mod db_pool;
 #[cfg(feature = "db_pool")]
 mod entities;
+#[cfg(feature = "db_pool")]
+mod miss_cache;
+#[cfg(feature = "db_pool")]
+mod statement_cache;
+#[cfg(feature = "db_pool")]
+mod write_behind;
+#[cfg(feature = "db_pool")]
+mod write_filter;
 
 #[cfg(feature = "migration")]
 pub mod migration;
@@ -21,8 +53,118 @@ pub mod migration;
 #[cfg(feature = "memory_pool")]
 pub mod memory_pool;
 
+#[cfg(feature = "dashmap")]
+pub mod dash_memory_pool;
+
+#[cfg(feature = "redis_pool")]
+pub mod redis_pool;
+
+#[cfg(feature = "cached_pool")]
+pub mod cached_pool;
+
+#[cfg(feature = "encrypted_pool")]
+pub mod encrypted_pool;
+
+#[cfg(feature = "signed_pool")]
+pub mod signed_pool;
+
+#[cfg(feature = "circuit_breaker")]
+pub mod circuit_breaker;
+
+#[cfg(feature = "compression")]
+pub mod compressed_pool;
+
+#[cfg(feature = "test-utils")]
+pub mod testing;
+
+#[cfg(feature = "pg_notify")]
+pub mod notifying_pool;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub use clock::*;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub use error::*;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub use expiry::{remaining_ttl, ExpiryHistogram};
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub use page::IdPage;
+
+#[cfg(any(feature = "db_pool", feature = "memory_pool"))]
+pub use session_id::{SessionId, SessionIdError};
+
 #[cfg(feature = "db_pool")]
 pub use db_pool::*;
 
 #[cfg(feature = "memory_pool")]
 pub use memory_pool::*;
+
+#[cfg(feature = "dashmap")]
+pub use dash_memory_pool::*;
+
+#[cfg(feature = "redis_pool")]
+pub use redis_pool::*;
+
+#[cfg(feature = "cached_pool")]
+pub use cached_pool::*;
+
+#[cfg(feature = "encrypted_pool")]
+pub use encrypted_pool::*;
+
+#[cfg(feature = "signed_pool")]
+pub use signed_pool::*;
+
+#[cfg(feature = "circuit_breaker")]
+pub use circuit_breaker::*;
+
+#[cfg(feature = "compression")]
+pub use compressed_pool::*;
+
+#[cfg(feature = "test-utils")]
+pub use testing::*;
+
+#[cfg(feature = "pg_notify")]
+pub use notifying_pool::*;
+
+//every pool ends up shared across the tasks of an axum server, this stops compiling once one of
+//them is no longer Send + Sync, e.g. after a lock is swapped for one that is not
+#[cfg(any(
+    feature = "db_pool",
+    feature = "memory_pool",
+    feature = "redis_pool",
+    feature = "test-utils",
+    feature = "encrypted_pool",
+    feature = "signed_pool",
+    feature = "circuit_breaker",
+    feature = "compression"
+))]
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[cfg(feature = "db_pool")]
+    assert_send_sync::<DbPool>();
+    #[cfg(feature = "memory_pool")]
+    assert_send_sync::<MemoryPool>();
+    #[cfg(feature = "dashmap")]
+    assert_send_sync::<DashMemoryPool>();
+    #[cfg(feature = "redis_pool")]
+    assert_send_sync::<RedisPool>();
+    #[cfg(feature = "cached_pool")]
+    assert_send_sync::<CachedDbPool>();
+    #[cfg(feature = "pg_notify")]
+    assert_send_sync::<NotifyingDbPool>();
+    #[cfg(feature = "test-utils")]
+    assert_send_sync::<MockPool>();
+
+    //the wrappers are Send + Sync whenever the pool they wrap is
+    #[cfg(feature = "encrypted_pool")]
+    assert_send_sync::<EncryptedPool<()>>();
+    #[cfg(feature = "signed_pool")]
+    assert_send_sync::<SignedPool<()>>();
+    #[cfg(feature = "circuit_breaker")]
+    assert_send_sync::<CircuitBreakerPool<()>>();
+    #[cfg(feature = "compression")]
+    assert_send_sync::<CompressedPool<()>>();
+};
@@ -0,0 +1,85 @@
+//operation counters and timings through the metrics facade, nothing is recorded without the
+//metrics feature
+//  session_store_operations_total{operation, backend}
+//  session_store_duration_seconds{operation, backend}
+//  session_store_load_total{result = "hit" | "miss", backend}
+//  session_store_active_sessions{backend}
+//  session_store_skipped_writes_total{backend}
+
+//counts the operation and records how long it took once dropped
+pub(crate) struct Timer {
+    #[cfg(feature = "metrics")]
+    backend: &'static str,
+    #[cfg(feature = "metrics")]
+    operation: &'static str,
+    #[cfg(feature = "metrics")]
+    start: std::time::Instant,
+}
+
+impl Timer {
+    #[inline(always)]
+    pub(crate) fn start(backend: &'static str, operation: &'static str) -> Timer {
+        #[cfg(not(feature = "metrics"))]
+        let _ = (backend, operation);
+
+        Timer {
+            #[cfg(feature = "metrics")]
+            backend,
+            #[cfg(feature = "metrics")]
+            operation,
+            #[cfg(feature = "metrics")]
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for Timer {
+    fn drop(&mut self) {
+        metrics::counter!(
+            "session_store_operations_total",
+            "operation" => self.operation,
+            "backend" => self.backend
+        )
+        .increment(1);
+        metrics::histogram!(
+            "session_store_duration_seconds",
+            "operation" => self.operation,
+            "backend" => self.backend
+        )
+        .record(self.start.elapsed().as_secs_f64());
+    }
+}
+
+#[inline(always)]
+pub(crate) fn load_result(backend: &'static str, hit: bool) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        "session_store_load_total",
+        "result" => if hit { "hit" } else { "miss" },
+        "backend" => backend
+    )
+    .increment(1);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = (backend, hit);
+}
+
+#[inline(always)]
+pub(crate) fn active_sessions(backend: &'static str, count: i64) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!("session_store_active_sessions", "backend" => backend).set(count as f64);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = (backend, count);
+}
+
+#[inline(always)]
+#[cfg(feature = "db_pool")]
+pub(crate) fn skipped_write(backend: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("session_store_skipped_writes_total", "backend" => backend).increment(1);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = backend;
+}
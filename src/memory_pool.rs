@@ -1,168 +1,892 @@
 use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
 };
 
 use axum_session::{DatabaseError, DatabasePool};
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, Utc};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+use crate::{
+    clock::Clock,
+    context::Context,
+    error::{ErrorKind, SessionError},
+    expiry::{
+        bucket_seconds, bucket_start, clamp_expires, max_lifetime, parse_expires, ExpiryHistogram,
+    },
+    measure::{self, Timer},
+    page::IdPage,
+    session_id::{self, SessionId},
+    trace::record,
+};
+
+/// A session as a `MemoryPool` holds it, see `MemoryPool::drain`.
 #[derive(Clone, Debug, Default)]
-struct SessionValue {
-    id: String,
-    session: String,
-    expires: i64,
+pub struct SessionValue {
+    pub id: String,
+    pub session: String,
+    /// Unix timestamp, `i64::MAX` for a session that never expires.
+    pub expires: i64,
+}
+
+//all maps live behind a single lock so they can never disagree with each other
+#[derive(Debug, Default)]
+struct MemoryStore {
+    entries: HashMap<String, SessionValue>,
+    //ordered so the expired buckets can be split off without looking at the live ones
+    expires: BTreeMap<i64, Vec<String>>,
+    capacity: Option<usize>,
+    //use order, only tracked when a capacity is set, recency maps a tick to the id used at it
+    clock: u64,
+    recency: BTreeMap<u64, String>,
+    last_used: HashMap<String, u64>,
+    evictions: u64,
+    //deleted ids store() must not bring back, mapped to when that stops
+    tombstones: HashMap<String, i64>,
+    //soft deleted sessions kept for purge_deleted(), mapped to when they were deleted
+    deleted: HashMap<String, (SessionValue, i64)>,
+}
+
+impl MemoryStore {
+    fn insert(&mut self, value: SessionValue) {
+        let id = value.id.clone();
+        let expiry = value.expires;
+
+        //storing a soft deleted id brings it back, like DbPool
+        self.deleted.remove(&id);
+
+        //nothing fits, the session is dropped as soon as it is stored
+        if self.capacity == Some(0) {
+            self.evictions += 1;
+            return;
+        }
+
+        if let Some(max_sessions) = self.capacity {
+            if !self.entries.contains_key(&id) {
+                while self.entries.len() >= max_sessions {
+                    let Some((_, oldest)) = self.recency.pop_first() else {
+                        break;
+                    };
+                    self.remove(&oldest);
+                    self.evictions += 1;
+                }
+            }
+        }
+
+        if let Some(previous) = self.entries.insert(id.clone(), value) {
+            self.unindex(&previous.id, previous.expires);
+        }
+
+        self.expires.entry(expiry).or_default().push(id.clone());
+        self.touch(&id);
+    }
+
+    fn remove(&mut self, id: &str) -> Option<SessionValue> {
+        let entry = self.entries.remove(id)?;
+        self.unindex(id, entry.expires);
+        self.forget(id);
+        Some(entry)
+    }
+
+    fn unindex(&mut self, id: &str, expires: i64) {
+        if let Some(ids) = self.expires.get_mut(&expires) {
+            ids.retain(|e| e != id);
+
+            //with an expiry second per session, empty buckets would pile up forever
+            if ids.is_empty() {
+                self.expires.remove(&expires);
+            }
+        }
+    }
+
+    //moves a live session to another expiry bucket, the payload is left alone
+    fn extend(&mut self, id: &str, expires: i64, now: i64) -> bool {
+        let Some(entry) = self.entries.get_mut(id).filter(|entry| entry.expires > now) else {
+            return false;
+        };
+
+        let previous = std::mem::replace(&mut entry.expires, expires);
+        self.unindex(id, previous);
+        self.expires.entry(expires).or_default().push(id.to_owned());
+        self.touch(id);
+        true
+    }
+
+    fn delete_expired(&mut self, now: i64, soft_delete: bool) -> Vec<String> {
+        let live = self.expires.split_off(&now.saturating_add(1));
+        let candidates: Vec<String> = std::mem::replace(&mut self.expires, live)
+            .into_values()
+            .flatten()
+            .collect();
+
+        //the entry itself decides, an id in an expired bucket may belong to a session stored again since
+        let mut expired_entries = Vec::with_capacity(candidates.len());
+        for id in candidates {
+            if self.entries.get(&id).is_some_and(|v| v.expires <= now) {
+                if let Some(entry) = self.entries.remove(&id) {
+                    self.retire(entry, now, soft_delete);
+                }
+                self.forget(&id);
+                expired_entries.push(id);
+            }
+        }
+
+        self.tombstones.retain(|_, &mut until| until > now);
+
+        expired_entries
+    }
+
+    fn retire(&mut self, entry: SessionValue, now: i64, soft_delete: bool) {
+        if soft_delete {
+            self.deleted.insert(entry.id.clone(), (entry, now));
+        }
+    }
+
+    fn is_buried(&self, id: &str, now: i64) -> bool {
+        self.tombstones.get(id).is_some_and(|&until| until > now)
+    }
+
+    fn touch(&mut self, id: &str) {
+        if self.capacity.is_none() {
+            return;
+        }
+
+        self.clock += 1;
+        if let Some(previous) = self.last_used.insert(id.to_owned(), self.clock) {
+            self.recency.remove(&previous);
+        }
+        self.recency.insert(self.clock, id.to_owned());
+    }
+
+    fn forget(&mut self, id: &str) {
+        if let Some(previous) = self.last_used.remove(id) {
+            self.recency.remove(&previous);
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct MemoryPool {
-    entries: Arc<RwLock<HashMap<String, SessionValue>>>,
-    expires: Arc<RwLock<HashMap<i64, Vec<String>>>>,
+    store: Arc<RwLock<MemoryStore>>,
+    max_expires: Option<i64>,
+    auto_handles_expiry: bool,
+    clock: Option<Arc<dyn Clock>>,
+    tombstone_grace: Option<Duration>,
+    max_lifetime: Option<chrono::Duration>,
+    #[cfg(feature = "soft_delete")]
+    soft_delete: bool,
 }
 
 impl MemoryPool {
     pub fn new() -> MemoryPool {
         MemoryPool::default()
     }
+
+    /// Holds at most `max_sessions`, evicting the least recently used session when full.
+    /// With 0 it holds nothing, every store() counts as an eviction right away.
+    pub fn with_capacity(max_sessions: usize) -> MemoryPool {
+        let store = MemoryStore {
+            capacity: Some(max_sessions),
+            ..Default::default()
+        };
+
+        MemoryPool {
+            store: Arc::new(RwLock::new(store)),
+            ..Default::default()
+        }
+    }
+
+    /// Number of sessions dropped to stay within the capacity, expired sessions are not counted.
+    pub async fn eviction_count(&self) -> u64 {
+        self.read().await.evictions
+    }
+
+    /// Expiry timestamps past `max_expires` are treated as never expiring.
+    pub fn with_max_expires(mut self, max_expires: i64) -> MemoryPool {
+        self.max_expires = Some(max_expires);
+        self
+    }
+
+    /// Takes the current time from `clock` instead of the system time, for tests.
+    /// Set it before `with_auto_expiry` so the cleanup task uses it too.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> MemoryPool {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// store() clamps every expiry to now + `max_ttl`, sessions that would never expire included.
+    pub fn with_max_ttl(self, max_ttl: Duration) -> MemoryPool {
+        self.with_max_lifetime(max_lifetime(max_ttl))
+    }
+
+    /// `with_max_ttl` with a chrono Duration, like `DbPool::with_max_lifetime`.
+    pub fn with_max_lifetime(mut self, max_lifetime: chrono::Duration) -> MemoryPool {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+
+    /// Remembers deleted ids for `grace`, store() does not bring them back so a request still
+    /// in flight after a logout can not resurrect the session.
+    pub fn with_tombstones(mut self, grace: Duration) -> MemoryPool {
+        self.tombstone_grace = Some(grace);
+        self
+    }
+
+    /// Removed sessions are kept aside instead of dropped until purge_deleted(), like
+    /// `DbPool::with_soft_delete`. Reads never see them, evictions still drop them.
+    /// Set it before `with_auto_expiry` so the cleanup task uses it too.
+    #[cfg(feature = "soft_delete")]
+    pub fn with_soft_delete(mut self, soft_delete: bool) -> MemoryPool {
+        self.soft_delete = soft_delete;
+        self
+    }
+
+    /// Drops the sessions soft deleted longer than `older_than` ago, returning how many.
+    #[cfg(feature = "soft_delete")]
+    pub async fn purge_deleted(
+        &self,
+        older_than: Duration,
+        _table_name: &str,
+    ) -> Result<u64, SessionError> {
+        let before = self
+            .now()
+            .timestamp()
+            .saturating_sub(i64::try_from(older_than.as_secs()).unwrap_or(i64::MAX));
+        let mut store = self.write().await;
+        let held = store.deleted.len();
+
+        store
+            .deleted
+            .retain(|_, &mut (_, deleted_at)| deleted_at >= before);
+        Ok((held - store.deleted.len()) as u64)
+    }
+
+    /// delete_by_expiry(), returning how many sessions were removed instead of their ids.
+    pub async fn purge_expired(&self, _table_name: &str) -> Result<u64, SessionError> {
+        let ids = self
+            .write()
+            .await
+            .delete_expired(self.now().timestamp(), self.soft_delete());
+
+        Ok(ids.len() as u64)
+    }
+
+    /// Spawns a task deleting expired sessions every `interval`, so axum_session does not have to.
+    /// The task stops once every clone of the pool is dropped. Must be called within a tokio runtime.
+    pub fn with_auto_expiry(mut self, interval: Duration) -> MemoryPool {
+        let store = Arc::downgrade(&self.store);
+        let clock = self.clock.clone();
+        let soft_delete = self.soft_delete();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                interval.tick().await;
+
+                let Some(store) = store.upgrade() else {
+                    break;
+                };
+
+                let now = clock.as_ref().map_or_else(Utc::now, |clock| clock.now());
+                store
+                    .write()
+                    .await
+                    .delete_expired(now.timestamp(), soft_delete);
+            }
+        });
+
+        self.auto_handles_expiry = true;
+        self
+    }
+
+    /// Tells axum_session whether expired sessions are removed without its delete_by_expiry
+    /// sweep, like `DbPool::with_auto_handles_expiry`. `with_auto_expiry` turns it on, set it
+    /// afterwards to override.
+    pub fn with_auto_handles_expiry(mut self, auto_handles_expiry: bool) -> MemoryPool {
+        self.auto_handles_expiry = auto_handles_expiry;
+        self
+    }
+
+    /// Deletes the session, returning whether it was held. Expired sessions not cleaned up yet count.
+    pub async fn remove_session(&self, id: &str, table_name: &str) -> Result<bool, SessionError> {
+        let context = Context::new("delete_one_by_id", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Delete)?;
+
+        let now = self.now().timestamp();
+        let mut store = self.write().await;
+
+        if let Some(grace) = self.tombstone_grace {
+            let until = now.saturating_add(i64::try_from(grace.as_secs()).unwrap_or(i64::MAX));
+            store.tombstones.insert(id.to_owned(), until);
+        }
+
+        let Some(entry) = store.remove(id) else {
+            return Ok(false);
+        };
+
+        store.retire(entry, now, self.soft_delete());
+        Ok(true)
+    }
+
+    /// Deletes every session in `ids` under one write lock, returning how many were held.
+    pub async fn delete_many_by_ids(
+        &self,
+        ids: &[&str],
+        table_name: &str,
+    ) -> Result<u64, SessionError> {
+        let context = Context::new("delete_many_by_ids", table_name);
+        for id in ids {
+            self.check_id(id, &context.with_id(id), ErrorKind::Delete)?;
+        }
+
+        let now = self.now().timestamp();
+        let mut store = self.write().await;
+        let mut deleted = 0;
+
+        for &id in ids {
+            if let Some(grace) = self.tombstone_grace {
+                let until = now.saturating_add(i64::try_from(grace.as_secs()).unwrap_or(i64::MAX));
+                store.tombstones.insert(id.to_owned(), until);
+            }
+
+            if let Some(entry) = store.remove(id) {
+                store.retire(entry, now, self.soft_delete());
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Empties the pool and returns every session it held, keyed by id, under one write lock so
+    /// no store() can slip in between. Expired sessions not cleaned up yet are included, soft
+    /// deleted ones are dropped.
+    pub async fn drain(&self) -> HashMap<String, SessionValue> {
+        let mut store = self.write().await;
+        store.deleted.clear();
+        store.expires.clear();
+        store.recency.clear();
+        store.last_used.clear();
+        std::mem::take(&mut store.entries)
+    }
+
+    /// Moves a live session to `new_id` under one write lock, like `DbPool::rotate`.
+    pub async fn rotate(
+        &self,
+        old_id: &str,
+        new_id: &str,
+        table_name: &str,
+    ) -> Result<bool, SessionError> {
+        let context = Context::new("rotate", table_name).with_id(old_id);
+        self.check_id(old_id, &context, ErrorKind::Insert)?;
+        self.check_id(new_id, &context, ErrorKind::Insert)?;
+        let now = self.now().timestamp();
+        let mut store = self.write().await;
+
+        if store
+            .entries
+            .get(old_id)
+            .is_none_or(|entry| entry.expires <= now)
+        {
+            return Ok(false);
+        }
+
+        if store.entries.contains_key(new_id) {
+            return Err(context.invalid(
+                ErrorKind::Insert,
+                "a session is already stored under the new id",
+            ));
+        }
+
+        if let Some(grace) = self.tombstone_grace {
+            let until = now.saturating_add(i64::try_from(grace.as_secs()).unwrap_or(i64::MAX));
+            store.tombstones.insert(old_id.to_owned(), until);
+        }
+
+        if let Some(entry) = store.remove(old_id) {
+            store.insert(SessionValue {
+                id: new_id.to_owned(),
+                ..entry.clone()
+            });
+            store.retire(entry, now, self.soft_delete());
+        }
+
+        Ok(true)
+    }
+
+    /// Moves the expiry of a live session without rewriting it, false when there is none.
+    pub async fn touch(
+        &self,
+        id: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<bool, SessionError> {
+        let context = Context::new("touch", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Insert)?;
+        let expires = self.expiry(expires, &context)?;
+        let now = self.now().timestamp();
+
+        Ok(self.write().await.extend(id, expires, now))
+    }
+
+    /// Stores every `(id, session, expires)` under a single write lock.
+    pub async fn store_many(
+        &self,
+        sessions: &[(&str, &str, i64)],
+        table_name: &str,
+    ) -> Result<(), SessionError> {
+        //parse everything first so a bad expiry leaves the store untouched
+        let mut models = Vec::with_capacity(sessions.len());
+        for &(id, session, expires) in sessions {
+            let context = Context::new("store_many", table_name).with_id(id);
+            models.push(self.session_value(id, session, expires, &context)?);
+        }
+
+        let now = self.now().timestamp();
+        let mut store = self.write().await;
+        for model in models {
+            if !store.is_buried(&model.id, now) {
+                store.insert(model);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// load() together with the expiry, None as the expiry for a session that never expires.
+    /// See `remaining_ttl` for the time left.
+    pub async fn load_with_expiry(
+        &self,
+        id: &str,
+        table_name: &str,
+    ) -> Result<Option<(String, Option<DateTime<Utc>>)>, SessionError> {
+        let context = Context::new("load_with_expiry", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
+
+        Ok(self.load_session(id).await.map(|(session, expires)| {
+            //i64::MAX is how a session that never expires is kept
+            let expires = (expires != i64::MAX)
+                .then(|| DateTime::from_timestamp(expires, 0))
+                .flatten();
+            (session, expires)
+        }))
+    }
+
+    /// Loads several sessions under one lock, ids that are missing or expired are left out.
+    pub async fn load_many(
+        &self,
+        ids: &[&str],
+        table_name: &str,
+    ) -> Result<HashMap<String, String>, SessionError> {
+        let context = Context::new("load_many", table_name);
+        for id in ids {
+            self.check_id(id, &context.with_id(id), ErrorKind::Select)?;
+        }
+
+        let now = self.now().timestamp();
+        let live = |store: &MemoryStore| -> HashMap<String, String> {
+            ids.iter()
+                .filter_map(|id| store.entries.get(*id))
+                .filter(|model| model.expires > now)
+                .map(|model| (model.id.clone(), model.session.clone()))
+                .collect()
+        };
+
+        //with a capacity every use has to be recorded, which needs the write lock
+        {
+            let store = self.read().await;
+            if store.capacity.is_none() {
+                return Ok(live(&store));
+            }
+        }
+
+        let mut store = self.write().await;
+        let sessions = live(&store);
+        for id in sessions.keys() {
+            store.touch(id);
+        }
+
+        Ok(sessions)
+    }
+
+    /// One page of live session ids, ordered by id like `DbPool::get_ids_page`.
+    pub async fn get_ids_page(
+        &self,
+        limit: u64,
+        offset: u64,
+        _table_name: &str,
+    ) -> Result<Vec<String>, SessionError> {
+        let now = self.now().timestamp();
+        let store = self.read().await;
+
+        let mut ids: Vec<&String> = store
+            .entries
+            .values()
+            .filter(|model| model.expires > now)
+            .map(|model| &model.id)
+            .collect();
+        ids.sort_unstable();
+
+        Ok(ids
+            .into_iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    /// The live session ids following `after` in id order, like `DbPool::get_ids_after`.
+    pub async fn get_ids_after(
+        &self,
+        after: Option<&str>,
+        limit: u64,
+        _table_name: &str,
+    ) -> Result<IdPage, SessionError> {
+        let limit = limit.max(1);
+        let now = self.now().timestamp();
+        let store = self.read().await;
+
+        let mut ids: Vec<&String> = store
+            .entries
+            .values()
+            .filter(|model| model.expires > now)
+            .filter(|model| after.is_none_or(|after| model.id.as_str() > after))
+            .map(|model| &model.id)
+            .collect();
+        ids.sort_unstable();
+
+        let ids = ids
+            .into_iter()
+            .take(usize::try_from(limit.saturating_add(1)).unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+
+        Ok(IdPage::new(ids, limit))
+    }
+
+    /// Number of live sessions, the total to page through with `get_ids_page`.
+    pub async fn get_ids_count(&self, _table_name: &str) -> Result<u64, SessionError> {
+        let now = self.now().timestamp();
+
+        Ok(self
+            .read()
+            .await
+            .entries
+            .values()
+            .filter(|model| model.expires > now)
+            .count() as u64)
+    }
+
+    /// Live sessions grouped into `bucket` long slices of the next `horizon`, like `DbPool::expiry_histogram`.
+    pub async fn expiry_histogram(
+        &self,
+        bucket: chrono::Duration,
+        horizon: chrono::Duration,
+        table_name: &str,
+    ) -> Result<ExpiryHistogram, SessionError> {
+        let context = Context::new("expiry_histogram", table_name);
+        let bucket_seconds = bucket_seconds(bucket).map_err(|err| context.wrap(err))?;
+
+        let now = self.now().timestamp();
+        let end = now.saturating_add(horizon.num_seconds());
+
+        let mut counts = BTreeMap::<i64, u64>::new();
+
+        let store = self.read().await;
+
+        //live sessions expire after now, the range must not start past its end
+        let start = now.saturating_add(1);
+        for (&expires, ids) in store.expires.range(start..end.max(start)) {
+            *counts.entry((expires - now) / bucket_seconds).or_default() += ids.len() as u64;
+        }
+
+        let never_expires = store
+            .expires
+            .get(&i64::MAX)
+            .map_or(0, |ids| ids.len() as u64);
+
+        let buckets = counts
+            .into_iter()
+            .filter_map(|(index, sessions)| {
+                bucket_start(now, bucket_seconds, index).map(|start| (start, sessions))
+            })
+            .collect();
+
+        Ok(ExpiryHistogram {
+            buckets,
+            never_expires,
+        })
+    }
+
+    fn session_value(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        context: &Context,
+    ) -> Result<SessionValue, SessionError> {
+        self.check_id(id, context, ErrorKind::Insert)?;
+
+        Ok(SessionValue {
+            id: id.to_owned(),
+            session: session.to_string(),
+            expires: self.expiry(expires, context)?,
+        })
+    }
+
+    fn check_id(&self, id: &str, context: &Context, kind: ErrorKind) -> Result<(), SessionError> {
+        session_id::validate(id, SessionId::MAX_LENGTH).map_err(|err| context.invalid(kind, err))
+    }
+
+    fn expiry(&self, expires: i64, context: &Context) -> Result<i64, SessionError> {
+        //never expiring sessions are kept with the largest possible expiry
+        let expiry = parse_expires(expires, self.max_expires).map_err(|err| context.wrap(err))?;
+        Ok(clamp_expires(expiry, self.now(), self.max_lifetime)
+            .map(|dt| dt.timestamp())
+            .unwrap_or(i64::MAX))
+    }
+
+    //the session with its expiry
+    async fn load_session(&self, id: &str) -> Option<(String, i64)> {
+        let now = self.now().timestamp();
+        {
+            let store = self.read().await;
+
+            match store.entries.get(id) {
+                //with a capacity the use has to be recorded, which needs the write lock
+                Some(model) if model.expires > now && store.capacity.is_none() => {
+                    return Some((model.session.clone(), model.expires))
+                }
+                Some(_) => {}
+                None => return None,
+            }
+        }
+
+        let mut store = self.write().await;
+
+        //re-check, the session could have changed while no lock was held
+        match store.entries.get(id) {
+            Some(model) if model.expires > now => {
+                let session = (model.session.clone(), model.expires);
+                store.touch(id);
+                Some(session)
+            }
+            //the entry is expired, drop it so it does not linger until the next delete_by_expiry
+            Some(_) => {
+                store.remove(id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock
+            .as_ref()
+            .map_or_else(Utc::now, |clock| clock.now())
+    }
+
+    fn soft_delete(&self) -> bool {
+        #[cfg(feature = "soft_delete")]
+        {
+            self.soft_delete
+        }
+
+        #[cfg(not(feature = "soft_delete"))]
+        {
+            false
+        }
+    }
+
+    async fn read(&self) -> RwLockReadGuard<'_, MemoryStore> {
+        self.store.read().await
+    }
+
+    async fn write(&self) -> RwLockWriteGuard<'_, MemoryStore> {
+        self.store.write().await
+    }
 }
 
 #[async_trait::async_trait]
 impl DatabasePool for MemoryPool {
     #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %_table_name))
+    )]
     async fn initiate(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        let _timer = Timer::start("memory", "initiate");
         Ok(())
     }
 
     #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %_table_name, deleted = tracing::field::Empty)
+        )
+    )]
     async fn delete_by_expiry(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
-        let mut expired = self
-            .expires
+        let _timer = Timer::start("memory", "delete_by_expiry");
+        let ids = self
             .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        let now = Utc::now().timestamp();
-        let expired_entries: Vec<String> = expired
-            .iter()
-            .filter(|(&k, _)| k < now)
-            .flat_map(|(_, v)| v.clone())
-            .collect();
-        expired.retain(|&k, _| k >= now);
+            .await
+            .delete_expired(self.now().timestamp(), self.soft_delete());
 
-        let mut entries = self
-            .entries
-            .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        entries.retain(|_, v| !expired_entries.contains(&v.id));
-
-        Ok(expired_entries)
+        record!("deleted", ids.len());
+        Ok(ids)
     }
 
     #[inline(always)]
-    async fn count(&self, _table_name: &str) -> Result<i64, DatabaseError> {
-        Ok(self
-            .entries
-            .read()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?
-            .len() as i64)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %table_name))
+    )]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        let _timer = Timer::start("memory", "count");
+        //expired entries stay until delete_by_expiry() or a load() finds them, like DbPool they
+        //are not counted
+        let count = self.get_ids_count(table_name).await? as i64;
+
+        measure::active_sessions("memory", count);
+        Ok(count)
     }
 
     #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %table_name, session_id = %crate::context::ShortId(id))
+        )
+    )]
     async fn store(
         &self,
         id: &str,
         session: &str,
         expires: i64,
-        _table_name: &str,
+        table_name: &str,
     ) -> Result<(), DatabaseError> {
-        let expiry = chrono::DateTime::from_timestamp(expires, 0)
-            .map(|expires| Utc.from_utc_datetime(&expires.naive_utc()))
-            .map(|dt| dt.timestamp())
-            .unwrap_or(0);
-
-        let model = SessionValue {
-            id: id.to_owned(),
-            session: session.to_string(),
-            expires: expiry,
-        };
+        let _timer = Timer::start("memory", "store");
+        let context = Context::new("store", table_name).with_id(id);
+        let model = self.session_value(id, session, expires, &context)?;
 
-        let mut entries = self
-            .entries
-            .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        entries.insert(id.to_owned(), model.clone());
+        let now = self.now().timestamp();
+        let mut store = self.write().await;
 
-        let mut expires = self
-            .expires
-            .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        expires.entry(expiry).or_default().push(id.to_owned());
+        //a logged out session stays dead, see with_tombstones()
+        if !store.is_buried(id, now) {
+            store.insert(model);
+        }
 
         Ok(())
     }
 
     #[inline(always)]
-    async fn load(&self, id: &str, _table_name: &str) -> Result<Option<String>, DatabaseError> {
-        let entries = self
-            .entries
-            .read()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        let maybe_model = entries.get(id);
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                table_name = %table_name,
+                session_id = %crate::context::ShortId(id),
+                hit = tracing::field::Empty,
+            )
+        )
+    )]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let _timer = Timer::start("memory", "load");
+        let context = Context::new("load", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
 
-        Ok(maybe_model.map(|model| model.session.clone()))
+        let session = self.load_session(id).await.map(|(session, _)| session);
+
+        record!("hit", session.is_some());
+        measure::load_result("memory", session.is_some());
+        Ok(session)
     }
 
     #[inline(always)]
-    async fn delete_one_by_id(&self, id: &str, _table_name: &str) -> Result<(), DatabaseError> {
-        let mut entries = self
-            .entries
-            .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        if let Some(entry) = entries.remove(id) {
-            let mut expires = self
-                .expires
-                .write()
-                .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-            expires.entry(entry.expires).and_modify(|v| {
-                v.retain(|e| e != id);
-            });
-        }
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %table_name, session_id = %crate::context::ShortId(id))
+        )
+    )]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        let _timer = Timer::start("memory", "delete_one_by_id");
+        self.remove_session(id, table_name).await?;
 
         Ok(())
     }
 
     #[inline(always)]
-    async fn exists(&self, id: &str, _table_name: &str) -> Result<bool, DatabaseError> {
-        let entries = self
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(table_name = %table_name, session_id = %crate::context::ShortId(id))
+        )
+    )]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        let _timer = Timer::start("memory", "exists");
+        let context = Context::new("exists", table_name).with_id(id);
+        self.check_id(id, &context, ErrorKind::Select)?;
+
+        let store = self.read().await;
+        let now = self.now().timestamp();
+        Ok(store
             .entries
-            .read()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        Ok(entries.contains_key(id))
+            .get(id)
+            .is_some_and(|model| model.expires > now))
     }
 
     #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %_table_name))
+    )]
     async fn delete_all(&self, _table_name: &str) -> Result<(), DatabaseError> {
-        let mut entries = self
-            .entries
-            .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        entries.clear();
-        let mut expires = self
-            .expires
-            .write()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        expires.clear();
+        let _timer = Timer::start("memory", "delete_all");
+        let mut store = self.write().await;
+        store.entries.clear();
+        store.expires.clear();
+        store.recency.clear();
+        store.last_used.clear();
+        store.deleted.clear();
         Ok(())
     }
 
     #[inline(always)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, err, fields(table_name = %_table_name))
+    )]
     async fn get_ids(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
-        let entries = self
-            .entries
+        let _timer = Timer::start("memory", "get_ids");
+        let now = self.now().timestamp();
+
+        Ok(self
             .read()
-            .map_err(|_| DatabaseError::GenericCreateError("Lock poisoned".into()))?;
-        Ok(entries.keys().cloned().collect())
+            .await
+            .entries
+            .values()
+            .filter(|model| model.expires > now)
+            .map(|model| model.id.clone())
+            .collect())
     }
 
     #[inline(always)]
     fn auto_handles_expiry(&self) -> bool {
-        false
+        self.auto_handles_expiry
     }
 }
@@ -1,8 +1,53 @@
 use sea_orm::DbBackend;
 use sea_orm_migration::prelude::*;
 
-#[derive(DeriveMigrationName)]
-pub struct Migration;
+/// Options for the initial migration, the defaults match `DbPool::initiate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MigrationConfig {
+    /// Length of the id column, the same value has to go to `DbPool::with_id_length`.
+    pub id_max_length: u16,
+}
+
+impl Default for MigrationConfig {
+    fn default() -> MigrationConfig {
+        MigrationConfig { id_max_length: 128 }
+    }
+}
+
+/// Postgres only options for the initial migration, ignored on other backends.
+#[cfg(feature = "postgres")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PostgresMigrationConfig {
+    /// Creates the table UNLOGGED, see `DbPool::with_unlogged`.
+    pub unlogged: bool,
+}
+
+#[derive(DeriveMigrationName, Default)]
+pub struct Migration {
+    config: MigrationConfig,
+    #[cfg(feature = "postgres")]
+    postgres: PostgresMigrationConfig,
+}
+
+impl Migration {
+    /// The migration with a custom config, run it from your own `MigratorTrait` impl.
+    pub fn with_config(config: MigrationConfig) -> Migration {
+        Migration {
+            config,
+            #[cfg(feature = "postgres")]
+            postgres: PostgresMigrationConfig::default(),
+        }
+    }
+
+    /// The migration with Postgres only options, run it from your own `MigratorTrait` impl.
+    #[cfg(feature = "postgres")]
+    pub fn postgres(config: PostgresMigrationConfig) -> Migration {
+        Migration {
+            postgres: config,
+            ..Migration::default()
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
@@ -10,43 +55,77 @@ impl MigrationTrait for Migration {
         let db = manager.get_connection();
         let backend = db.get_database_backend();
 
-        let mut result = manager
-            .create_table(
-                Table::create()
-                    .table(Sessions::Table)
-                    .if_not_exists()
-                    .col(
-                        ColumnDef::new(Sessions::Id)
-                            .string_len(128)
-                            .not_null()
-                            .primary_key(),
-                    )
-                    .col(ColumnDef::new(Sessions::Expires).date_time().null())
-                    .col(ColumnDef::new(Sessions::Session).text().not_null())
-                    .to_owned(),
+        let create_table = Table::create()
+            .table(Sessions::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(Sessions::Id)
+                    .string_len(u32::from(self.config.id_max_length))
+                    .not_null()
+                    .primary_key(),
             )
+            .col(ColumnDef::new(Sessions::Expires).date_time().null())
+            .col(ColumnDef::new(Sessions::Session).text().not_null())
+            .to_owned();
+
+        //sea_query has no UNLOGGED, the rendered statement gets it the way DbPool::initiate() does
+        #[cfg(feature = "postgres")]
+        if self.postgres.unlogged && backend == DbBackend::Postgres {
+            let create_table = backend.build(&create_table);
+
+            db.execute(sea_orm::Statement {
+                sql: create_table
+                    .sql
+                    .replacen("CREATE TABLE", "CREATE UNLOGGED TABLE", 1),
+                ..create_table
+            })
             .await?;
 
-        if backend != DbBackend::Sqlite {
-            let foreign_key = sea_query::Index::create()
-                .name("sessions_expires_idx")
-                .col(Sessions::Expires)
-                .if_not_exists()
-                .to_owned();
+            manager.create_index(expires_index()).await?;
+            return Ok(());
+        }
+
+        let mut result = manager.create_table(create_table).await?;
 
-            result = manager.create_index(foreign_key).await?;
+        //sqlite never got the index from this migration and an applied migration must not change,
+        //DbPool::initiate() creates it there; down() mirrors this and leaves sqlite to drop_table
+        if backend != DbBackend::Sqlite {
+            result = manager.create_index(expires_index()).await?;
         }
 
         Ok(result)
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_connection().get_database_backend();
+
+        //dropping the table would take the index with it, dropped first to undo up() step by step
+        if backend != DbBackend::Sqlite {
+            manager
+                .drop_index(
+                    sea_query::Index::drop()
+                        .name("sessions_expires_idx")
+                        .table(Sessions::Table)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
         manager
             .drop_table(Table::drop().table(Sessions::Table).to_owned())
             .await
     }
 }
 
+fn expires_index() -> IndexCreateStatement {
+    sea_query::Index::create()
+        .name("sessions_expires_idx")
+        .table(Sessions::Table)
+        .col(Sessions::Expires)
+        .if_not_exists()
+        .to_owned()
+}
+
 #[derive(Iden)]
 #[iden = "sessions"]
 enum Sessions {
@@ -0,0 +1,70 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        for column in [Sessions::CreatedAt, Sessions::UpdatedAt] {
+            let mut column_def = ColumnDef::new(column);
+            super::timestamp(&mut column_def, backend);
+
+            //sqlite can not add a column with a non-constant default, existing rows are filled below
+            if backend != DbBackend::Sqlite {
+                column_def.not_null().default(Expr::current_timestamp());
+            }
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Sessions::Table)
+                        .add_column(column_def)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        if backend == DbBackend::Sqlite {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Sessions::Table)
+                        .value(Sessions::CreatedAt, Expr::current_timestamp())
+                        .value(Sessions::UpdatedAt, Expr::current_timestamp())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //one column per statement, sqlite does not support multiple alter options
+        for column in [Sessions::CreatedAt, Sessions::UpdatedAt] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Sessions::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "sessions"]
+enum Sessions {
+    Table,
+    CreatedAt,
+    UpdatedAt,
+}
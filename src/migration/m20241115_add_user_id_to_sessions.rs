@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+//used by DbPool::store_with_user, NULL for sessions stored without an owner
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .add_column(ColumnDef::new(Sessions::UserId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        //same name as the one DbPool::initiate creates
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("sessions_user_id_idx")
+                    .table(Sessions::Table)
+                    .col(Sessions::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("sessions_user_id_idx")
+                    .table(Sessions::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .drop_column(Sessions::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "sessions"]
+enum Sessions {
+    Table,
+    UserId,
+}
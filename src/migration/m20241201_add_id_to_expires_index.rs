@@ -0,0 +1,63 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+//rebuilds sessions_expires_idx on (expires, id) like DbPool::initiate creates it now, so the
+//expired ids can be read from the index without visiting the rows
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_expires_index(manager).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("sessions_expires_idx")
+                    .table(Sessions::Table)
+                    .col(Sessions::Expires)
+                    .col(Sessions::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_expires_index(manager).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("sessions_expires_idx")
+                    .table(Sessions::Table)
+                    .col(Sessions::Expires)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+//sqlite only has the index when DbPool::initiate() ran, the first migration skips it there
+async fn drop_expires_index(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let backend = manager.get_connection().get_database_backend();
+
+    let mut drop_index = Index::drop();
+    drop_index
+        .name("sessions_expires_idx")
+        .table(Sessions::Table);
+
+    if backend == DbBackend::Sqlite {
+        drop_index.if_exists();
+    }
+
+    manager.drop_index(drop_index.to_owned()).await
+}
+
+#[derive(Iden)]
+#[iden = "sessions"]
+enum Sessions {
+    Table,
+    Id,
+    Expires,
+}
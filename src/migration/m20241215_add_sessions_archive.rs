@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+//filled by delete_by_expiry() with DbPool::with_archival, deleted_at says when a row was archived
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_connection().get_database_backend();
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SessionsArchive::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SessionsArchive::Id)
+                            .string_len(128)
+                            .not_null(),
+                    )
+                    .col(
+                        super::timestamp(&mut ColumnDef::new(SessionsArchive::Expires), backend)
+                            .null(),
+                    )
+                    .col(ColumnDef::new(SessionsArchive::Session).text().not_null())
+                    .col(
+                        super::timestamp(&mut ColumnDef::new(SessionsArchive::CreatedAt), backend)
+                            .null(),
+                    )
+                    .col(
+                        super::timestamp(&mut ColumnDef::new(SessionsArchive::UpdatedAt), backend)
+                            .null(),
+                    )
+                    .col(ColumnDef::new(SessionsArchive::UserId).string().null())
+                    .col(
+                        super::timestamp(&mut ColumnDef::new(SessionsArchive::DeletedAt), backend)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        //same name as the one DbPool::initiate creates
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("sessions_archive_id_idx")
+                    .table(SessionsArchive::Table)
+                    .col(SessionsArchive::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //the index goes with the table
+        manager
+            .drop_table(Table::drop().table(SessionsArchive::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SessionsArchive {
+    Table,
+    Id,
+    Expires,
+    Session,
+    CreatedAt,
+    UpdatedAt,
+    UserId,
+    DeletedAt,
+}
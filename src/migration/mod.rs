@@ -1,2 +1,36 @@
 mod m20240912_321949_session;
+pub mod m20241001_add_metadata_to_sessions;
+pub mod m20241101_add_deleted_at_to_sessions;
+pub mod m20241115_add_user_id_to_sessions;
+pub mod m20241201_add_id_to_expires_index;
+pub mod m20241215_add_sessions_archive;
 pub use m20240912_321949_session::*;
+
+use sea_orm::DbBackend;
+use sea_orm_migration::{prelude::ColumnDef, MigrationTrait, MigratorTrait};
+
+//every migration in the order it has to run
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20240912_321949_session::Migration::default()),
+            Box::new(m20241001_add_metadata_to_sessions::Migration),
+            Box::new(m20241101_add_deleted_at_to_sessions::Migration),
+            Box::new(m20241115_add_user_id_to_sessions::Migration),
+            Box::new(m20241201_add_id_to_expires_index::Migration),
+            Box::new(m20241215_add_sessions_archive::Migration),
+        ]
+    }
+}
+
+//the timestamp columns DbPool::initiate() creates, DATETIME on MySQL whose TIMESTAMP ends in 2038
+fn timestamp(column_def: &mut ColumnDef, backend: DbBackend) -> &mut ColumnDef {
+    if backend == DbBackend::MySql {
+        column_def.date_time()
+    } else {
+        column_def.timestamp_with_time_zone()
+    }
+}
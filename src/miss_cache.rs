@@ -0,0 +1,86 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+//ids load() found no session for, answered without a query until the entry runs out,
+//see DbPool::with_miss_cache
+#[derive(Debug)]
+pub(crate) struct MissCache {
+    capacity: usize,
+    ttl: Duration,
+    //keyed by table name and id, mapped to when the miss was seen
+    misses: Mutex<HashMap<(String, String), Instant>>,
+    //bumped by every remove(), a miss seen before a write landed must not be cached after it
+    generation: AtomicU64,
+}
+
+impl MissCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> MissCache {
+        MissCache {
+            capacity,
+            ttl,
+            misses: Mutex::default(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn contains(&self, table_name: &str, id: &str) -> bool {
+        let key = (table_name.to_owned(), id.to_owned());
+        let mut misses = self.misses();
+
+        match misses.get(&key) {
+            Some(seen) if seen.elapsed() < self.ttl => true,
+            Some(_) => {
+                misses.remove(&key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    //taken before the query whose miss is then passed to insert()
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    //a write landed since `since`, the query may have run before it, so the miss is dropped.
+    //one counter for all ids, a write to another id only costs a query
+    pub(crate) fn insert(&self, table_name: &str, id: &str, since: u64) {
+        let mut misses = self.misses();
+
+        if self.generation() != since {
+            return;
+        }
+
+        if misses.len() >= self.capacity {
+            misses.retain(|_, seen| seen.elapsed() < self.ttl);
+
+            //rather forget misses than grow without bound, the next one is just a query again
+            if misses.len() >= self.capacity {
+                return;
+            }
+        }
+
+        misses.insert((table_name.to_owned(), id.to_owned()), Instant::now());
+    }
+
+    pub(crate) fn remove<'a>(&self, table_name: &str, ids: impl IntoIterator<Item = &'a str>) {
+        let mut misses = self.misses();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        for id in ids {
+            misses.remove(&(table_name.to_owned(), id.to_owned()));
+        }
+    }
+
+    fn misses(&self) -> MutexGuard<'_, HashMap<(String, String), Instant>> {
+        self.misses.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,193 @@
+use std::{sync::Arc, time::Duration};
+
+use axum_session::{DatabaseError, DatabasePool};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use sqlx::postgres::PgListener;
+use tokio::task::JoinHandle;
+
+use crate::{context::Context, error::ErrorKind, trace::Traced, DbPool, MemoryPool, SessionError};
+
+/// The channel `NotifyingDbPool` announces written and deleted session ids on.
+pub const INVALIDATION_CHANNEL: &str = "session_invalidated";
+
+/// The channel `NotifyingDbPool` announces delete_all() on, the payload is the table name.
+pub const CLEAR_CHANNEL: &str = "sessions_cleared";
+
+//how long the listener waits before it connects again after a failed attempt
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+//announces every store() and delete_one_by_id() with pg_notify() once it is written, so every
+//instance can drop the session from its memory cache, see subscribe(). a store() queued by
+//with_write_behind() is announced by the flush writing it. postgres only, other backends fail
+//the write
+#[derive(Clone, Debug)]
+pub struct NotifyingDbPool {
+    db: DbPool,
+}
+
+impl NotifyingDbPool {
+    pub fn new(db: DbPool) -> NotifyingDbPool {
+        NotifyingDbPool {
+            db: db.with_write_notifications(),
+        }
+    }
+
+    /// Evicts every announced session from `cache`, and all of them on delete_all(), until the
+    /// returned task is aborted.
+    /// Announcements sent while the listener is disconnected are lost, so the whole cache is
+    /// cleared whenever the connection drops.
+    pub async fn subscribe(&self, cache: Arc<MemoryPool>) -> Result<JoinHandle<()>, SessionError> {
+        let context = Context::new("subscribe", INVALIDATION_CHANNEL);
+
+        //get_postgres_connection_pool() panics on anything else
+        let pool = match self.db.connection() {
+            connection @ DatabaseConnection::SqlxPostgresPoolConnection(_) => {
+                connection.get_postgres_connection_pool()
+            }
+            _ => return Err(context.setup("LISTEN needs a PostgreSQL connection")),
+        };
+
+        let mut listener = PgListener::connect_with(pool)
+            .await
+            .map_err(|err| context.setup(err))?;
+        listener
+            .listen_all([INVALIDATION_CHANNEL, CLEAR_CHANNEL])
+            .await
+            .map_err(|err| context.setup(err))?;
+
+        Ok(tokio::spawn(async move {
+            loop {
+                match listener.try_recv().await {
+                    Ok(Some(notification)) if notification.channel() == CLEAR_CHANNEL => {
+                        let _ = cache.delete_all(INVALIDATION_CHANNEL).await;
+                    }
+                    Ok(Some(notification)) => {
+                        //the cache holds one table, the channel only names it in errors
+                        let _ = cache
+                            .remove_session(notification.payload(), INVALIDATION_CHANNEL)
+                            .await;
+                    }
+                    //reconnected by the next try_recv()
+                    Ok(None) => {
+                        let _ = cache.delete_all(INVALIDATION_CHANNEL).await;
+                    }
+                    Err(err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("listening for session invalidations failed: {err}");
+
+                        #[cfg(not(feature = "tracing"))]
+                        let _ = err;
+
+                        let _ = cache.delete_all(INVALIDATION_CHANNEL).await;
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+//announces a written or deleted id on INVALIDATION_CHANNEL
+pub(crate) async fn notify(
+    db: &DatabaseConnection,
+    id: &str,
+    table_name: &str,
+    kind: ErrorKind,
+) -> Result<(), SessionError> {
+    let context = Context::new("notify", table_name).with_id(id);
+    announce(db, INVALIDATION_CHANNEL, id, &context, kind).await
+}
+
+async fn announce(
+    db: &DatabaseConnection,
+    channel: &str,
+    payload: &str,
+    context: &Context<'_>,
+    kind: ErrorKind,
+) -> Result<(), SessionError> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_notify($1, $2)",
+        [channel.into(), payload.into()],
+    ))
+    .traced()
+    .await
+    .map_err(|err| context.db(kind, err))?;
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl DatabasePool for NotifyingDbPool {
+    #[inline(always)]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.db.initiate(table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.db.delete_by_expiry(table_name).await
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        self.db.count(table_name).await
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        //announced by the DbPool once written
+        self.db.store(id, session, expires, table_name).await
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        self.db.load(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        self.db.delete_one_by_id(id, table_name).await?;
+        notify(self.db.connection(), id, table_name, ErrorKind::Delete).await?;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        self.db.exists(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.db.delete_all(table_name).await?;
+
+        let context = Context::new("notify", table_name);
+        announce(
+            self.db.connection(),
+            CLEAR_CHANNEL,
+            table_name,
+            &context,
+            ErrorKind::Delete,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.db.get_ids(table_name).await
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        self.db.auto_handles_expiry()
+    }
+}
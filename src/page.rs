@@ -0,0 +1,22 @@
+/// One page of session ids from `get_ids_after`, ordered by id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IdPage {
+    pub ids: Vec<String>,
+    /// Pass it as `after` to get the next page, None on the last page.
+    pub next: Option<String>,
+}
+
+impl IdPage {
+    //`ids` holds one id more than the page when there is another page after it
+    pub(crate) fn new(mut ids: Vec<String>, limit: u64) -> IdPage {
+        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+
+        if ids.len() <= limit {
+            return IdPage { ids, next: None };
+        }
+
+        ids.truncate(limit);
+        let next = ids.last().cloned();
+        IdPage { ids, next }
+    }
+}
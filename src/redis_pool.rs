@@ -0,0 +1,191 @@
+use axum_session::{DatabaseError, DatabasePool};
+use chrono::Utc;
+use deadpool_redis::{
+    redis::{self, AsyncCommands},
+    Connection, Pool,
+};
+
+use crate::expiry::parse_expires;
+
+//every session is a plain key with the expiry baked into its redis TTL,
+//the sorted set {table_name}:expires scores ids by their expiry for the range queries
+#[derive(Clone, Debug)]
+pub struct RedisPool {
+    pool: Pool,
+    max_expires: Option<i64>,
+}
+
+impl RedisPool {
+    pub fn new(pool: Pool) -> RedisPool {
+        RedisPool {
+            pool,
+            max_expires: None,
+        }
+    }
+
+    /// Expiry timestamps past `max_expires` are stored without a TTL, i.e. the session never expires.
+    pub fn with_max_expires(mut self, max_expires: i64) -> RedisPool {
+        self.max_expires = Some(max_expires);
+        self
+    }
+
+    async fn connection(
+        &self,
+        error: fn(String) -> DatabaseError,
+    ) -> Result<Connection, DatabaseError> {
+        self.pool.get().await.map_err(|err| error(err.to_string()))
+    }
+}
+
+//deletes every session in the index KEYS[1], ARGV[1] is the key prefix of the sessions
+const CLEAR_SCRIPT: &str = r#"
+for _, id in ipairs(redis.call('ZRANGE', KEYS[1], 0, -1)) do
+    redis.call('DEL', ARGV[1] .. id)
+end
+redis.call('DEL', KEYS[1])
+"#;
+
+fn session_key(table_name: &str, id: &str) -> String {
+    format!("{table_name}:session:{id}")
+}
+
+fn expires_key(table_name: &str) -> String {
+    format!("{table_name}:expires")
+}
+
+#[async_trait::async_trait]
+impl DatabasePool for RedisPool {
+    #[inline(always)]
+    async fn initiate(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericDeleteError).await?;
+        let max = Utc::now().timestamp();
+
+        //the keys themselves are already gone through their TTL, only the index needs cleaning
+        let (ids,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .zrangebyscore(expires_key(table_name), "-inf", max)
+            .zrembyscore(expires_key(table_name), "-inf", max)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))?;
+
+        Ok(ids)
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericSelectError).await?;
+        let min = format!("({}", Utc::now().timestamp());
+
+        conn.zcount(expires_key(table_name), min, "+inf")
+            .await
+            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        let expires = parse_expires(expires, self.max_expires)?;
+        let mut conn = self.connection(DatabaseError::GenericInsertError).await?;
+        let key = session_key(table_name, id);
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+
+        match expires.map(|expires| expires.timestamp()) {
+            None => {
+                pipe.set(&key, session)
+                    .zadd(expires_key(table_name), id, "+inf");
+            }
+            Some(expires) => {
+                let ttl = expires - Utc::now().timestamp();
+
+                //redis refuses a TTL of zero or less, the session is already expired anyway
+                if ttl <= 0 {
+                    pipe.del(&key).zrem(expires_key(table_name), id);
+                } else {
+                    pipe.set_ex(&key, session, ttl as u64).zadd(
+                        expires_key(table_name),
+                        id,
+                        expires,
+                    );
+                }
+            }
+        }
+
+        pipe.query_async::<()>(&mut conn)
+            .await
+            .map_err(|err| DatabaseError::GenericInsertError(err.to_string()))
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericSelectError).await?;
+
+        conn.get(session_key(table_name, id))
+            .await
+            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericDeleteError).await?;
+
+        redis::pipe()
+            .atomic()
+            .del(session_key(table_name, id))
+            .zrem(expires_key(table_name), id)
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericSelectError).await?;
+
+        conn.exists(session_key(table_name, id))
+            .await
+            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericDeleteError).await?;
+
+        //one script, so a session stored in the meantime is not left without its index entry
+        redis::cmd("EVAL")
+            .arg(CLEAR_SCRIPT)
+            .arg(1)
+            .arg(expires_key(table_name))
+            .arg(session_key(table_name, ""))
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|err| DatabaseError::GenericDeleteError(err.to_string()))
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let mut conn = self.connection(DatabaseError::GenericSelectError).await?;
+        let min = format!("({}", Utc::now().timestamp());
+
+        conn.zrangebyscore(expires_key(table_name), min, "+inf")
+            .await
+            .map_err(|err| DatabaseError::GenericSelectError(err.to_string()))
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// A session id `DbPool` and `MemoryPool` accept: 1 to `SessionId::MAX_LENGTH` ASCII letters,
+/// digits, `-` or `_`, which covers the UUIDs axum_session generates.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SessionId(String);
+
+/// Why `SessionId::parse` rejected an id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SessionIdError {
+    Empty,
+    TooLong { length: usize, max_length: usize },
+    InvalidCharacter(char),
+}
+
+impl SessionId {
+    pub const MAX_LENGTH: usize = 128;
+
+    pub fn parse(id: &str) -> Result<SessionId, SessionIdError> {
+        SessionId::parse_with_max_length(id, SessionId::MAX_LENGTH)
+    }
+
+    /// Like parse() with another length limit, e.g. the one of `DbPoolBuilder::id_max_length`.
+    pub fn parse_with_max_length(id: &str, max_length: usize) -> Result<SessionId, SessionIdError> {
+        validate(id, max_length)?;
+        Ok(SessionId(id.to_owned()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for SessionId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+//the checks of SessionId::parse without the allocation, run by the pools on every id
+pub(crate) fn validate(id: &str, max_length: usize) -> Result<(), SessionIdError> {
+    if id.is_empty() {
+        return Err(SessionIdError::Empty);
+    }
+
+    //only ASCII passes below, so bytes and characters are the same here
+    if id.len() > max_length {
+        return Err(SessionIdError::TooLong {
+            length: id.len(),
+            max_length,
+        });
+    }
+
+    match id
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+    {
+        Some(c) => Err(SessionIdError::InvalidCharacter(c)),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for SessionIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionIdError::Empty => write!(f, "session id is empty"),
+            SessionIdError::TooLong { length, max_length } => write!(
+                f,
+                "session id is {length} bytes long, at most {max_length} are allowed"
+            ),
+            SessionIdError::InvalidCharacter(c) => write!(
+                f,
+                "session id contains {c:?}, only ASCII letters, digits, - and _ are allowed"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SessionIdError {}
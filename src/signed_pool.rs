@@ -0,0 +1,149 @@
+use axum_session::{DatabaseError, DatabasePool};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+//wraps any pool and stores "{session}|{hex hmac-sha256 tag}", a payload whose tag does not
+//verify loads as None so a session edited directly in the storage is never trusted. the tag
+//covers the table name and the id too, a payload copied to another session does not verify
+#[derive(Clone)]
+pub struct SignedPool<P> {
+    inner: P,
+    mac: HmacSha256,
+}
+
+impl<P: std::fmt::Debug> std::fmt::Debug for SignedPool<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedPool")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> SignedPool<P> {
+    pub fn new(inner: P, secret: &[u8]) -> SignedPool<P> {
+        SignedPool {
+            inner,
+            //hmac accepts keys of any length, this can not fail
+            mac: <HmacSha256 as Mac>::new_from_slice(secret)
+                .unwrap_or_else(|_| unreachable!("hmac accepts keys of any length")),
+        }
+    }
+
+    //each part but the last is length prefixed, so no two (table, id, session) sign the same bytes
+    fn mac(&self, table_name: &str, id: &str, session: &str) -> HmacSha256 {
+        let mut mac = self.mac.clone();
+
+        for part in [table_name, id] {
+            mac.update(&(part.len() as u64).to_be_bytes());
+            mac.update(part.as_bytes());
+        }
+
+        mac.update(session.as_bytes());
+        mac
+    }
+
+    fn sign(&self, table_name: &str, id: &str, session: &str) -> String {
+        let tag: String = self
+            .mac(table_name, id, session)
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        format!("{session}|{tag}")
+    }
+
+    fn verify(&self, table_name: &str, id: &str, payload: &str) -> Option<String> {
+        //the tag never contains a |, the session itself might
+        let (session, tag) = payload.rsplit_once('|')?;
+        let tag = decode_hex(tag)?;
+
+        self.mac(table_name, id, session).verify_slice(&tag).ok()?;
+
+        Some(session.to_owned())
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl<P> DatabasePool for SignedPool<P>
+where
+    P: DatabasePool + Send + Sync,
+{
+    #[inline(always)]
+    async fn initiate(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.initiate(table_name).await
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.inner.delete_by_expiry(table_name).await
+    }
+
+    #[inline(always)]
+    async fn count(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        self.inner.count(table_name).await
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        self.inner
+            .store(id, &self.sign(table_name, id, session), expires, table_name)
+            .await
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let payload = self.inner.load(id, table_name).await?;
+
+        Ok(payload.and_then(|payload| self.verify(table_name, id, &payload)))
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_one_by_id(id, table_name).await
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, table_name: &str) -> Result<bool, DatabaseError> {
+        //a session that load() would reject does not exist either
+        Ok(self.load(id, table_name).await?.is_some())
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, table_name: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_all(table_name).await
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.inner.get_ids(table_name).await
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        self.inner.auto_handles_expiry()
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+use sea_orm::{DbBackend, Statement, Value};
+
+use crate::error::SessionError;
+
+//past this many tables statements are built per call again instead of growing the cache
+const MAX_TABLES: usize = 64;
+
+//an operation and the options its SQL depends on, see DbPool::statement_options
+type StatementKey = (&'static str, u8);
+
+//rendered SQL of the statements every request runs, so only the bind values change per call
+#[derive(Debug, Default)]
+pub(crate) struct StatementCache {
+    //rendered SQL by table name
+    statements: RwLock<HashMap<StatementKey, HashMap<String, String>>>,
+}
+
+impl StatementCache {
+    //`values` are the bind values in the order `build` renders them, it gets them to build from.
+    //the SQL is only kept when the first build agrees with them, a mismatch means building per
+    //call instead of wrong binds
+    pub(crate) fn statement(
+        &self,
+        backend: DbBackend,
+        key: StatementKey,
+        table_name: &str,
+        values: Vec<Value>,
+        build: impl FnOnce(&[Value]) -> Result<Statement, SessionError>,
+    ) -> Result<Statement, SessionError> {
+        let cached = self
+            .statements
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .and_then(|tables| tables.get(table_name))
+            .cloned();
+
+        if let Some(sql) = cached {
+            return Ok(Statement::from_sql_and_values(backend, sql, values));
+        }
+
+        let statement = build(&values)?;
+        let bound = statement.values.as_ref().map_or(&[][..], |bound| &bound.0);
+
+        if bound == values.as_slice() {
+            let mut statements = self
+                .statements
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            let tables = statements.entry(key).or_default();
+
+            if tables.len() < MAX_TABLES {
+                tables.insert(table_name.to_owned(), statement.sql.clone());
+            }
+        }
+
+        Ok(statement)
+    }
+}
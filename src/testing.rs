@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use axum_session::{DatabaseError, DatabasePool};
+
+/// How often each `DatabasePool` method of a `MockPool` was called.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MockCallCounts {
+    pub initiate: usize,
+    pub delete_by_expiry: usize,
+    pub count: usize,
+    pub store: usize,
+    pub load: usize,
+    pub delete_one_by_id: usize,
+    pub exists: usize,
+    pub delete_all: usize,
+    pub get_ids: usize,
+}
+
+//a pool for application tests, a plain map that never expires anything and records its calls,
+//clones share the map and the counts
+#[derive(Clone, Debug, Default)]
+pub struct MockPool {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    sessions: HashMap<String, String>,
+    last_stored_id: Option<String>,
+    calls: MockCallCounts,
+}
+
+impl MockPool {
+    pub fn new() -> MockPool {
+        MockPool::default()
+    }
+
+    /// Sessions held right now, expired ones included.
+    pub fn session_count(&self) -> usize {
+        self.state().sessions.len()
+    }
+
+    /// The id of the latest store(), even if it was deleted since.
+    pub fn last_stored_id(&self) -> Option<String> {
+        self.state().last_stored_id.clone()
+    }
+
+    pub fn call_counts(&self) -> MockCallCounts {
+        self.state().calls
+    }
+
+    //a test that panicked while holding the lock should not fail the next assertion as well
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePool for MockPool {
+    #[inline(always)]
+    async fn initiate(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        self.state().calls.initiate += 1;
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn delete_by_expiry(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.state().calls.delete_by_expiry += 1;
+        Ok(Vec::new())
+    }
+
+    #[inline(always)]
+    async fn count(&self, _table_name: &str) -> Result<i64, DatabaseError> {
+        let mut state = self.state();
+        state.calls.count += 1;
+        Ok(state.sessions.len() as i64)
+    }
+
+    #[inline(always)]
+    async fn store(
+        &self,
+        id: &str,
+        session: &str,
+        _expires: i64,
+        _table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.state();
+        state.calls.store += 1;
+        state.sessions.insert(id.to_owned(), session.to_owned());
+        state.last_stored_id = Some(id.to_owned());
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn load(&self, id: &str, _table_name: &str) -> Result<Option<String>, DatabaseError> {
+        let mut state = self.state();
+        state.calls.load += 1;
+        Ok(state.sessions.get(id).cloned())
+    }
+
+    #[inline(always)]
+    async fn delete_one_by_id(&self, id: &str, _table_name: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state();
+        state.calls.delete_one_by_id += 1;
+        state.sessions.remove(id);
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn exists(&self, id: &str, _table_name: &str) -> Result<bool, DatabaseError> {
+        let mut state = self.state();
+        state.calls.exists += 1;
+        Ok(state.sessions.contains_key(id))
+    }
+
+    #[inline(always)]
+    async fn delete_all(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state();
+        state.calls.delete_all += 1;
+        state.sessions.clear();
+        Ok(())
+    }
+
+    #[inline(always)]
+    async fn get_ids(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let mut state = self.state();
+        state.calls.get_ids += 1;
+        Ok(state.sessions.keys().cloned().collect())
+    }
+
+    #[inline(always)]
+    fn auto_handles_expiry(&self) -> bool {
+        false
+    }
+}
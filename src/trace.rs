@@ -0,0 +1,29 @@
+//span helpers that compile to nothing without the tracing feature
+
+//sets a field declared with tracing::field::Empty on the current span
+macro_rules! record {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, $value);
+    };
+}
+
+pub(crate) use record;
+
+//runs a sea_orm future inside a db.session.query span, a child of the pool method's span
+#[cfg(feature = "db_pool")]
+pub(crate) trait Traced: std::future::Future + Sized {
+    #[cfg(feature = "tracing")]
+    fn traced(self) -> tracing::instrument::Instrumented<Self> {
+        tracing::Instrument::instrument(self, tracing::info_span!("db.session.query"))
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    #[inline(always)]
+    fn traced(self) -> Self {
+        self
+    }
+}
+
+#[cfg(feature = "db_pool")]
+impl<F: std::future::Future> Traced for F {}
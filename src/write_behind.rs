@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+//a queued store(), keyed by table name and id
+type Key = (String, String);
+
+//store() calls queued by DbPool::with_write_behind, written in batches by a background task
+#[derive(Debug)]
+pub(crate) struct WriteBehind {
+    pub(crate) interval: Duration,
+    batch_size: usize,
+    max_pending: usize,
+    //the latest payload and expiry per session, a store() of a queued id replaces it
+    pending: Mutex<HashMap<Key, (String, i64)>>,
+    //wakes the background task once batch_size sessions are queued
+    batch_ready: Notify,
+    //held while a batch is written, so a delete can not run between reading and writing it
+    pub(crate) flushing: tokio::sync::Mutex<()>,
+    //set once the first store() started the background task
+    started: AtomicBool,
+    //set by NotifyingDbPool, every clone of the pool flushing the queue has to announce it
+    #[cfg(feature = "pg_notify")]
+    pub(crate) notify_writes: AtomicBool,
+}
+
+impl WriteBehind {
+    pub(crate) fn new(interval: Duration, batch_size: usize, max_pending: usize) -> WriteBehind {
+        WriteBehind {
+            interval,
+            batch_size: batch_size.max(1),
+            max_pending,
+            pending: Mutex::default(),
+            batch_ready: Notify::new(),
+            flushing: tokio::sync::Mutex::new(()),
+            started: AtomicBool::new(false),
+            #[cfg(feature = "pg_notify")]
+            notify_writes: AtomicBool::new(false),
+        }
+    }
+
+    //true for the one caller that has to start the background task
+    pub(crate) fn start(&self) -> bool {
+        !self.started.swap(true, Ordering::AcqRel)
+    }
+
+    pub(crate) fn started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    //false when the queue is full, the caller then writes right away
+    pub(crate) fn push(&self, table_name: &str, id: &str, session: &str, expires: i64) -> bool {
+        let mut pending = self.pending();
+        let key = (table_name.to_owned(), id.to_owned());
+
+        if pending.len() >= self.max_pending && !pending.contains_key(&key) {
+            return false;
+        }
+
+        pending.insert(key, (session.to_owned(), expires));
+
+        if pending.len() >= self.batch_size {
+            self.batch_ready.notify_one();
+        }
+
+        true
+    }
+
+    pub(crate) fn get(&self, table_name: &str, id: &str) -> Option<(String, i64)> {
+        self.pending()
+            .get(&(table_name.to_owned(), id.to_owned()))
+            .cloned()
+    }
+
+    pub(crate) fn remove<'a>(&self, table_name: &str, ids: impl IntoIterator<Item = &'a str>) {
+        let mut pending = self.pending();
+
+        for id in ids {
+            pending.remove(&(table_name.to_owned(), id.to_owned()));
+        }
+    }
+
+    pub(crate) fn remove_table(&self, table_name: &str) {
+        self.pending().retain(|(table, _), _| table != table_name);
+    }
+
+    //what is queued right now, left in the queue so load() still finds it while it is written
+    pub(crate) fn snapshot(&self) -> HashMap<Key, (String, i64)> {
+        self.pending().clone()
+    }
+
+    //drops what a flush wrote, unless a newer store() replaced it in the meantime
+    pub(crate) fn written(&self, table_name: &str, written: &[(String, String, i64)]) {
+        let mut pending = self.pending();
+
+        for (id, session, expires) in written {
+            let key = (table_name.to_owned(), id.clone());
+
+            if pending
+                .get(&key)
+                .is_some_and(|queued| queued.0 == *session && queued.1 == *expires)
+            {
+                pending.remove(&key);
+            }
+        }
+    }
+
+    pub(crate) async fn batch_ready(&self) {
+        self.batch_ready.notified().await;
+    }
+
+    fn pending(&self) -> MutexGuard<'_, HashMap<Key, (String, i64)>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
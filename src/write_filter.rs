@@ -0,0 +1,123 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, RandomState},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+};
+
+//what store() last wrote per id, so a write that would leave the row as it is can be skipped,
+//see DbPool::with_skip_unchanged
+#[derive(Debug)]
+pub(crate) struct WriteFilter {
+    capacity: usize,
+    //an expiry moved by at most this many seconds still counts as unchanged
+    slack: u64,
+    hasher: RandomState,
+    written: Mutex<Written>,
+    skipped: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Written {
+    //payload hash and expiry of the last write, with the tick it was last used at
+    entries: HashMap<String, (u64, i64, u64)>,
+    //use order like MemoryStore, maps a tick to the id used at it
+    clock: u64,
+    recency: BTreeMap<u64, String>,
+}
+
+impl WriteFilter {
+    pub(crate) fn new(capacity: usize, slack: std::time::Duration) -> WriteFilter {
+        WriteFilter {
+            capacity,
+            slack: slack.as_secs(),
+            hasher: RandomState::new(),
+            written: Mutex::default(),
+            skipped: AtomicU64::new(0),
+        }
+    }
+
+    //true when the last write of `id` had the same payload and about the same expiry
+    pub(crate) fn unchanged(
+        &self,
+        id: &str,
+        session: &str,
+        expires: i64,
+        table_name: &str,
+    ) -> bool {
+        let hash = self.hasher.hash_one((table_name, session));
+        let mut written = self.written();
+
+        let Some(&(written_hash, written_expires, _)) = written.entries.get(id) else {
+            return false;
+        };
+
+        if written_hash != hash || written_expires.abs_diff(expires) > self.slack {
+            return false;
+        }
+
+        //the expiry is not updated, so the drift adds up until a write lands again
+        written.touch(id);
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    //the table is hashed with the payload, the same id written to another table is a change
+    pub(crate) fn record(&self, id: &str, session: &str, expires: i64, table_name: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let hash = self.hasher.hash_one((table_name, session));
+        let mut written = self.written();
+
+        if !written.entries.contains_key(id) {
+            while written.entries.len() >= self.capacity {
+                let Some((_, oldest)) = written.recency.pop_first() else {
+                    break;
+                };
+                written.entries.remove(&oldest);
+            }
+        }
+
+        written.entries.insert(id.to_owned(), (hash, expires, 0));
+        written.touch(id);
+    }
+
+    pub(crate) fn forget(&self, id: &str) {
+        let mut written = self.written();
+
+        if let Some((_, _, tick)) = written.entries.remove(id) {
+            written.recency.remove(&tick);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut written = self.written();
+        written.entries.clear();
+        written.recency.clear();
+    }
+
+    pub(crate) fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    fn written(&self) -> MutexGuard<'_, Written> {
+        self.written.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Written {
+    fn touch(&mut self, id: &str) {
+        self.clock += 1;
+        let clock = self.clock;
+
+        if let Some(entry) = self.entries.get_mut(id) {
+            let previous = std::mem::replace(&mut entry.2, clock);
+            self.recency.remove(&previous);
+            self.recency.insert(clock, id.to_owned());
+        }
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "postgres")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+
+//live sessions and expired ones not swept yet
+async fn seed(pool: &DbPool, table: &str, live: usize, expired: usize) {
+    let now = common::now();
+    let ids: Vec<String> = (0..live + expired).map(|n| format!("id{n}")).collect();
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .enumerate()
+        .map(|(n, id)| {
+            let expires = if n < live { now + 3600 } else { now - 60 };
+            (id.as_str(), "{\"a\":1}", expires)
+        })
+        .collect();
+
+    pool.initiate(table).await.unwrap();
+    pool.store_many(&sessions, table).await.unwrap();
+}
+
+async fn analyze(db: &DatabaseConnection, table: &str) {
+    db.execute_unprepared(&format!("ANALYZE {table}"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn the_estimate_is_close_on_an_analyzed_table() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let pool = DbPool::new(db.clone()).with_approximate_count(true);
+    let table = common::table("approximate");
+
+    seed(&pool, &table, 2_000, 0).await;
+    analyze(&db, &table).await;
+
+    let estimate = pool.count(&table).await.unwrap();
+    assert!((1_800..=2_200).contains(&estimate), "{estimate}");
+}
+
+//reltuples is -1 until the first ANALYZE, too few rows for autovacuum to run one
+#[tokio::test]
+async fn a_table_never_analyzed_is_counted_exactly() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let pool = DbPool::new(db).with_approximate_count(true);
+    let table = common::table("approximate");
+
+    seed(&pool, &table, 10, 5).await;
+    assert_eq!(pool.count(&table).await.unwrap(), 10);
+}
+
+//the estimate would include the expired rows
+#[tokio::test]
+async fn the_exact_count_is_the_default() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let pool = DbPool::new(db.clone());
+    let table = common::table("approximate");
+
+    seed(&pool, &table, 30, 20).await;
+    analyze(&db, &table).await;
+    assert_eq!(pool.count(&table).await.unwrap(), 30);
+}
+
+#[tokio::test]
+async fn sqlite_is_counted_exactly() {
+    let pool = DbPool::new(common::sqlite().await).with_approximate_count(true);
+
+    seed(&pool, "sessions", 30, 20).await;
+    assert_eq!(pool.count("sessions").await.unwrap(), 30);
+}
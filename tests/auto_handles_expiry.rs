@@ -0,0 +1,59 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool};
+
+#[tokio::test]
+async fn db_pool_reports_the_builder_value() {
+    let db = common::sqlite().await;
+
+    assert!(!DbPool::new(db.clone()).auto_handles_expiry());
+    assert!(DbPool::new(db.clone())
+        .with_auto_handles_expiry(true)
+        .auto_handles_expiry());
+    assert!(!DbPool::new(db)
+        .with_auto_handles_expiry(true)
+        .with_auto_handles_expiry(false)
+        .auto_handles_expiry());
+}
+
+#[tokio::test]
+async fn db_pool_clones_keep_it() {
+    let pool = DbPool::new(common::sqlite().await).with_auto_handles_expiry(true);
+
+    assert!(pool.clone().auto_handles_expiry());
+}
+
+#[tokio::test]
+async fn memory_pool_handles_expiry_with_its_sweeper() {
+    assert!(!MemoryPool::new().auto_handles_expiry());
+    assert!(MemoryPool::new()
+        .with_auto_expiry(Duration::from_secs(60))
+        .auto_handles_expiry());
+}
+
+#[tokio::test]
+async fn memory_pool_reports_the_builder_value() {
+    assert!(MemoryPool::new()
+        .with_auto_handles_expiry(true)
+        .auto_handles_expiry());
+    assert!(!MemoryPool::new()
+        .with_auto_handles_expiry(true)
+        .with_auto_handles_expiry(false)
+        .auto_handles_expiry());
+
+    //the builder set after the sweeper wins, axum_session then sweeps as well
+    assert!(!MemoryPool::new()
+        .with_auto_expiry(Duration::from_secs(60))
+        .with_auto_handles_expiry(false)
+        .auto_handles_expiry());
+}
+
+#[tokio::test]
+async fn memory_pool_clones_keep_it() {
+    let pool = MemoryPool::new().with_auto_handles_expiry(true);
+
+    assert!(pool.clone().auto_handles_expiry());
+}
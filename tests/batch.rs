@@ -0,0 +1,218 @@
+mod common;
+
+use std::collections::HashMap;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool};
+
+async fn seed(pool: &impl DatabasePool, table: &str, now: i64) {
+    pool.initiate(table).await.unwrap();
+    pool.store("present", "{\"a\":1}", now + 60, table)
+        .await
+        .unwrap();
+    pool.store("never", "{\"a\":2}", i64::MAX, table)
+        .await
+        .unwrap();
+    pool.store("expired", "{\"a\":3}", now - 60, table)
+        .await
+        .unwrap();
+}
+
+fn expected() -> HashMap<String, String> {
+    HashMap::from([
+        ("present".to_owned(), "{\"a\":1}".to_owned()),
+        ("never".to_owned(), "{\"a\":2}".to_owned()),
+    ])
+}
+
+const IDS: [&str; 4] = ["present", "never", "expired", "unknown"];
+
+#[tokio::test]
+async fn db_pool_load_many_leaves_out_expired_and_unknown_ids() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("batch");
+
+        seed(&pool, &table, common::now()).await;
+        assert_eq!(pool.load_many(&IDS, &table).await.unwrap(), expected());
+        assert!(pool.load_many(&[], &table).await.unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_load_many_leaves_out_expired_and_unknown_ids() {
+    let pool = MemoryPool::new();
+
+    seed(&pool, "sessions", common::now()).await;
+    assert_eq!(pool.load_many(&IDS, "sessions").await.unwrap(), expected());
+    assert!(pool.load_many(&[], "sessions").await.unwrap().is_empty());
+}
+
+//more ids than SQLite takes bind parameters in one statement
+#[tokio::test]
+async fn load_many_chunks_a_long_id_list() {
+    let pool = DbPool::new(common::sqlite().await);
+    let now = common::now();
+
+    seed(&pool, "sessions", now).await;
+    let unknown: Vec<String> = (0..2_000).map(|n| format!("unknown{n}")).collect();
+    let mut ids: Vec<&str> = unknown.iter().map(String::as_str).collect();
+    ids.extend(IDS);
+
+    assert_eq!(pool.load_many(&ids, "sessions").await.unwrap(), expected());
+}
+
+#[tokio::test]
+async fn store_many_inserts_and_updates_in_one_batch() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("batch");
+        let now = common::now();
+
+        seed(&pool, &table, now).await;
+        pool.store_many(
+            &[
+                ("present", "{\"b\":1}", now + 120),
+                ("expired", "{\"b\":3}", now + 120),
+                ("new", "{\"b\":4}", now + 120),
+                ("new_never", "{\"b\":5}", i64::MAX),
+            ],
+            &table,
+        )
+        .await
+        .unwrap();
+
+        for (id, session) in [
+            ("present", "{\"b\":1}"),
+            ("never", "{\"a\":2}"),
+            ("expired", "{\"b\":3}"),
+            ("new", "{\"b\":4}"),
+        ] {
+            assert_eq!(
+                pool.load(id, &table).await.unwrap().as_deref(),
+                Some(session),
+                "{id}"
+            );
+        }
+
+        //converted like store() does it
+        let (_, expires) = pool
+            .load_with_expiry("present", &table)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(expires.map(|expires| expires.timestamp()), Some(now + 120));
+        let (_, expires) = pool
+            .load_with_expiry("new_never", &table)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(expires, None);
+    }
+}
+
+#[tokio::test]
+async fn store_many_writes_nothing_when_one_entry_is_invalid() {
+    let pool = DbPool::new(common::sqlite().await);
+    let now = common::now();
+
+    pool.initiate("sessions").await.unwrap();
+    assert!(pool
+        .store_many(
+            &[("a", "{\"a\":1}", now + 60), ("b", "{\"b\":1}", -1)],
+            "sessions"
+        )
+        .await
+        .is_err());
+    assert_eq!(pool.count_total("sessions").await.unwrap(), 0);
+
+    let memory = MemoryPool::new();
+    assert!(memory
+        .store_many(
+            &[("a", "{\"a\":1}", now + 60), ("b", "{\"b\":1}", -1)],
+            "sessions"
+        )
+        .await
+        .is_err());
+    assert_eq!(memory.count("sessions").await.unwrap(), 0);
+}
+
+//more rows than fit in one statement
+#[tokio::test]
+async fn store_many_chunks_a_large_batch() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("batch");
+        let now = common::now();
+
+        let ids: Vec<String> = (0..5_000).map(|n| format!("id{n}")).collect();
+        let sessions: Vec<(&str, &str, i64)> = ids
+            .iter()
+            .map(|id| (id.as_str(), "{\"a\":1}", now + 60))
+            .collect();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store_many(&sessions, &table).await.unwrap();
+        assert_eq!(pool.count(&table).await.unwrap(), 5_000);
+    }
+}
+
+#[tokio::test]
+async fn db_pool_delete_many_by_ids_counts_what_it_removed() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("batch");
+
+        seed(&pool, &table, common::now()).await;
+        assert_eq!(pool.delete_many_by_ids(&[], &table).await.unwrap(), 0);
+        //expired rows not swept yet are still removed
+        assert_eq!(pool.delete_many_by_ids(&IDS, &table).await.unwrap(), 3);
+        assert_eq!(pool.delete_many_by_ids(&IDS, &table).await.unwrap(), 0);
+        assert_eq!(pool.count_total(&table).await.unwrap(), 0);
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_delete_many_by_ids_counts_what_it_removed() {
+    let pool = MemoryPool::new();
+    let now = common::now();
+
+    seed(&pool, "sessions", now).await;
+    assert_eq!(pool.delete_many_by_ids(&[], "sessions").await.unwrap(), 0);
+    assert_eq!(pool.delete_many_by_ids(&IDS, "sessions").await.unwrap(), 3);
+    assert_eq!(pool.delete_many_by_ids(&IDS, "sessions").await.unwrap(), 0);
+
+    //no empty expiry bucket is left behind
+    let histogram = pool
+        .expiry_histogram(
+            chrono::Duration::seconds(1),
+            chrono::Duration::seconds(3600),
+            "sessions",
+        )
+        .await
+        .unwrap();
+    assert_eq!(histogram.buckets.len(), 0);
+    assert_eq!(histogram.never_expires, 0);
+}
+
+#[tokio::test]
+async fn delete_many_by_ids_chunks_a_long_id_list() {
+    let pool = DbPool::new(common::sqlite().await);
+    let now = common::now();
+
+    let ids: Vec<String> = (0..2_500).map(|n| format!("id{n}")).collect();
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .map(|id| (id.as_str(), "{\"a\":1}", now + 60))
+        .collect();
+    pool.initiate("sessions").await.unwrap();
+    pool.store_many(&sessions, "sessions").await.unwrap();
+
+    let mut delete: Vec<&str> = ids.iter().map(String::as_str).collect();
+    delete.push("unknown");
+    assert_eq!(
+        pool.delete_many_by_ids(&delete, "sessions").await.unwrap(),
+        2_500
+    );
+    assert_eq!(pool.count_total("sessions").await.unwrap(), 0);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum_session::{DatabaseError, DatabasePool};
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, TransactionTrait};
+
+fn sqlite_path() -> PathBuf {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "dxp-axum-session-busy-{}-{}.db",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+//without a busy timeout a locked database fails right away instead of sqlx waiting up to 5s
+async fn impatient_sqlite(path: &Path) -> DatabaseConnection {
+    let mut options = ConnectOptions::new(format!("sqlite://{}?mode=rwc", path.display()));
+    options.map_sqlx_sqlite_opts(|options| options.busy_timeout(Duration::ZERO));
+    Database::connect(options).await.expect("sqlite connects")
+}
+
+//another process holding the write lock for a moment
+async fn lock_for(path: &Path, duration: Duration) -> tokio::task::JoinHandle<()> {
+    let db = impatient_sqlite(path).await;
+    let lock = db.begin().await.unwrap();
+    lock.execute_unprepared("DELETE FROM sessions WHERE id = 'none'")
+        .await
+        .unwrap();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        lock.commit().await.unwrap();
+    })
+}
+
+#[tokio::test]
+async fn a_locked_database_is_retried() {
+    let path = sqlite_path();
+    let pool = DbPool::new(impatient_sqlite(&path).await);
+    pool.initiate("sessions").await.unwrap();
+
+    let lock = lock_for(&path, Duration::from_millis(50)).await;
+    pool.store("a", "{\"a\":1}", common::now() + 60, "sessions")
+        .await
+        .unwrap();
+    lock.await.unwrap();
+
+    assert!(pool.load("a", "sessions").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn without_retries_the_busy_error_is_returned() {
+    let path = sqlite_path();
+    let pool = DbPool::new(impatient_sqlite(&path).await).with_busy_retries(0);
+    pool.initiate("sessions").await.unwrap();
+
+    let lock = lock_for(&path, Duration::from_millis(50)).await;
+    let result = pool
+        .store("a", "{\"a\":1}", common::now() + 60, "sessions")
+        .await;
+    lock.await.unwrap();
+
+    assert!(
+        matches!(result, Err(DatabaseError::GenericInsertError(_))),
+        "{result:?}"
+    );
+}
+
+//many tasks writing to one SQLite file
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_writes_to_sqlite_do_not_fail() {
+    let pool = Arc::new(DbPool::new(impatient_sqlite(&sqlite_path()).await));
+    let expires = common::now() + 60;
+    pool.initiate("sessions").await.unwrap();
+
+    let tasks: Vec<_> = (0..32)
+        .map(|task| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                for n in 0..25 {
+                    let id = format!("t{task}n{n}");
+                    pool.store(&id, "{\"a\":1}", expires, "sessions").await?;
+                    if n % 3 == 0 {
+                        pool.delete_one_by_id(&id, "sessions").await?;
+                    }
+                    if n % 10 == 0 {
+                        pool.delete_by_expiry("sessions").await?;
+                    }
+                }
+                Ok::<_, DatabaseError>(())
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    //9 of every 25 ids were deleted again
+    assert_eq!(pool.count("sessions").await.unwrap(), 32 * 16);
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "cached_pool")]
+
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum_session::DatabasePool;
+use chrono::{DateTime, Utc};
+use dxp_axum_session::{CachedDbPool, Clock, DbPool, MemoryPool};
+
+#[tokio::test]
+async fn an_empty_session_is_not_cached() {
+    let db = common::sqlite().await;
+    let plain = DbPool::new(db.clone());
+    let pool = CachedDbPool::new(DbPool::new(db).with_skip_empty_sessions(true));
+    let table = common::table("cached");
+    let expires = common::now() + 3600;
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+    assert!(pool.load("a", &table).await.unwrap().is_some());
+
+    //deleted from the database, so gone from memory as well
+    pool.store("a", "{}", expires, &table).await.unwrap();
+    assert_eq!(plain.load("a", &table).await.unwrap(), None);
+    assert_eq!(pool.load("a", &table).await.unwrap(), None);
+    assert!(!pool.exists("a", &table).await.unwrap());
+
+    pool.store("b", "{}", expires, &table).await.unwrap();
+    assert_eq!(pool.load("b", &table).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn a_store_replaces_the_cached_copy() {
+    let pool = CachedDbPool::new(DbPool::new(common::sqlite().await));
+    let table = common::table("cached");
+    let expires = common::now() + 3600;
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+    assert!(pool.load("a", &table).await.unwrap().is_some());
+
+    pool.store("a", "{\"a\":2}", expires, &table).await.unwrap();
+    assert_eq!(
+        pool.load("a", &table).await.unwrap().as_deref(),
+        Some("{\"a\":2}")
+    );
+}
+
+//the memory layer's clock, holding up the second read of it: the store() of a load() caching
+//what it read, the first read is the lookup that missed
+#[derive(Clone, Debug, Default)]
+struct StallingClock(Arc<(AtomicU32, AtomicBool)>);
+
+impl Clock for StallingClock {
+    fn now(&self) -> DateTime<Utc> {
+        let (calls, stalling) = &*self.0;
+
+        if calls.fetch_add(1, Ordering::SeqCst) == 1 {
+            stalling.store(true, Ordering::SeqCst);
+            //the tasks queued on this worker, e.g. returning the connection, move to another one
+            tokio::task::block_in_place(|| std::thread::sleep(Duration::from_millis(300)));
+        }
+        Utc::now()
+    }
+}
+
+//a store, or a delete when `stored` is None, lands while a load() is about to cache what it read
+async fn write_during_a_load(stored: Option<&str>) -> Option<String> {
+    let db = common::sqlite().await;
+    let clock = StallingClock::default();
+    let pool = Arc::new(
+        CachedDbPool::new(DbPool::new(db.clone()))
+            .with_memory_pool(MemoryPool::new().with_clock(clock.clone())),
+    );
+    let table = common::table("cached");
+    let expires = common::now() + 3600;
+
+    //in the database only, the load below reads it from there and caches it
+    pool.initiate(&table).await.unwrap();
+    DbPool::new(db)
+        .store("a", "{\"v\":1}", expires, &table)
+        .await
+        .unwrap();
+
+    let loading = pool.clone();
+    let load_table = table.clone();
+    let load = tokio::spawn(async move { loading.load("a", &load_table).await.unwrap() });
+
+    while !clock.0 .1.load(Ordering::SeqCst) {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    match stored {
+        Some(session) => pool.store("a", session, expires, &table).await.unwrap(),
+        None => pool.delete_one_by_id("a", &table).await.unwrap(),
+    }
+
+    assert_eq!(load.await.unwrap().as_deref(), Some("{\"v\":1}"));
+    pool.load("a", &table).await.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_store_during_a_load_is_not_overwritten_by_the_older_payload() {
+    assert_eq!(
+        write_during_a_load(Some("{\"v\":2}")).await.as_deref(),
+        Some("{\"v\":2}")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_delete_during_a_load_does_not_bring_the_session_back() {
+    assert_eq!(write_during_a_load(None).await, None);
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "circuit_breaker")]
+
+use std::{
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum_session::{DatabaseError, DatabasePool};
+use dxp_axum_session::{CircuitBreakerPool, CircuitState};
+
+const OK: u8 = 0;
+const FAIL: u8 = 1;
+const HANG: u8 = 2;
+
+//answers every call as `mode` says
+#[derive(Clone, Debug, Default)]
+struct Backend {
+    mode: Arc<AtomicU8>,
+}
+
+impl Backend {
+    fn set(&self, mode: u8) {
+        self.mode.store(mode, Ordering::SeqCst);
+    }
+
+    async fn answer<T>(&self, value: T) -> Result<T, DatabaseError> {
+        match self.mode.load(Ordering::SeqCst) {
+            FAIL => Err(DatabaseError::GenericSelectError("down".into())),
+            HANG => std::future::pending().await,
+            _ => Ok(value),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePool for Backend {
+    async fn initiate(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        self.answer(()).await
+    }
+
+    async fn delete_by_expiry(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.answer(Vec::new()).await
+    }
+
+    async fn count(&self, _table_name: &str) -> Result<i64, DatabaseError> {
+        self.answer(0).await
+    }
+
+    async fn store(
+        &self,
+        _id: &str,
+        _session: &str,
+        _expires: i64,
+        _table_name: &str,
+    ) -> Result<(), DatabaseError> {
+        self.answer(()).await
+    }
+
+    async fn load(&self, _id: &str, _table_name: &str) -> Result<Option<String>, DatabaseError> {
+        self.answer(None).await
+    }
+
+    async fn delete_one_by_id(&self, _id: &str, _table_name: &str) -> Result<(), DatabaseError> {
+        self.answer(()).await
+    }
+
+    async fn exists(&self, _id: &str, _table_name: &str) -> Result<bool, DatabaseError> {
+        self.answer(false).await
+    }
+
+    async fn delete_all(&self, _table_name: &str) -> Result<(), DatabaseError> {
+        self.answer(()).await
+    }
+
+    async fn get_ids(&self, _table_name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.answer(Vec::new()).await
+    }
+
+    fn auto_handles_expiry(&self) -> bool {
+        false
+    }
+}
+
+const COOLDOWN: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn opens_after_threshold_and_closes_after_probe() {
+    let backend = Backend::default();
+    let pool = CircuitBreakerPool::new(backend.clone(), 2, COOLDOWN);
+
+    backend.set(FAIL);
+    assert!(pool.count("t").await.is_err());
+    assert_eq!(pool.state(), CircuitState::Closed);
+    assert!(pool.count("t").await.is_err());
+    assert_eq!(pool.state(), CircuitState::Open);
+
+    //turned away without reaching the backend
+    backend.set(OK);
+    assert!(pool.count("t").await.is_err());
+
+    tokio::time::sleep(COOLDOWN).await;
+    assert_eq!(pool.state(), CircuitState::HalfOpen);
+    assert_eq!(pool.count("t").await.unwrap(), 0);
+    assert_eq!(pool.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn a_cancelled_probe_lets_the_next_call_probe() {
+    let backend = Backend::default();
+    let pool = CircuitBreakerPool::new(backend.clone(), 1, COOLDOWN);
+
+    backend.set(FAIL);
+    assert!(pool.count("t").await.is_err());
+    tokio::time::sleep(COOLDOWN).await;
+
+    //the probe's future is dropped while it waits, as axum does when the client disconnects
+    backend.set(HANG);
+    let probe = tokio::time::timeout(Duration::from_millis(10), pool.count("t")).await;
+    assert!(probe.is_err());
+
+    backend.set(OK);
+    assert_eq!(pool.count("t").await.unwrap(), 0);
+    assert_eq!(pool.state(), CircuitState::Closed);
+}
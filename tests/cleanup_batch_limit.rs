@@ -0,0 +1,67 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, MockClock};
+use sea_orm::DatabaseConnection;
+
+const TOTAL: usize = 23;
+
+//each call deletes at most `limit` sessions, so draining takes ceil(total / limit) calls
+async fn check_drain(db: DatabaseConnection, limit: usize, chunk_size: usize) {
+    let now = common::now();
+    let clock = MockClock::new(DateTime::from_timestamp(now, 0).unwrap());
+    let pool = DbPool::new(db)
+        .with_clock(clock.clone())
+        .with_cleanup_batch_limit(limit)
+        .with_cleanup_chunk_size(chunk_size);
+    let table = common::table("batch_limit");
+
+    pool.initiate(&table).await.unwrap();
+    for i in 0..TOTAL {
+        pool.store(&format!("id{i:02}"), "{\"a\":1}", now + 1, &table)
+            .await
+            .unwrap();
+    }
+    pool.store("live", "{\"a\":1}", now + 3600, &table)
+        .await
+        .unwrap();
+    clock.advance(chrono::Duration::seconds(1));
+
+    let mut deleted = BTreeSet::new();
+    let mut calls = 0;
+    loop {
+        let ids = pool.delete_by_expiry(&table).await.unwrap();
+        if ids.is_empty() {
+            break;
+        }
+
+        assert!(ids.len() <= limit, "{} ids past the limit", ids.len());
+        calls += 1;
+        deleted.extend(ids);
+    }
+
+    assert_eq!(calls, TOTAL.div_ceil(limit), "limit {limit}");
+    assert_eq!(deleted.len(), TOTAL);
+    assert!(pool.exists("live", &table).await.unwrap());
+}
+
+#[tokio::test]
+async fn delete_by_expiry_is_capped() {
+    for (limit, chunk_size) in [(5, 100), (5, 2), (1, 100), (23, 4), (100, 100)] {
+        check_drain(common::sqlite().await, limit, chunk_size).await;
+    }
+}
+
+#[tokio::test]
+async fn delete_by_expiry_is_capped_on_postgres() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+
+    for (limit, chunk_size) in [(5, 100), (5, 2), (23, 4)] {
+        check_drain(db.clone(), limit, chunk_size).await;
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use std::{collections::BTreeSet, time::Duration};
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use sea_orm::DatabaseConnection;
+
+//one call goes through every chunk and reports the ids of all of them
+async fn check_chunks(db: DatabaseConnection, expired: usize, chunk_size: usize) {
+    let pool = DbPool::new(db)
+        .with_cleanup_chunk_size(chunk_size)
+        .with_cleanup_chunk_pause(Duration::from_millis(1));
+    check_sweep(pool, expired).await;
+}
+
+async fn check_sweep(pool: DbPool, expired: usize) {
+    let table = common::table("chunks");
+    let now = common::now();
+
+    let ids: Vec<String> = (0..expired).map(|n| format!("id{n:05}")).collect();
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .map(|id| (id.as_str(), "{\"a\":1}", now - 60))
+        .chain([("live", "{\"a\":1}", now + 3600)])
+        .collect();
+
+    pool.initiate(&table).await.unwrap();
+    pool.store_many(&sessions, &table).await.unwrap();
+
+    let deleted = pool.delete_by_expiry(&table).await.unwrap();
+    assert_eq!(deleted.len(), expired);
+    assert_eq!(
+        deleted.into_iter().collect::<BTreeSet<_>>(),
+        ids.into_iter().collect::<BTreeSet<_>>()
+    );
+    assert_eq!(pool.count_total(&table).await.unwrap(), 1);
+    assert!(pool.exists("live", &table).await.unwrap());
+}
+
+#[tokio::test]
+async fn a_backlog_larger_than_a_chunk_is_deleted() {
+    //more than a chunk, exactly two chunks, less than one
+    for (expired, chunk_size) in [(2_500, 1_000), (2_000, 1_000), (10, 1_000)] {
+        check_chunks(common::sqlite().await, expired, chunk_size).await;
+    }
+}
+
+#[tokio::test]
+async fn a_backlog_larger_than_a_chunk_is_deleted_on_postgres() {
+    if let Some(db) = common::postgres().await {
+        check_chunks(db, 2_500, 1_000).await;
+    }
+}
+
+//the default chunk is capped at what one statement binds, the ids are in an IN list when the
+//rows are selected first
+#[tokio::test]
+async fn the_default_chunk_fits_in_one_statement() {
+    check_sweep(DbPool::new(common::sqlite().await), 2_500).await;
+    check_sweep(
+        DbPool::new(common::sqlite().await).with_archival(true),
+        2_500,
+    )
+    .await;
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{Clock, DbPool, MockClock};
+
+const LEASE: Duration = Duration::from_secs(60);
+
+//two instances sharing one SQLite file and one clock
+async fn instances() -> (DbPool, DbPool, MockClock, String) {
+    let db = common::sqlite().await;
+    let clock = MockClock::new(DateTime::from_timestamp(common::now(), 0).unwrap());
+    let table = common::table("lease");
+
+    let instance = |db| {
+        DbPool::new(db)
+            .with_clock(clock.clone())
+            .with_cleanup_lease(LEASE)
+            .with_busy_retries(100)
+    };
+    let (a, b) = (instance(db.clone()), instance(db));
+
+    a.initiate(&table).await.unwrap();
+    b.initiate(&table).await.unwrap();
+
+    (a, b, clock, table)
+}
+
+//stores `n` sessions that expire right away once the clock moves
+async fn expire(pool: &DbPool, clock: &MockClock, prefix: &str, n: usize, table: &str) {
+    let expires = clock.now().timestamp() + 1;
+
+    for i in 0..n {
+        pool.store(&format!("{prefix}{i}"), "{\"a\":1}", expires, table)
+            .await
+            .unwrap();
+    }
+    clock.advance(chrono::Duration::seconds(1));
+}
+
+#[tokio::test]
+async fn one_instance_sweeps_per_lease() {
+    let (a, b, clock, table) = instances().await;
+
+    expire(&a, &clock, "first", 5, &table).await;
+    assert_eq!(a.delete_by_expiry(&table).await.unwrap().len(), 5);
+
+    //the lease is a's until it runs out, b skips even with new expired sessions
+    expire(&a, &clock, "second", 3, &table).await;
+    assert!(b.delete_by_expiry(&table).await.unwrap().is_empty());
+    assert!(a.delete_by_expiry(&table).await.unwrap().is_empty());
+
+    clock.advance(chrono::Duration::from_std(LEASE).unwrap());
+    assert_eq!(b.delete_by_expiry(&table).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn concurrent_sweeps_delete_once() {
+    let (a, b, clock, table) = instances().await;
+
+    expire(&a, &clock, "id", 20, &table).await;
+    let (from_a, from_b) = tokio::join!(a.delete_by_expiry(&table), b.delete_by_expiry(&table));
+    let (from_a, from_b) = (from_a.unwrap(), from_b.unwrap());
+
+    assert!(
+        from_a.is_empty() != from_b.is_empty(),
+        "exactly one instance sweeps"
+    );
+    assert_eq!(from_a.len() + from_b.len(), 20);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+
+async fn stored_ids(db: &DatabaseConnection, table: &str) -> BTreeSet<String> {
+    db.query_all(Statement::from_string(
+        db.get_database_backend(),
+        format!("SELECT id FROM {table}"),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| row.try_get("", "id").unwrap())
+    .collect()
+}
+
+//the ids returned are exactly the rows that are gone
+async fn check_reported_ids(db: DatabaseConnection) {
+    let pool = DbPool::new(db.clone());
+    let table = common::table("returning");
+    let now = common::now();
+
+    pool.initiate(&table).await.unwrap();
+    for n in 0..30 {
+        let expires = if n % 3 == 0 { now - 60 } else { now + 60 };
+        pool.store(&format!("id{n}"), "{\"a\":1}", expires, &table)
+            .await
+            .unwrap();
+    }
+
+    let before = stored_ids(&db, &table).await;
+    let deleted: BTreeSet<String> = pool
+        .delete_by_expiry(&table)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    let after = stored_ids(&db, &table).await;
+
+    assert_eq!(deleted.len(), 10);
+    assert_eq!(
+        deleted,
+        before.difference(&after).cloned().collect::<BTreeSet<_>>()
+    );
+}
+
+//DELETE ... RETURNING with the sqlite feature, a select then a delete without it
+#[tokio::test]
+async fn sqlite_reports_the_deleted_ids() {
+    check_reported_ids(common::sqlite().await).await;
+}
+
+//always DELETE ... RETURNING
+#[tokio::test]
+async fn postgres_reports_the_deleted_ids() {
+    if let Some(db) = common::postgres().await {
+        check_reported_ids(db).await;
+    }
+}
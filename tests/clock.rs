@@ -0,0 +1,64 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::{DateTime, Duration};
+use dxp_axum_session::{DbPool, MemoryPool, MockClock};
+
+//expiry follows the mock clock alone, no test sleeps
+async fn check_expiry(pool: &impl DatabasePool, clock: &MockClock, now: i64, table: &str) {
+    pool.initiate(table).await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 60, table).await.unwrap();
+
+    clock.advance(Duration::seconds(59));
+    assert!(pool.exists("a", table).await.unwrap());
+    assert!(pool.load("a", table).await.unwrap().is_some());
+    assert!(pool.delete_by_expiry(table).await.unwrap().is_empty());
+
+    clock.advance(Duration::seconds(1));
+    assert!(!pool.exists("a", table).await.unwrap());
+    assert_eq!(pool.load("a", table).await.unwrap(), None);
+}
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+#[tokio::test]
+async fn db_pool_follows_the_clock() {
+    let (clock, now) = frozen();
+    let pool = DbPool::new(common::sqlite().await).with_clock(clock.clone());
+    let table = common::table("clock");
+
+    check_expiry(&pool, &clock, now, &table).await;
+    assert_eq!(pool.delete_by_expiry(&table).await.unwrap(), ["a"]);
+}
+
+#[tokio::test]
+async fn memory_pool_follows_the_clock() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new().with_clock(clock.clone());
+
+    check_expiry(&pool, &clock, now, "clock").await;
+}
+
+#[tokio::test]
+async fn a_clock_set_back_revives_nothing_deleted() {
+    let (clock, now) = frozen();
+    let pool = DbPool::new(common::sqlite().await).with_clock(clock.clone());
+    let table = common::table("clock");
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 60, &table)
+        .await
+        .unwrap();
+
+    clock.set(DateTime::from_timestamp(now + 120, 0).unwrap());
+    assert_eq!(pool.delete_by_expiry(&table).await.unwrap(), ["a"]);
+
+    clock.set(DateTime::from_timestamp(now, 0).unwrap());
+    assert_eq!(pool.load("a", &table).await.unwrap(), None);
+}
@@ -0,0 +1,54 @@
+//shared by the integration tests, not every test file uses every helper
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use sea_orm::{Database, DatabaseConnection};
+
+//a fresh SQLite file per call, an in-memory database would be a different one per connection
+pub async fn sqlite() -> DatabaseConnection {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "dxp-axum-session-{}-{}.db",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    Database::connect(format!("sqlite://{}?mode=rwc", path.display()))
+        .await
+        .expect("sqlite connects")
+}
+
+//the Postgres tests only run with POSTGRES_URL set, e.g. postgres://postgres@localhost/postgres
+pub async fn postgres() -> Option<DatabaseConnection> {
+    let url = std::env::var("POSTGRES_URL").ok()?;
+    Some(Database::connect(url).await.expect("postgres connects"))
+}
+
+//a table name no other test uses, so tests sharing a database can run in parallel
+pub fn table(prefix: &str) -> String {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+
+    format!(
+        "{prefix}_{}_{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+//seconds since 1970, what DatabasePool::store() takes as expiry
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("the clock is past 1970")
+        .as_secs() as i64
+}
+
+//a fresh SQLite database, and Postgres as well when POSTGRES_URL is set
+pub async fn databases() -> Vec<DatabaseConnection> {
+    let mut databases = vec![sqlite().await];
+    databases.extend(postgres().await);
+    databases
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{Clock, DbPool, MemoryPool, MockClock};
+
+//expired sessions still stored, not yet removed by delete_by_expiry(), are not counted
+async fn check_count(pool: &impl DatabasePool, clock: Option<&MockClock>, table: &str) {
+    let now = clock.map_or_else(common::now, |clock| clock.now().timestamp());
+    pool.initiate(table).await.unwrap();
+
+    pool.store("past", "{\"a\":1}", now - 60, table)
+        .await
+        .unwrap();
+    pool.store("live", "{\"a\":1}", now + 3600, table)
+        .await
+        .unwrap();
+    pool.store("never", "{\"a\":1}", i64::MAX, table)
+        .await
+        .unwrap();
+    assert_eq!(pool.count(table).await.unwrap(), 2);
+
+    if let Some(clock) = clock {
+        pool.store("soon", "{\"a\":1}", now + 10, table)
+            .await
+            .unwrap();
+        assert_eq!(pool.count(table).await.unwrap(), 3);
+
+        clock.advance(chrono::Duration::seconds(10));
+        assert_eq!(pool.count(table).await.unwrap(), 2);
+    }
+}
+
+fn clock() -> MockClock {
+    MockClock::new(DateTime::from_timestamp(common::now(), 0).unwrap())
+}
+
+#[tokio::test]
+async fn db_pool_counts_live_sessions() {
+    let clock = clock();
+    let pool = DbPool::new(common::sqlite().await).with_clock(clock.clone());
+
+    check_count(&pool, Some(&clock), &common::table("count")).await;
+}
+
+#[tokio::test]
+async fn db_pool_counts_live_sessions_on_postgres() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let clock = clock();
+    let pool = DbPool::new(db).with_clock(clock.clone());
+
+    check_count(&pool, Some(&clock), &common::table("count")).await;
+}
+
+#[tokio::test]
+async fn memory_pool_counts_live_sessions() {
+    let clock = clock();
+    let pool = MemoryPool::new().with_clock(clock.clone());
+
+    check_count(&pool, Some(&clock), "count").await;
+}
+
+#[cfg(feature = "dashmap")]
+#[tokio::test]
+async fn dash_memory_pool_counts_live_sessions() {
+    let pool = dxp_axum_session::DashMemoryPool::new();
+
+    check_count(&pool, None, "count").await;
+}
+
+#[tokio::test]
+async fn count_total_includes_expired_rows() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("count");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        for (id, expires) in [
+            ("a", now - 60),
+            ("b", now - 1),
+            ("c", now + 60),
+            ("d", i64::MAX),
+        ] {
+            pool.store(id, "{\"a\":1}", expires, &table).await.unwrap();
+        }
+
+        assert_eq!(pool.count(&table).await.unwrap(), 2);
+        assert_eq!(pool.count_total(&table).await.unwrap(), 4);
+    }
+}
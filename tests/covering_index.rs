@@ -0,0 +1,62 @@
+#![cfg(feature = "postgres")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement, TransactionTrait};
+
+//without seq scans, a table this small would be read whole no matter the index
+async fn plan(db: &DatabaseConnection, query: &str) -> String {
+    let txn = db.begin().await.unwrap();
+    txn.execute_unprepared("SET LOCAL enable_seqscan = off")
+        .await
+        .unwrap();
+    let rows = txn
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            format!("EXPLAIN {query}"),
+        ))
+        .await
+        .unwrap();
+    txn.rollback().await.unwrap();
+
+    rows.iter()
+        .map(|row| row.try_get_by_index::<String>(0).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+//the queries of get_ids() and the cleanup sweep read the id alone, the (expires, id) index
+//answers them without touching the table. ignored rather than passed without POSTGRES_URL, run
+//it with --ignored
+#[tokio::test]
+#[ignore = "needs POSTGRES_URL"]
+async fn id_queries_are_answered_from_the_index() {
+    let db = common::postgres().await.expect("POSTGRES_URL is set");
+    let pool = DbPool::new(db.clone());
+    let table = common::table("covering");
+    let now = common::now();
+
+    let ids: Vec<String> = (0..5_000).map(|n| format!("id{n}")).collect();
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .enumerate()
+        .map(|(n, id)| (id.as_str(), "{\"a\":1}", now - 60 + n as i64))
+        .collect();
+    pool.initiate(&table).await.unwrap();
+    pool.store_many(&sessions, &table).await.unwrap();
+    db.execute_unprepared(&format!("VACUUM ANALYZE {table}"))
+        .await
+        .unwrap();
+
+    let index_only = format!("Index Only Scan using {table}_expires_idx");
+    for query in [
+        format!("SELECT id FROM {table} WHERE expires IS NULL OR expires > to_timestamp({now})"),
+        format!("SELECT id FROM {table} WHERE expires <= to_timestamp({now})"),
+        format!("SELECT id FROM {table} WHERE expires <= to_timestamp({now}) LIMIT 100"),
+    ] {
+        let plan = plan(&db, &query).await;
+        assert!(plan.contains(&index_only), "{query}\n{plan}");
+    }
+}
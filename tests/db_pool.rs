@@ -0,0 +1,377 @@
+mod common;
+
+use axum_session::{DatabaseError, DatabasePool};
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, MockClock};
+use sea_orm::ConnectionTrait;
+
+const YEAR: i64 = 365 * 24 * 3600;
+
+#[tokio::test]
+async fn exists_sees_sessions_that_never_expire() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("never", "{\"a\":1}", i64::MAX, &table)
+            .await
+            .unwrap();
+        pool.store("far", "{\"a\":1}", now + 10 * YEAR, &table)
+            .await
+            .unwrap();
+        pool.store("expired", "{\"a\":1}", now - 1, &table)
+            .await
+            .unwrap();
+
+        assert!(pool.exists("never", &table).await.unwrap());
+        assert!(pool.exists("far", &table).await.unwrap());
+        assert!(!pool.exists("expired", &table).await.unwrap());
+    }
+}
+
+#[tokio::test]
+async fn expiry_keeps_its_seconds() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+        let expires = common::now() + 90;
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+
+        let (_, stored) = pool.load_with_expiry("a", &table).await.unwrap().unwrap();
+        assert_eq!(stored.map(|stored| stored.timestamp()), Some(expires));
+    }
+}
+
+#[tokio::test]
+async fn a_date_expires_column_is_refused() {
+    for db in common::databases().await {
+        let table = common::table("db_pool");
+        db.execute_unprepared(&format!(
+            "CREATE TABLE {table} (id VARCHAR(128) PRIMARY KEY, expires DATE, session TEXT NOT NULL)"
+        ))
+        .await
+        .unwrap();
+
+        let err = DbPool::new(db).initiate(&table).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("DATE truncates every expiry to midnight"),
+            "{err}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn delete_by_expiry_leaves_sessions_that_never_expire() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("never", "{\"a\":1}", i64::MAX, &table)
+            .await
+            .unwrap();
+        pool.store("expired", "{\"a\":1}", common::now() - 1, &table)
+            .await
+            .unwrap();
+
+        assert_eq!(pool.delete_by_expiry(&table).await.unwrap(), ["expired"]);
+        assert!(pool.load("never", &table).await.unwrap().is_some());
+    }
+}
+
+//a DATE column would have the session expire at midnight, not 90 seconds from now
+#[tokio::test]
+async fn sessions_expire_to_the_second() {
+    for db in common::databases().await {
+        let now = common::now();
+        let clock = MockClock::new(DateTime::from_timestamp(now, 0).unwrap());
+        let pool = DbPool::new(db).with_clock(clock.clone());
+        let table = common::table("db_pool");
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", now + 90, &table)
+            .await
+            .unwrap();
+
+        clock.advance(chrono::Duration::seconds(89));
+        assert!(pool.exists("a", &table).await.unwrap());
+
+        clock.advance(chrono::Duration::seconds(1));
+        assert!(!pool.exists("a", &table).await.unwrap());
+        assert_eq!(pool.delete_by_expiry(&table).await.unwrap(), ["a"]);
+    }
+}
+
+#[tokio::test]
+async fn load_deletes_an_expired_row_lazily() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_lazy_expiry(true);
+        let table = common::table("db_pool");
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", common::now() - 10, &table)
+            .await
+            .unwrap();
+        assert_eq!(pool.count_total(&table).await.unwrap(), 1);
+        assert_eq!(pool.load("a", &table).await.unwrap(), None);
+
+        //deleted by a background task
+        let mut left = 1;
+        for _ in 0..200 {
+            left = pool.count_total(&table).await.unwrap();
+            if left == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(left, 0);
+    }
+}
+
+#[tokio::test]
+async fn the_table_name_passed_in_is_used() {
+    let db = common::sqlite().await;
+    let pool = DbPool::new(db.clone());
+    let table = common::table("custom");
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", common::now() + 60, &table)
+        .await
+        .unwrap();
+    assert!(pool.exists("a", &table).await.unwrap());
+    assert_eq!(pool.get_ids(&table).await.unwrap(), ["a"]);
+    assert_eq!(pool.count(&table).await.unwrap(), 1);
+    pool.delete_one_by_id("a", &table).await.unwrap();
+    assert_eq!(pool.load("a", &table).await.unwrap(), None);
+    pool.delete_by_expiry(&table).await.unwrap();
+    pool.delete_all(&table).await.unwrap();
+
+    let tables = db
+        .query_all(sea_orm::Statement::from_string(
+            sea_orm::DbBackend::Sqlite,
+            "SELECT name FROM sqlite_master WHERE type = 'table'",
+        ))
+        .await
+        .unwrap();
+    let tables: Vec<String> = tables
+        .iter()
+        .map(|row| row.try_get::<String>("", "name").unwrap())
+        .collect();
+    assert_eq!(tables, [table]);
+}
+
+#[tokio::test]
+async fn a_second_store_replaces_payload_and_expiry() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", now + 60, &table)
+            .await
+            .unwrap();
+        pool.store("a", "{\"a\":2}", now + 120, &table)
+            .await
+            .unwrap();
+
+        let (session, expires) = pool.load_with_expiry("a", &table).await.unwrap().unwrap();
+        assert_eq!(session, "{\"a\":2}");
+        assert_eq!(expires.map(|expires| expires.timestamp()), Some(now + 120));
+        assert_eq!(pool.count_total(&table).await.unwrap(), 1);
+    }
+}
+
+#[tokio::test]
+async fn storing_the_same_tuple_twice_is_ok() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+        let expires = common::now() + 60;
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+        pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+
+        assert_eq!(
+            pool.load("a", &table).await.unwrap().as_deref(),
+            Some("{\"a\":1}")
+        );
+    }
+}
+
+#[tokio::test]
+async fn tenant_tables_are_isolated() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let tenant_a = common::table("tenant_a_sessions");
+        let tenant_b = common::table("tenant_b_sessions");
+        let expires = common::now() + 60;
+
+        pool.initiate(&tenant_a).await.unwrap();
+        pool.initiate(&tenant_b).await.unwrap();
+        pool.store("a", "{\"tenant\":\"a\"}", expires, &tenant_a)
+            .await
+            .unwrap();
+        pool.store("b", "{\"tenant\":\"b\"}", expires, &tenant_b)
+            .await
+            .unwrap();
+
+        assert_eq!(pool.load("b", &tenant_a).await.unwrap(), None);
+        assert_eq!(pool.load("a", &tenant_b).await.unwrap(), None);
+        assert!(!pool.exists("b", &tenant_a).await.unwrap());
+        assert_eq!(pool.get_ids(&tenant_a).await.unwrap(), ["a"]);
+        assert_eq!(pool.get_ids(&tenant_b).await.unwrap(), ["b"]);
+
+        //deleting in one tenant leaves the other alone
+        pool.delete_one_by_id("b", &tenant_a).await.unwrap();
+        pool.delete_all(&tenant_a).await.unwrap();
+        assert_eq!(
+            pool.load("b", &tenant_b).await.unwrap().as_deref(),
+            Some("{\"tenant\":\"b\"}")
+        );
+    }
+}
+
+//a 6 KB payload, the ids are read without it
+fn large_payload(n: usize) -> String {
+    format!("{{\"n\":{n},\"data\":\"{}\"}}", "x".repeat(6 * 1024))
+}
+
+#[tokio::test]
+async fn get_ids_returns_the_live_ids() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        for n in 0..50 {
+            let expires = if n % 5 == 0 { now - 60 } else { now + 60 };
+            pool.store(&format!("id{n:02}"), &large_payload(n), expires, &table)
+                .await
+                .unwrap();
+        }
+        pool.store("never", &large_payload(50), i64::MAX, &table)
+            .await
+            .unwrap();
+
+        let mut ids = pool.get_ids(&table).await.unwrap();
+        ids.sort();
+        let mut expected: Vec<String> = (0..50)
+            .filter(|n| n % 5 != 0)
+            .map(|n| format!("id{n:02}"))
+            .collect();
+        expected.push("never".to_owned());
+        assert_eq!(ids, expected);
+    }
+}
+
+#[tokio::test]
+async fn delete_by_expiry_returns_the_deleted_ids() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("db_pool");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        for n in 0..50 {
+            let expires = if n % 5 == 0 { now - 60 } else { now + 60 };
+            pool.store(&format!("id{n:02}"), &large_payload(n), expires, &table)
+                .await
+                .unwrap();
+        }
+
+        let mut deleted = pool.delete_by_expiry(&table).await.unwrap();
+        deleted.sort();
+        let expired: Vec<String> = (0..50)
+            .filter(|n| n % 5 == 0)
+            .map(|n| format!("id{n:02}"))
+            .collect();
+        assert_eq!(deleted, expired);
+        assert_eq!(pool.count_total(&table).await.unwrap(), 40);
+        assert_eq!(
+            pool.delete_by_expiry(&table).await.unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
+
+//the upsert does not ask for the inserted row back, a failed one must still be an error
+#[tokio::test]
+async fn a_failing_upsert_is_an_error() {
+    for db in common::databases().await {
+        let table = common::table("db_pool");
+        let expires = common::now() + 60;
+
+        let missing = DbPool::new(db.clone())
+            .store("a", "{\"a\":1}", expires, &common::table("missing"))
+            .await;
+        assert!(
+            matches!(missing, Err(DatabaseError::GenericInsertError(_))),
+            "{missing:?}"
+        );
+
+        db.execute_unprepared(&format!(
+            "CREATE TABLE {table} (id VARCHAR(128) PRIMARY KEY, \
+             expires TIMESTAMP WITH TIME ZONE, \
+             session TEXT NOT NULL CHECK (LENGTH(session) < 10))"
+        ))
+        .await
+        .unwrap();
+        let pool = DbPool::new(db).with_schema_check(false);
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+
+        //an insert and an update breaking the constraint
+        for id in ["b", "a"] {
+            let result = pool
+                .store(id, "{\"a\":\"too long\"}", expires, &table)
+                .await;
+            assert!(
+                matches!(result, Err(DatabaseError::GenericInsertError(_))),
+                "{result:?}"
+            );
+        }
+        assert_eq!(
+            pool.load("a", &table).await.unwrap().as_deref(),
+            Some("{\"a\":1}")
+        );
+    }
+}
+
+//a table from before the metadata columns, its rows get them filled in rather than left NULL
+#[tokio::test]
+async fn initiate_stamps_existing_rows_with_the_metadata_columns() {
+    for db in common::databases().await {
+        let table = common::table("db_pool");
+
+        db.execute_unprepared(&format!(
+            "CREATE TABLE {table} (id VARCHAR(128) PRIMARY KEY, \
+             expires TIMESTAMP WITH TIME ZONE, session TEXT NOT NULL)"
+        ))
+        .await
+        .unwrap();
+        db.execute_unprepared(&format!(
+            "INSERT INTO {table} (id, expires, session) VALUES ('old', NULL, '{{}}')"
+        ))
+        .await
+        .unwrap();
+
+        let pool = DbPool::new(db);
+        pool.initiate(&table).await.unwrap();
+
+        let meta = pool
+            .get_session_metadata("old", &table)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(meta.created_at.is_some(), "{meta:?}");
+        assert!(meta.updated_at.is_some(), "{meta:?}");
+    }
+}
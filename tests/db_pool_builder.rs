@@ -0,0 +1,70 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, DbPoolBuildError};
+
+#[tokio::test]
+async fn two_table_names_share_one_database() {
+    let db = common::sqlite().await;
+    let admin_table = common::table("admin_sessions");
+    let api_table = common::table("api_sessions");
+    let admin = DbPool::builder(db.clone())
+        .table_name(admin_table.clone())
+        .build()
+        .unwrap();
+    let api = DbPool::builder(db)
+        .table_name(api_table.clone())
+        .build()
+        .unwrap();
+    let expires = common::now() + 60;
+
+    //both are handed the same name, the builder's one wins
+    admin.initiate("sessions").await.unwrap();
+    api.initiate("sessions").await.unwrap();
+    admin
+        .store("a", "{\"admin\":1}", expires, "sessions")
+        .await
+        .unwrap();
+    api.store("a", "{\"api\":1}", expires, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        admin.load("a", "sessions").await.unwrap().as_deref(),
+        Some("{\"admin\":1}")
+    );
+    assert_eq!(
+        api.load("a", "sessions").await.unwrap().as_deref(),
+        Some("{\"api\":1}")
+    );
+
+    admin.delete_all("sessions").await.unwrap();
+    assert_eq!(admin.load("a", "sessions").await.unwrap(), None);
+    assert_eq!(
+        api.load("a", "sessions").await.unwrap().as_deref(),
+        Some("{\"api\":1}")
+    );
+    assert_eq!(api.count("sessions").await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn build_refuses_bad_table_names() {
+    let db = common::sqlite().await;
+
+    assert_eq!(
+        DbPool::builder(db.clone())
+            .table_name("")
+            .build()
+            .unwrap_err(),
+        DbPoolBuildError::EmptyTableName
+    );
+    for name in ["sessions; DROP TABLE users", "a-b", "\"sessions\"", "sé"] {
+        assert_eq!(
+            DbPool::builder(db.clone())
+                .table_name(name)
+                .build()
+                .unwrap_err(),
+            DbPoolBuildError::InvalidTableName(name.to_string())
+        );
+    }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MockClock};
+use sea_orm::DatabaseConnection;
+
+const SESSIONS: usize = 300;
+
+//even ids are renewed one by one while the sweep runs in a loop, odd ids are left to expire.
+//a returned id has to be gone: a renewal after the delete inserts a new row with a new
+//created_at, the upsert of a row the sweep only claimed to delete keeps the old one
+async fn renewals_survive_cleanup(db: DatabaseConnection) {
+    let table = common::table("cleanup_race");
+    //seeded while the clock stands before their expiry, so their metadata can still be read
+    let clock = MockClock::new(chrono::Utc::now() - chrono::Duration::minutes(1));
+    let pool = DbPool::new(db)
+        .with_clock(clock.clone())
+        .with_cleanup_chunk_size(7)
+        .with_busy_retries(100);
+    pool.initiate(&table).await.unwrap();
+
+    let ids: Vec<String> = (0..SESSIONS).map(|i| format!("session-{i:04}")).collect();
+    for id in &ids {
+        pool.store(id, "{\"user\":1}", common::now() - 10, &table)
+            .await
+            .unwrap();
+    }
+
+    let mut created = Vec::new();
+    for id in &ids {
+        let meta = pool
+            .get_session_metadata(id, &table)
+            .await
+            .unwrap()
+            .unwrap();
+        created.push(meta.created_at);
+    }
+    clock.set(chrono::Utc::now());
+
+    let renewed = Arc::new(Mutex::new(HashSet::new()));
+    let renewer = {
+        let (pool, table, ids, renewed) =
+            (pool.clone(), table.clone(), ids.clone(), renewed.clone());
+        tokio::spawn(async move {
+            for id in ids.iter().step_by(2) {
+                pool.store(id, "{\"user\":2}", common::now() + 3600, &table)
+                    .await
+                    .unwrap();
+                renewed.lock().unwrap().insert(id.clone());
+                tokio::task::yield_now().await;
+            }
+        })
+    };
+
+    let mut swept = HashSet::new();
+    loop {
+        let done = renewer.is_finished();
+        //renewed before this sweep started, so live for all of it
+        let live: HashSet<String> = renewed.lock().unwrap().clone();
+
+        for id in pool.delete_by_expiry(&table).await.unwrap() {
+            assert!(
+                !live.contains(&id),
+                "the sweep returned the live session {id}"
+            );
+            assert!(swept.insert(id.clone()), "{id} was returned twice");
+        }
+
+        if done {
+            break;
+        }
+    }
+    renewer.await.unwrap();
+
+    for (i, id) in ids.iter().enumerate() {
+        let session = pool.load(id, &table).await.unwrap();
+
+        if i % 2 == 0 {
+            assert_eq!(session.as_deref(), Some("{\"user\":2}"), "{id} was dropped");
+
+            if swept.contains(id) {
+                let meta = pool
+                    .get_session_metadata(id, &table)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_ne!(
+                    meta.created_at, created[i],
+                    "{id} was returned but not deleted"
+                );
+            }
+        } else {
+            assert_eq!(session, None);
+            assert!(swept.contains(id), "{id} was deleted but never returned");
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn sqlite_renewals_survive_cleanup() {
+    renewals_survive_cleanup(common::sqlite().await).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn postgres_renewals_survive_cleanup() {
+    if let Some(db) = common::postgres().await {
+        renewals_survive_cleanup(db).await;
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "encrypted_pool")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{EncryptedPool, MemoryPool};
+
+const KEY: [u8; 32] = [7; 32];
+
+//the clone shares its entries, so a test can read and write the raw payloads
+fn pools() -> (EncryptedPool<MemoryPool>, MemoryPool) {
+    let storage = MemoryPool::new();
+    (EncryptedPool::new(storage.clone(), &KEY), storage)
+}
+
+#[tokio::test]
+async fn roundtrip_returns_the_original_data() {
+    let (pool, storage) = pools();
+    let session = r#"{"user":1}"#;
+    pool.store("a", session, common::now() + 60, "t")
+        .await
+        .unwrap();
+
+    assert_eq!(pool.load("a", "t").await.unwrap().as_deref(), Some(session));
+    assert!(pool.exists("a", "t").await.unwrap());
+    let stored = storage.load("a", "t").await.unwrap().unwrap();
+    assert!(!stored.contains("user"));
+}
+
+#[tokio::test]
+async fn a_ciphertext_moved_to_another_session_does_not_decrypt() {
+    let (pool, storage) = pools();
+    let expires = common::now() + 60;
+    pool.store("admin", r#"{"admin":true}"#, expires, "t")
+        .await
+        .unwrap();
+    let stored = storage.load("admin", "t").await.unwrap().unwrap();
+
+    storage
+        .store("attacker", &stored, expires, "t")
+        .await
+        .unwrap();
+
+    assert_eq!(pool.load("attacker", "t").await.unwrap(), None);
+    assert!(!pool.exists("attacker", "t").await.unwrap());
+    assert_eq!(pool.load("admin", "other").await.unwrap(), None);
+    assert!(pool.load("admin", "t").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn an_old_key_still_decrypts_after_rotation() {
+    let (pool, storage) = pools();
+    pool.store("a", "{}x", common::now() + 60, "t")
+        .await
+        .unwrap();
+
+    let rotated = EncryptedPool::new(storage.clone(), &[8; 32]).with_decryption_key(&KEY);
+    assert_eq!(
+        rotated.load("a", "t").await.unwrap().as_deref(),
+        Some("{}x")
+    );
+
+    let forgotten = EncryptedPool::new(storage, &[8; 32]);
+    assert_eq!(forgotten.load("a", "t").await.unwrap(), None);
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, ErrorKind, MockClock};
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+//the layout axum_session_sqlx creates
+async fn legacy_table(db: &impl ConnectionTrait, table: &str) {
+    db.execute_unprepared(&format!(
+        "CREATE TABLE {table} (id VARCHAR(128) NOT NULL PRIMARY KEY, expires BIGINT NULL, \
+         session TEXT NOT NULL)"
+    ))
+    .await
+    .unwrap();
+}
+
+//the table's column names, sorted
+async fn columns(db: &impl ConnectionTrait, table: &str) -> Vec<String> {
+    let backend = db.get_database_backend();
+    let sql = match backend {
+        DbBackend::Postgres => format!(
+            "SELECT column_name::text AS name FROM information_schema.columns \
+             WHERE table_name = '{table}'"
+        ),
+        _ => format!("SELECT name FROM pragma_table_info('{table}')"),
+    };
+
+    let mut columns: Vec<String> = db
+        .query_all(Statement::from_string(backend, sql))
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.try_get("", "name").unwrap())
+        .collect();
+    columns.sort();
+    columns
+}
+
+async fn raw_expires(db: &impl ConnectionTrait, table: &str, id: &str) -> Option<i64> {
+    let row = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            format!("SELECT expires FROM {table} WHERE id = '{id}'"),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    row.try_get("", "expires").unwrap()
+}
+
+#[tokio::test]
+async fn a_legacy_bigint_table_round_trips() {
+    for db in common::databases().await {
+        let now = common::now();
+        let table = common::table("legacy_sessions");
+        legacy_table(&db, &table).await;
+        let pool = DbPool::new(db.clone())
+            .with_epoch_expires(true)
+            .with_clock(MockClock::new(DateTime::from_timestamp(now, 0).unwrap()));
+
+        //the table exists already, initiate() accepts it as it is and leaves it so
+        pool.initiate(&table).await.unwrap();
+        assert_eq!(columns(&db, &table).await, ["expires", "id", "session"]);
+        pool.store("live", "{\"a\":1}", now + 60, &table)
+            .await
+            .unwrap();
+        pool.store("expired", "{\"a\":2}", now - 60, &table)
+            .await
+            .unwrap();
+
+        //written as unix seconds, what axum_session_sqlx reads back
+        assert_eq!(raw_expires(&db, &table, "live").await, Some(now + 60));
+        assert_eq!(raw_expires(&db, &table, "expired").await, Some(now - 60));
+
+        assert!(pool.exists("live", &table).await.unwrap());
+        assert!(!pool.exists("expired", &table).await.unwrap());
+        assert_eq!(pool.get_ids(&table).await.unwrap(), ["live"]);
+        assert_eq!(pool.count(&table).await.unwrap(), 1);
+        assert_eq!(
+            pool.load("live", &table).await.unwrap().as_deref(),
+            Some("{\"a\":1}")
+        );
+
+        pool.store("live", "{\"a\":3}", now + 120, &table)
+            .await
+            .unwrap();
+        assert_eq!(raw_expires(&db, &table, "live").await, Some(now + 120));
+        assert_eq!(
+            pool.load("live", &table).await.unwrap().as_deref(),
+            Some("{\"a\":3}")
+        );
+
+        assert_eq!(pool.delete_by_expiry(&table).await.unwrap(), ["expired"]);
+        pool.delete_one_by_id("live", &table).await.unwrap();
+        assert_eq!(pool.load("live", &table).await.unwrap(), None);
+        assert_eq!(pool.count(&table).await.unwrap(), 0);
+        assert_eq!(columns(&db, &table).await, ["expires", "id", "session"]);
+    }
+}
+
+#[tokio::test]
+async fn a_new_epoch_table_has_the_legacy_layout() {
+    for db in common::databases().await {
+        let table = common::table("epoch_sessions");
+        let pool = DbPool::new(db.clone()).with_epoch_expires(true);
+
+        pool.initiate(&table).await.unwrap();
+        assert_eq!(columns(&db, &table).await, ["expires", "id", "session"]);
+
+        //a session moved to another id is copied as it is
+        let now = common::now();
+        pool.store("a", "{\"a\":1}", now + 60, &table)
+            .await
+            .unwrap();
+        pool.touch("a", now + 120, &table).await.unwrap();
+        assert!(pool.rotate("a", "b", &table).await.unwrap());
+        assert_eq!(raw_expires(&db, &table, "b").await, Some(now + 120));
+        pool.store_many(&[("c", "{\"c\":1}", now + 60)], &table)
+            .await
+            .unwrap();
+        assert_eq!(pool.get_ids(&table).await.unwrap().len(), 2);
+    }
+}
+
+//they need the metadata columns the layout does not have
+#[tokio::test]
+async fn metadata_features_are_not_supported() {
+    let pool = DbPool::new(common::sqlite().await).with_epoch_expires(true);
+    let table = common::table("epoch_sessions");
+    pool.initiate(&table).await.unwrap();
+
+    let kinds = [
+        pool.store_with_user("a", "{}", common::now() + 60, "1", &table)
+            .await
+            .unwrap_err()
+            .kind(),
+        pool.get_ids_by_user_id("1", &table)
+            .await
+            .unwrap_err()
+            .kind(),
+        pool.delete_by_user_id("1", &table)
+            .await
+            .unwrap_err()
+            .kind(),
+        pool.get_session_metadata("a", &table)
+            .await
+            .unwrap_err()
+            .kind(),
+    ];
+    assert_eq!(kinds, [ErrorKind::NotSupported; 4]);
+}
+
+#[cfg(feature = "soft_delete")]
+#[tokio::test]
+async fn soft_deletes_are_refused() {
+    let db = common::sqlite().await;
+    let table = common::table("legacy_sessions");
+    legacy_table(&db, &table).await;
+    let pool = DbPool::new(db.clone())
+        .with_epoch_expires(true)
+        .with_soft_delete(true);
+
+    assert!(pool.initiate(&table).await.is_err());
+    assert_eq!(columns(&db, &table).await, ["expires", "id", "session"]);
+}
+
+//rows axum_session_sqlx wrote before the switch are read as they are
+#[tokio::test]
+async fn rows_written_by_the_old_store_are_read() {
+    for db in common::databases().await {
+        let now = common::now();
+        let table = common::table("legacy_sessions");
+        legacy_table(&db, &table).await;
+        db.execute_unprepared(&format!(
+            "INSERT INTO {table} (id, expires, session) VALUES ('old', {}, '{{\"a\":1}}')",
+            now + 60
+        ))
+        .await
+        .unwrap();
+        let pool = DbPool::new(db).with_epoch_expires(true);
+
+        pool.initiate(&table).await.unwrap();
+        assert_eq!(
+            pool.load("old", &table).await.unwrap().as_deref(),
+            Some("{\"a\":1}")
+        );
+        assert_eq!(pool.get_ids(&table).await.unwrap(), ["old"]);
+    }
+}
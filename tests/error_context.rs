@@ -0,0 +1,85 @@
+mod common;
+
+use axum_session::{DatabaseError, DatabasePool};
+use dxp_axum_session::{DbPool, MemoryPool};
+
+const ID: &str = "1a2b3c4d5e6f7a8b9c0d";
+
+//what axum_session gets to log
+fn message(error: DatabaseError) -> String {
+    match error {
+        DatabaseError::GenericAquire(message)
+        | DatabaseError::GenericInsertError(message)
+        | DatabaseError::GenericSelectError(message)
+        | DatabaseError::GenericCreateError(message)
+        | DatabaseError::GenericDeleteError(message)
+        | DatabaseError::GenericNotSupportedError(message) => message,
+    }
+}
+
+fn message_of<T: std::fmt::Debug>(result: Result<T, DatabaseError>) -> String {
+    message(result.unwrap_err())
+}
+
+#[tokio::test]
+async fn a_db_error_names_operation_table_and_id_prefix() {
+    let pool = DbPool::new(common::sqlite().await);
+    let table = common::table("missing");
+
+    //the table was never created
+    let error = pool.load(ID, &table).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::GenericSelectError(_)));
+    let message = message(error);
+    assert!(
+        message.starts_with(&format!("load on {table} for session 1a2b3c4d…: ")),
+        "{message}"
+    );
+    assert!(!message.contains(ID), "{message}");
+
+    let message = message_of(pool.store(ID, "{}", common::now() + 60, &table).await);
+    assert!(
+        message.starts_with(&format!("store on {table} for session 1a2b3c4d…: ")),
+        "{message}"
+    );
+    assert!(!message.contains(ID), "{message}");
+
+    let message = message_of(pool.delete_by_expiry(&table).await);
+    assert!(
+        message.starts_with(&format!("delete_by_expiry on {table}: ")),
+        "{message}"
+    );
+}
+
+#[tokio::test]
+async fn a_memory_error_names_operation_table_and_id_prefix() {
+    let pool = MemoryPool::new();
+    let id = format!("{ID}!");
+
+    let message = message_of(pool.store(&id, "{}", common::now() + 60, "sessions").await);
+    assert!(
+        message.starts_with("store on sessions for session 1a2b3c4d…: "),
+        "{message}"
+    );
+    assert!(!message.contains(&id), "{message}");
+
+    let message = message_of(pool.load(&id, "sessions").await);
+    assert!(
+        message.starts_with("load on sessions for session 1a2b3c4d…: "),
+        "{message}"
+    );
+    assert!(!message.contains(ID), "{message}");
+}
+
+//an id of 8 characters or less is left out, its prefix would be all of it
+#[tokio::test]
+async fn a_short_id_is_not_printed() {
+    let pool = DbPool::new(common::sqlite().await);
+    let table = common::table("missing");
+
+    let message = message_of(pool.load("abcdefgh", &table).await);
+    assert!(
+        message.starts_with(&format!("load on {table} for session <8 chars>: ")),
+        "{message}"
+    );
+    assert!(!message.contains("abcdefgh"), "{message}");
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, MemoryPool, MockClock};
+
+//a session expiring at exactly `now` is expired everywhere, one expiring a second later is live
+//everywhere
+async fn check_boundary(pool: &impl DatabasePool, table: &str, now: i64) {
+    pool.initiate(table).await.unwrap();
+    pool.store("at_now", "{\"a\":1}", now, table).await.unwrap();
+    pool.store("after_now", "{\"a\":2}", now + 1, table)
+        .await
+        .unwrap();
+
+    //load() last, the memory pools drop an expired entry they are asked to load
+    assert!(!pool.exists("at_now", table).await.unwrap());
+    assert_eq!(
+        pool.get_ids(table).await.unwrap(),
+        vec!["after_now".to_owned()]
+    );
+    assert_eq!(pool.count(table).await.unwrap(), 1);
+    assert!(pool.load("after_now", table).await.unwrap().is_some());
+    assert!(pool.exists("after_now", table).await.unwrap());
+    assert_eq!(
+        pool.delete_by_expiry(table).await.unwrap(),
+        vec!["at_now".to_owned()]
+    );
+    assert_eq!(pool.load("at_now", table).await.unwrap(), None);
+    assert_eq!(
+        pool.get_ids(table).await.unwrap(),
+        vec!["after_now".to_owned()]
+    );
+}
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+#[tokio::test]
+async fn db_pool_boundary() {
+    let (clock, now) = frozen();
+    let pool = DbPool::new(common::sqlite().await).with_clock(clock);
+    check_boundary(&pool, "sessions", now).await;
+}
+
+#[tokio::test]
+async fn db_pool_epoch_boundary() {
+    let (clock, now) = frozen();
+    let pool = DbPool::new(common::sqlite().await)
+        .with_epoch_expires(true)
+        .with_clock(clock);
+    check_boundary(&pool, "sessions", now).await;
+}
+
+#[tokio::test]
+async fn postgres_db_pool_boundary() {
+    if let Some(db) = common::postgres().await {
+        let (clock, now) = frozen();
+        let pool = DbPool::new(db).with_clock(clock);
+        check_boundary(&pool, &common::table("boundary"), now).await;
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_boundary() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new().with_clock(clock);
+    check_boundary(&pool, "sessions", now).await;
+}
+
+//no clock to freeze, the session expiring this second is expired by the time it is read
+#[cfg(feature = "dashmap")]
+#[tokio::test]
+async fn dash_memory_pool_boundary() {
+    let pool = dxp_axum_session::DashMemoryPool::new();
+    let now = common::now();
+    pool.store("at_now", "{}x", now, "sessions").await.unwrap();
+    pool.store("later", "{}x", now + 3600, "sessions")
+        .await
+        .unwrap();
+
+    assert!(!pool.exists("at_now", "sessions").await.unwrap());
+    assert_eq!(
+        pool.get_ids("sessions").await.unwrap(),
+        vec!["later".to_owned()]
+    );
+    assert_eq!(pool.count("sessions").await.unwrap(), 1);
+    assert_eq!(
+        pool.delete_by_expiry("sessions").await.unwrap(),
+        vec!["at_now".to_owned()]
+    );
+    assert_eq!(pool.load("at_now", "sessions").await.unwrap(), None);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::{DateTime, Duration, Utc};
+use dxp_axum_session::{DbPool, ExpiryHistogram, MemoryPool, MockClock};
+
+const BUCKET: Duration = Duration::seconds(60);
+const HORIZON: Duration = Duration::seconds(300);
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+//sessions right around each edge, buckets start at now and the horizon ends the last one
+async fn fill(pool: &impl DatabasePool, table: &str, now: i64) {
+    pool.initiate(table).await.unwrap();
+
+    for (id, expires) in [
+        ("expired", now - 10),
+        ("at_now", now),
+        ("first_second", now + 1),
+        ("last_of_first", now + 59),
+        ("first_of_second", now + 60),
+        ("last_before_horizon", now + 299),
+        ("at_horizon", now + 300),
+        ("never", i64::MAX),
+    ] {
+        pool.store(id, "{\"a\":1}", expires, table).await.unwrap();
+    }
+}
+
+fn expected(now: i64) -> ExpiryHistogram {
+    let start = |offset: i64| DateTime::<Utc>::from_timestamp(now + offset, 0).unwrap();
+
+    ExpiryHistogram {
+        buckets: vec![(start(0), 2), (start(60), 1), (start(240), 1)],
+        never_expires: 1,
+    }
+}
+
+#[tokio::test]
+async fn db_pool_buckets_around_now() {
+    for db in common::databases().await {
+        let (clock, now) = frozen();
+        let pool = DbPool::new(db).with_clock(clock);
+        let table = common::table("histogram");
+
+        fill(&pool, &table, now).await;
+        assert_eq!(
+            pool.expiry_histogram(BUCKET, HORIZON, &table)
+                .await
+                .unwrap(),
+            expected(now),
+        );
+    }
+}
+
+#[tokio::test]
+async fn db_pool_epoch_buckets_around_now() {
+    let (clock, now) = frozen();
+    let pool = DbPool::new(common::sqlite().await)
+        .with_epoch_expires(true)
+        .with_clock(clock);
+
+    fill(&pool, "sessions", now).await;
+    assert_eq!(
+        pool.expiry_histogram(BUCKET, HORIZON, "sessions")
+            .await
+            .unwrap(),
+        expected(now),
+    );
+}
+
+#[tokio::test]
+async fn memory_pool_buckets_around_now() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new().with_clock(clock);
+
+    fill(&pool, "sessions", now).await;
+    assert_eq!(
+        pool.expiry_histogram(BUCKET, HORIZON, "sessions")
+            .await
+            .unwrap(),
+        expected(now),
+    );
+}
+
+#[tokio::test]
+async fn a_bucket_under_a_second_is_refused() {
+    let pool = MemoryPool::new();
+
+    assert!(pool
+        .expiry_histogram(Duration::milliseconds(500), HORIZON, "sessions")
+        .await
+        .is_err());
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool};
+
+//the last second of 9999, the latest expiry every backend can store
+const YEAR_9999: i64 = 253_402_300_799;
+
+async fn check_inputs(pool: &impl DatabasePool, table: &str) {
+    pool.initiate(table).await.unwrap();
+
+    let err = pool
+        .store("negative", "{\"a\":1}", -1, table)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("must not be negative"), "{err}");
+    assert_eq!(pool.load("negative", table).await.unwrap(), None);
+
+    //1970, stored but expired
+    pool.store("zero", "{\"a\":1}", 0, table).await.unwrap();
+    assert_eq!(pool.load("zero", table).await.unwrap(), None);
+
+    for (id, expires) in [
+        ("normal", common::now() + 3600),
+        ("year_9999", YEAR_9999),
+        ("max", i64::MAX),
+    ] {
+        pool.store(id, "{\"a\":1}", expires, table).await.unwrap();
+        assert!(pool.load(id, table).await.unwrap().is_some(), "{id}");
+    }
+
+    assert_eq!(pool.count(table).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn db_pool_expiry_inputs() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("expiry_input");
+        check_inputs(&pool, &table).await;
+
+        //far past the default of 50 years, so stored as never expiring
+        for id in ["year_9999", "max"] {
+            let (_, expires) = pool.load_with_expiry(id, &table).await.unwrap().unwrap();
+            assert_eq!(expires, None, "{id}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_expiry_inputs() {
+    check_inputs(&MemoryPool::new(), "expiry_input").await;
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, IdPage, MemoryPool};
+
+const SESSIONS: usize = 23;
+
+async fn fill(pool: &impl DatabasePool, table: &str) -> BTreeSet<String> {
+    let expires = common::now() + 3600;
+    pool.initiate(table).await.unwrap();
+
+    let mut ids = BTreeSet::new();
+    for i in 0..SESSIONS {
+        let id = format!("id{i:03}");
+        pool.store(&id, "{\"a\":1}", expires, table).await.unwrap();
+        ids.insert(id);
+    }
+
+    //expired, never on a page
+    pool.store("expired", "{\"a\":1}", common::now() - 10, table)
+        .await
+        .unwrap();
+
+    ids
+}
+
+//follows `next` until the last page, checking no id comes twice
+async fn walk<F, Fut>(mut page_after: F) -> (BTreeSet<String>, usize)
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: std::future::Future<Output = IdPage>,
+{
+    let mut seen = BTreeSet::new();
+    let mut pages = 0;
+    let mut after = None;
+
+    loop {
+        let page = page_after(after).await;
+        pages += 1;
+        assert!(pages <= SESSIONS + 1, "paging does not end");
+
+        for id in page.ids {
+            assert!(seen.insert(id.clone()), "{id} on two pages");
+        }
+
+        match page.next {
+            Some(next) => after = Some(next),
+            None => return (seen, pages),
+        }
+    }
+}
+
+#[tokio::test]
+async fn db_pool_pages_cover_every_id_once() {
+    let pool = DbPool::new(common::sqlite().await);
+    let table = common::table("pages");
+    let ids = fill(&pool, &table).await;
+
+    for (limit, expected_pages) in [(0, SESSIONS), (1, SESSIONS), (5, 5), (23, 1), (100, 1)] {
+        let (seen, pages) = walk(|after| {
+            let (pool, table) = (&pool, &table);
+            async move {
+                pool.get_ids_after(after.as_deref(), limit, table)
+                    .await
+                    .unwrap()
+            }
+        })
+        .await;
+
+        assert_eq!(seen, ids, "limit {limit}");
+        assert_eq!(pages, expected_pages, "limit {limit}");
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_pages_cover_every_id_once() {
+    let pool = MemoryPool::new();
+    let table = "pages";
+    let ids = fill(&pool, table).await;
+
+    for (limit, expected_pages) in [(0, SESSIONS), (1, SESSIONS), (5, 5), (23, 1), (100, 1)] {
+        let (seen, pages) = walk(|after| {
+            let pool = &pool;
+            async move {
+                pool.get_ids_after(after.as_deref(), limit, table)
+                    .await
+                    .unwrap()
+            }
+        })
+        .await;
+
+        assert_eq!(seen, ids, "limit {limit}");
+        assert_eq!(pages, expected_pages, "limit {limit}");
+    }
+}
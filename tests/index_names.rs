@@ -0,0 +1,71 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+async fn indexes(db: &DatabaseConnection, table: &str) -> Vec<String> {
+    let sql = match db.get_database_backend() {
+        DbBackend::Postgres => "SELECT indexname AS name FROM pg_indexes WHERE tablename = $1",
+        _ => "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = ?",
+    };
+
+    let mut names: Vec<String> = db
+        .query_all(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            sql,
+            [table.into()],
+        ))
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.try_get("", "name").unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+//index names are global to a Postgres schema, IF NOT EXISTS would leave the second table without one
+#[tokio::test]
+async fn two_tables_get_their_own_indexes() {
+    for db in common::databases().await {
+        let first = common::table("index_names");
+        let second = common::table("index_names");
+
+        DbPool::new(db.clone()).initiate(&first).await.unwrap();
+        DbPool::new(db.clone()).initiate(&second).await.unwrap();
+
+        for table in [&first, &second] {
+            let indexes = indexes(&db, table).await;
+            assert!(
+                indexes.contains(&format!("{table}_expires_idx")),
+                "{indexes:?}"
+            );
+            assert!(
+                indexes.contains(&format!("{table}_user_id_idx")),
+                "{indexes:?}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn the_expires_index_name_can_be_set() {
+    for db in common::databases().await {
+        let table = common::table("index_names");
+        let name = format!("{table}_by_expiry");
+
+        DbPool::new(db.clone())
+            .with_expires_index_name(name.clone())
+            .initiate(&table)
+            .await
+            .unwrap();
+
+        let indexes = indexes(&db, &table).await;
+        assert!(indexes.contains(&name), "{indexes:?}");
+        assert!(
+            !indexes.contains(&format!("{table}_expires_idx")),
+            "{indexes:?}"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, MemoryPool, MockClock};
+
+const MAX: i64 = 30 * 24 * 3600;
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+//an offset from now, never expiring as i64::MAX
+fn expires(now: i64, requested: i64) -> i64 {
+    if requested == i64::MAX {
+        requested
+    } else {
+        now + requested
+    }
+}
+
+//requested and stored lifetime
+const CASES: [(&str, i64, i64); 4] = [
+    ("below", MAX - 1, MAX - 1),
+    ("at", MAX, MAX),
+    ("above", MAX + 1, MAX),
+    ("never", i64::MAX, MAX),
+];
+
+#[tokio::test]
+async fn db_pool_clamps_below_at_and_above_the_limit() {
+    for db in common::databases().await {
+        let (clock, now) = frozen();
+        let pool = DbPool::new(db)
+            .with_max_lifetime(chrono::Duration::seconds(MAX))
+            .with_clock(clock);
+        let table = common::table("max_lifetime");
+
+        pool.initiate(&table).await.unwrap();
+        for (id, requested, stored) in CASES {
+            pool.store(id, "{\"a\":1}", expires(now, requested), &table)
+                .await
+                .unwrap();
+
+            let (_, expiry) = pool.load_with_expiry(id, &table).await.unwrap().unwrap();
+            assert_eq!(
+                expiry.map(|expiry| expiry.timestamp()),
+                Some(now + stored),
+                "{id}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_clamps_below_at_and_above_the_limit() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new()
+        .with_max_lifetime(chrono::Duration::seconds(MAX))
+        .with_clock(clock);
+
+    for (id, requested, stored) in CASES {
+        pool.store(id, "{\"a\":1}", expires(now, requested), "sessions")
+            .await
+            .unwrap();
+
+        let (_, expiry) = pool
+            .load_with_expiry(id, "sessions")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            expiry.map(|expiry| expiry.timestamp()),
+            Some(now + stored),
+            "{id}"
+        );
+    }
+}
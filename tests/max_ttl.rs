@@ -0,0 +1,82 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, MemoryPool, MockClock};
+
+const TTL: Duration = Duration::from_secs(60);
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+#[tokio::test]
+async fn memory_pool_clamps_to_max_ttl() {
+    let (clock, _) = frozen();
+    let pool = MemoryPool::new()
+        .with_clock(clock.clone())
+        .with_max_ttl(TTL);
+    let table = "max_ttl";
+
+    //never expires without the cap
+    pool.store("a", "{\"a\":1}", i64::MAX, table).await.unwrap();
+
+    clock.advance(chrono::Duration::seconds(59));
+    assert!(pool.load("a", table).await.unwrap().is_some());
+
+    clock.advance(chrono::Duration::seconds(1));
+    assert_eq!(pool.load("a", table).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn memory_pool_keeps_shorter_expiries() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new()
+        .with_clock(clock.clone())
+        .with_max_ttl(TTL);
+    let table = "max_ttl";
+
+    pool.store("a", "{\"a\":1}", now + 10, table).await.unwrap();
+
+    clock.advance(chrono::Duration::seconds(10));
+    assert_eq!(pool.load("a", table).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn db_pool_clamps_to_max_session_ttl() {
+    let (clock, now) = frozen();
+    let pool = DbPool::builder(common::sqlite().await)
+        .max_session_ttl(TTL)
+        .build()
+        .unwrap()
+        .with_clock(clock);
+    let table = common::table("max_ttl");
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", i64::MAX, &table)
+        .await
+        .unwrap();
+    pool.store("b", "{\"b\":1}", now + 10, &table)
+        .await
+        .unwrap();
+
+    let expiry = |id: &'static str| {
+        let pool = &pool;
+        let table = &table;
+        async move {
+            pool.load_with_expiry(id, table)
+                .await
+                .unwrap()
+                .and_then(|(_, expires)| expires)
+                .map(|expires| expires.timestamp())
+        }
+    };
+    assert_eq!(expiry("a").await, Some(now + 60));
+    assert_eq!(expiry("b").await, Some(now + 10));
+}
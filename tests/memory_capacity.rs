@@ -0,0 +1,37 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::MemoryPool;
+
+const TABLE: &str = "capacity";
+
+#[tokio::test]
+async fn the_least_recently_used_session_is_evicted() {
+    let pool = MemoryPool::with_capacity(2);
+    let expires = common::now() + 3600;
+
+    pool.store("a", "{\"a\":1}", expires, TABLE).await.unwrap();
+    pool.store("b", "{\"b\":1}", expires, TABLE).await.unwrap();
+    assert!(pool.load("a", TABLE).await.unwrap().is_some());
+
+    pool.store("c", "{\"c\":1}", expires, TABLE).await.unwrap();
+    assert_eq!(pool.load("b", TABLE).await.unwrap(), None);
+    assert!(pool.load("a", TABLE).await.unwrap().is_some());
+    assert!(pool.load("c", TABLE).await.unwrap().is_some());
+    assert_eq!(pool.eviction_count().await, 1);
+}
+
+#[tokio::test]
+async fn a_capacity_of_zero_holds_nothing() {
+    let pool = MemoryPool::with_capacity(0);
+    let expires = common::now() + 3600;
+
+    pool.store("a", "{\"a\":1}", expires, TABLE).await.unwrap();
+    pool.store("b", "{\"b\":1}", expires, TABLE).await.unwrap();
+
+    assert_eq!(pool.load("a", TABLE).await.unwrap(), None);
+    assert!(!pool.exists("b", TABLE).await.unwrap());
+    assert_eq!(pool.count(TABLE).await.unwrap(), 0);
+    assert!(pool.get_ids(TABLE).await.unwrap().is_empty());
+    assert_eq!(pool.eviction_count().await, 2);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{MemoryPool, MockClock};
+
+const TABLE: &str = "memory";
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+#[tokio::test]
+async fn load_skips_an_expired_session() {
+    let pool = MemoryPool::new();
+
+    pool.store("a", "{\"a\":1}", common::now() - 60, TABLE)
+        .await
+        .unwrap();
+    assert_eq!(pool.load("a", TABLE).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn load_evicts_an_expired_session() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new().with_clock(clock.clone());
+
+    pool.store("a", "{\"a\":1}", now + 10, TABLE).await.unwrap();
+    clock.advance(chrono::Duration::seconds(10));
+    assert_eq!(pool.load("a", TABLE).await.unwrap(), None);
+
+    //gone, not just hidden, so turning the clock back does not revive it
+    clock.advance(chrono::Duration::seconds(-10));
+    assert_eq!(pool.load("a", TABLE).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn exists_is_false_for_an_expired_session() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new().with_clock(clock);
+
+    pool.store("expired", "{\"a\":1}", now - 1, TABLE)
+        .await
+        .unwrap();
+    pool.store("live", "{\"a\":1}", now + 1, TABLE)
+        .await
+        .unwrap();
+
+    assert!(!pool.exists("expired", TABLE).await.unwrap());
+    assert!(pool.exists("live", TABLE).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_stores_and_loads() {
+    let pool = MemoryPool::new();
+    let expires = common::now() + 3600;
+
+    let tasks: Vec<_> = (0..64)
+        .map(|i| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let id = format!("id{i}");
+                let session = format!("{{\"n\":{i}}}");
+
+                pool.store(&id, &session, expires, TABLE).await.unwrap();
+                assert_eq!(pool.load(&id, TABLE).await.unwrap(), Some(session));
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(pool.count(TABLE).await.unwrap(), 64);
+}
+
+//an expiry second per session, once all are gone no bucket may be left, not even an empty one
+#[tokio::test]
+async fn deleting_leaves_no_empty_expiry_buckets() {
+    const SESSIONS: i64 = 10_000;
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new().with_clock(clock);
+    let horizon = chrono::Duration::seconds(SESSIONS + 1);
+    let histogram = || pool.expiry_histogram(chrono::Duration::seconds(1), horizon, TABLE);
+
+    for n in 1..=SESSIONS {
+        pool.store(&format!("id{n}"), "{\"a\":1}", now + n, TABLE)
+            .await
+            .unwrap();
+    }
+    assert_eq!(histogram().await.unwrap().buckets.len(), SESSIONS as usize);
+
+    for n in 1..=SESSIONS {
+        pool.delete_one_by_id(&format!("id{n}"), TABLE)
+            .await
+            .unwrap();
+    }
+    assert_eq!(histogram().await.unwrap().buckets.len(), 0);
+}
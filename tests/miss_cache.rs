@@ -0,0 +1,114 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+
+const TTL: Duration = Duration::from_millis(200);
+
+async fn pools() -> (DbPool, DbPool, String) {
+    let db = common::sqlite().await;
+    let cached = DbPool::new(db.clone()).with_miss_cache(100, TTL);
+    let other = DbPool::new(db);
+    let table = common::table("misses");
+
+    cached.initiate(&table).await.unwrap();
+    (cached, other, table)
+}
+
+#[tokio::test]
+async fn a_miss_is_remembered_until_the_ttl_is_over() {
+    let (cached, other, table) = pools().await;
+
+    assert_eq!(cached.load("a", &table).await.unwrap(), None);
+
+    //stored by another instance, hidden by the remembered miss
+    other
+        .store("a", "{\"a\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+    assert_eq!(cached.load("a", &table).await.unwrap(), None);
+    assert!(!cached.exists("a", &table).await.unwrap());
+
+    tokio::time::sleep(TTL).await;
+    assert!(cached.exists("a", &table).await.unwrap());
+    assert!(cached.load("a", &table).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn a_store_forgets_the_miss() {
+    let (cached, _, table) = pools().await;
+
+    assert_eq!(cached.load("a", &table).await.unwrap(), None);
+    assert!(!cached.exists("b", &table).await.unwrap());
+
+    cached
+        .store("a", "{\"a\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+    cached
+        .store("b", "{\"b\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        cached.load("a", &table).await.unwrap().as_deref(),
+        Some("{\"a\":1}")
+    );
+    assert!(cached.exists("b", &table).await.unwrap());
+}
+
+#[tokio::test]
+async fn a_delete_forgets_the_miss() {
+    let (cached, other, table) = pools().await;
+
+    assert_eq!(cached.load("a", &table).await.unwrap(), None);
+    cached.delete_one_by_id("a", &table).await.unwrap();
+
+    other
+        .store("a", "{\"a\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+    assert!(cached.load("a", &table).await.unwrap().is_some());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_load_racing_a_store_does_not_hide_the_session() {
+    let (cached, _, table) = pools().await;
+    let cached = std::sync::Arc::new(cached.with_miss_cache(1000, Duration::from_secs(3600)));
+
+    //each load may run before, during or after its store, only a miss cached after the store
+    //landed would hide the session below
+    let mut tasks = Vec::new();
+    for i in 0..200 {
+        let id = format!("id{i}");
+        let (storing, loading) = (cached.clone(), cached.clone());
+        let (store_table, load_table) = (table.clone(), table.clone());
+        let store_id = id.clone();
+
+        tasks.push(tokio::spawn(async move {
+            storing
+                .store(&store_id, "{\"a\":1}", common::now() + 3600, &store_table)
+                .await
+                .unwrap();
+        }));
+        tasks.push(tokio::spawn(async move {
+            loading.load(&id, &load_table).await.unwrap();
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    for i in 0..200 {
+        assert!(
+            cached
+                .load(&format!("id{i}"), &table)
+                .await
+                .unwrap()
+                .is_some(),
+            "id{i} is hidden by a stale miss"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "test-utils")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{MockCallCounts, MockPool};
+
+#[tokio::test]
+async fn calls_are_counted() {
+    let mock = MockPool::new();
+
+    mock.initiate("sessions").await.unwrap();
+    mock.store("a", "{\"a\":1}", 0, "sessions").await.unwrap();
+    mock.store("b", "{\"b\":1}", 0, "sessions").await.unwrap();
+    mock.load("a", "sessions").await.unwrap();
+    mock.exists("a", "sessions").await.unwrap();
+    mock.delete_one_by_id("a", "sessions").await.unwrap();
+
+    assert_eq!(
+        mock.call_counts(),
+        MockCallCounts {
+            initiate: 1,
+            store: 2,
+            load: 1,
+            exists: 1,
+            delete_one_by_id: 1,
+            ..MockCallCounts::default()
+        }
+    );
+    assert_eq!(mock.session_count(), 1);
+    assert_eq!(mock.last_stored_id().as_deref(), Some("b"));
+}
+
+//the expiry given to store() is not looked at
+#[tokio::test]
+async fn nothing_expires() {
+    let mock = MockPool::new();
+
+    mock.store("a", "{\"a\":1}", common::now() - 3600, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        mock.load("a", "sessions").await.unwrap().as_deref(),
+        Some("{\"a\":1}")
+    );
+    assert!(mock.delete_by_expiry("sessions").await.unwrap().is_empty());
+    assert_eq!(mock.count("sessions").await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn delete_all_empties_it() {
+    let mock = MockPool::new();
+
+    mock.store("a", "{\"a\":1}", 0, "sessions").await.unwrap();
+    mock.delete_all("sessions").await.unwrap();
+
+    assert_eq!(mock.session_count(), 0);
+    assert_eq!(
+        mock.get_ids("sessions").await.unwrap(),
+        Vec::<String>::new()
+    );
+    assert_eq!(mock.last_stored_id().as_deref(), Some("a"));
+}
+
+//clones share the sessions and the counts, e.g. the one handed to the session layer and the
+//one the test keeps
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn clones_share_state_across_threads() {
+    let mock = MockPool::new();
+
+    let tasks: Vec<_> = (0..16)
+        .map(|n| {
+            let mock = mock.clone();
+            tokio::spawn(async move {
+                mock.store(&format!("id{n}"), "{\"a\":1}", 0, "sessions")
+                    .await
+                    .unwrap();
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(mock.session_count(), 16);
+    assert_eq!(mock.call_counts().store, 16);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+
+async fn expiry(pool: &DbPool, id: &str, table: &str) -> Option<i64> {
+    let (_, expires) = pool.load_with_expiry(id, table).await.unwrap().unwrap();
+    expires.map(|expires| expires.timestamp())
+}
+
+#[tokio::test]
+async fn an_older_expiry_does_not_shorten_the_session() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_monotonic_expiry(true);
+        let table = common::table("monotonic");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"tab\":1}", now + 120, &table)
+            .await
+            .unwrap();
+        pool.store("a", "{\"tab\":2}", now + 60, &table)
+            .await
+            .unwrap();
+
+        assert_eq!(expiry(&pool, "a", &table).await, Some(now + 120));
+        assert_eq!(
+            pool.load("a", &table).await.unwrap().as_deref(),
+            Some("{\"tab\":2}")
+        );
+
+        pool.store("a", "{\"tab\":3}", now + 180, &table)
+            .await
+            .unwrap();
+        assert_eq!(expiry(&pool, "a", &table).await, Some(now + 180));
+    }
+}
+
+#[tokio::test]
+async fn never_expiring_beats_any_timestamp() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_monotonic_expiry(true);
+        let table = common::table("monotonic");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", i64::MAX, &table)
+            .await
+            .unwrap();
+        pool.store("a", "{\"a\":2}", now + 60, &table)
+            .await
+            .unwrap();
+        assert_eq!(expiry(&pool, "a", &table).await, None);
+
+        pool.store("b", "{\"b\":1}", now + 60, &table)
+            .await
+            .unwrap();
+        pool.store("b", "{\"b\":2}", i64::MAX, &table)
+            .await
+            .unwrap();
+        assert_eq!(expiry(&pool, "b", &table).await, None);
+    }
+}
+
+#[tokio::test]
+async fn without_it_the_last_store_wins() {
+    let pool = DbPool::new(common::sqlite().await);
+    let now = common::now();
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 120, "sessions")
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":2}", now + 60, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(expiry(&pool, "a", "sessions").await, Some(now + 60));
+}
+
+//many tabs renewing at once, in whatever order they land the latest expiry sticks
+#[tokio::test]
+async fn racing_renewals_keep_the_latest_expiry() {
+    for db in common::databases().await {
+        let pool = Arc::new(DbPool::new(db).with_monotonic_expiry(true));
+        let table = common::table("monotonic");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        let renewals: Vec<_> = (1..=20)
+            .rev()
+            .map(|n| {
+                let pool = pool.clone();
+                let table = table.clone();
+                tokio::spawn(async move {
+                    pool.store("a", "{\"a\":1}", now + n * 60, &table)
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for renewal in renewals {
+            renewal.await.unwrap();
+        }
+
+        assert_eq!(expiry(&pool, "a", &table).await, Some(now + 20 * 60));
+    }
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "mysql-tests")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::{DateTime, Duration};
+use dxp_axum_session::{DbPool, MockClock};
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, Statement};
+
+//every test is ignored, run them with --ignored and MYSQL_URL set, e.g.
+//mysql://root@localhost/sessions. without it they fail instead of passing without having run
+fn mysql_url() -> String {
+    std::env::var("MYSQL_URL").expect("MYSQL_URL is set")
+}
+
+async fn mysql() -> DatabaseConnection {
+    Database::connect(mysql_url())
+        .await
+        .expect("mysql connects")
+}
+
+#[tokio::test]
+#[ignore = "needs MYSQL_URL"]
+async fn a_second_store_replaces_payload_and_expiry() {
+    let db = mysql().await;
+    let pool = DbPool::new(db);
+    let table = common::table("mysql");
+    let now = common::now();
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 60, &table)
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":2}", now + 120, &table)
+        .await
+        .unwrap();
+
+    let (session, expires) = pool.load_with_expiry("a", &table).await.unwrap().unwrap();
+    assert_eq!(session, "{\"a\":2}");
+    assert_eq!(expires.map(|expires| expires.timestamp()), Some(now + 120));
+    assert_eq!(pool.count_total(&table).await.unwrap(), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs MYSQL_URL"]
+async fn the_upsert_keeps_the_later_expiry() {
+    let db = mysql().await;
+    let pool = DbPool::new(db).with_monotonic_expiry(true);
+    let table = common::table("mysql");
+    let now = common::now();
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 120, &table)
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":2}", now + 60, &table)
+        .await
+        .unwrap();
+
+    let (session, expires) = pool.load_with_expiry("a", &table).await.unwrap().unwrap();
+    assert_eq!(session, "{\"a\":2}");
+    assert_eq!(expires.map(|expires| expires.timestamp()), Some(now + 120));
+}
+
+//TIMESTAMP would end at 2038-01-19
+#[tokio::test]
+#[ignore = "needs MYSQL_URL"]
+async fn initiate_creates_the_timestamps_as_datetime() {
+    let db = mysql().await;
+    let table = common::table("mysql");
+
+    DbPool::new(db.clone()).initiate(&table).await.unwrap();
+
+    let rows = db
+        .query_all(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT data_type AS data_type FROM information_schema.columns \
+             WHERE table_schema = DATABASE() AND table_name = ? \
+             AND column_name IN ('expires', 'created_at', 'updated_at')",
+            [table.as_str().into()],
+        ))
+        .await
+        .unwrap();
+    let types: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            row.try_get::<String>("", "data_type")
+                .unwrap()
+                .to_lowercase()
+        })
+        .collect();
+    assert_eq!(types, ["datetime"; 3]);
+}
+
+//past the end of TIMESTAMP, well within the 50 years before an expiry is stored as NULL
+#[tokio::test]
+#[ignore = "needs MYSQL_URL"]
+async fn an_expiry_in_2040_round_trips() {
+    let db = mysql().await;
+    let pool = DbPool::new(db);
+    let table = common::table("mysql");
+    //2040-06-01 00:00:00 UTC
+    let expires = 2_222_121_600;
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+
+    let (session, stored) = pool.load_with_expiry("a", &table).await.unwrap().unwrap();
+    assert_eq!(session, "{\"a\":1}");
+    assert_eq!(stored.map(|stored| stored.timestamp()), Some(expires));
+}
+
+#[tokio::test]
+#[ignore = "needs MYSQL_URL"]
+async fn initiate_refuses_a_timestamp_expires_column() {
+    let db = mysql().await;
+    let table = common::table("mysql");
+    db.execute_unprepared(&format!(
+        "CREATE TABLE {table} (id VARCHAR(128) NOT NULL PRIMARY KEY, \
+         expires TIMESTAMP NULL, session TEXT NOT NULL)"
+    ))
+    .await
+    .unwrap();
+
+    let err = DbPool::new(db).initiate(&table).await.unwrap_err();
+    assert!(
+        format!("{err:?}").contains("TIMESTAMP ends at 2038"),
+        "{err:?}"
+    );
+}
+
+//a DATETIME is neither converted on the way in nor out, the connection's time_zone is not UTC
+#[tokio::test]
+#[ignore = "needs MYSQL_URL"]
+async fn a_one_hour_session_lives_a_full_hour_off_utc() {
+    let mut options = ConnectOptions::new(mysql_url());
+    options.map_sqlx_mysql_opts(|options| options.timezone(Some("+05:00".to_owned())));
+
+    let now = common::now();
+    let clock = MockClock::new(DateTime::from_timestamp(now, 0).unwrap());
+    let pool = DbPool::new(Database::connect(options).await.unwrap()).with_clock(clock.clone());
+    let table = common::table("mysql");
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 3600, &table)
+        .await
+        .unwrap();
+
+    let histogram = pool
+        .expiry_histogram(Duration::hours(2), Duration::hours(2), &table)
+        .await
+        .unwrap();
+    assert_eq!(
+        histogram.buckets,
+        [(DateTime::from_timestamp(now, 0).unwrap(), 1)]
+    );
+
+    let mut loaded = Vec::new();
+    for advance in [0, 3599, 1] {
+        clock.advance(Duration::seconds(advance));
+        loaded.push(pool.load("a", &table).await.unwrap().is_some());
+    }
+    assert_eq!(loaded, [true, true, false]);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::{DateTime, Duration};
+use dxp_axum_session::{DbPool, MockClock};
+
+const YEAR: i64 = 365 * 24 * 3600;
+
+#[tokio::test]
+async fn a_sentinel_expiry_loads_forever_and_is_never_swept() {
+    for db in common::databases().await {
+        let now = common::now();
+        let clock = MockClock::new(DateTime::from_timestamp(now, 0).unwrap());
+        let pool = DbPool::new(db).with_clock(clock.clone());
+        let table = common::table("never_expires");
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("sentinel", "{\"a\":1}", now + 60 * YEAR, &table)
+            .await
+            .unwrap();
+
+        //stored as NULL rather than the far-future timestamp
+        let (_, expires) = pool
+            .load_with_expiry("sentinel", &table)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(expires, None);
+
+        //the time the sentinel stood for has long passed
+        clock.advance(Duration::days(100 * 365));
+        assert_eq!(
+            pool.delete_by_expiry(&table).await.unwrap(),
+            Vec::<String>::new()
+        );
+        assert!(pool.exists("sentinel", &table).await.unwrap());
+        assert_eq!(pool.get_ids(&table).await.unwrap(), ["sentinel"]);
+        assert_eq!(pool.count(&table).await.unwrap(), 1);
+        assert_eq!(
+            pool.load("sentinel", &table).await.unwrap().as_deref(),
+            Some("{\"a\":1}")
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_threshold_is_configurable() {
+    let now = common::now();
+    let clock = MockClock::new(DateTime::from_timestamp(now, 0).unwrap());
+    let pool = DbPool::new(common::sqlite().await)
+        .with_never_expires_after(Duration::days(1))
+        .with_clock(clock);
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("hour", "{\"a\":1}", now + 3600, "sessions")
+        .await
+        .unwrap();
+    pool.store("days", "{\"a\":1}", now + 2 * 24 * 3600, "sessions")
+        .await
+        .unwrap();
+
+    let expiry = |id: &'static str| {
+        let pool = &pool;
+        async move {
+            let (_, expires) = pool
+                .load_with_expiry(id, "sessions")
+                .await
+                .unwrap()
+                .unwrap();
+            expires.map(|expires| expires.timestamp())
+        }
+    };
+    assert_eq!(expiry("hour").await, Some(now + 3600));
+    assert_eq!(expiry("days").await, None);
+}
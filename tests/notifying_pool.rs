@@ -0,0 +1,94 @@
+#![cfg(feature = "pg_notify")]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool, NotifyingDbPool};
+
+const INTERVAL: Duration = Duration::from_secs(3600);
+
+//the table the cache is filled for, the listener evicts by id whatever the table
+const CACHED: &str = "cached";
+
+async fn cached(cache: &MemoryPool, id: &str) -> bool {
+    cache.load(id, CACHED).await.unwrap().is_some()
+}
+
+//the listener runs in a task of its own, give it time to catch up
+async fn evicted(cache: &MemoryPool, id: &str) -> bool {
+    for _ in 0..200 {
+        if !cached(cache, id).await {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn a_queued_store_is_announced_once_written() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let table = common::table("notifying");
+    let expires = common::now() + 3600;
+    let queued = DbPool::new(db.clone()).with_write_behind(INTERVAL, 100, 100);
+    let pool = NotifyingDbPool::new(queued.clone());
+    let plain = DbPool::new(db);
+
+    let cache = Arc::new(MemoryPool::new());
+    let listener = pool.subscribe(cache.clone()).await.unwrap();
+
+    pool.initiate(&table).await.unwrap();
+    cache
+        .store("a", "{\"a\":0}", expires, CACHED)
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+
+    //still queued, evicted now the cache would load the old row again
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(cached(&cache, "a").await);
+
+    //a clone of the DbPool flushes the same queue
+    queued.flush().await.unwrap();
+    assert!(evicted(&cache, "a").await);
+    assert_eq!(
+        plain.load("a", &table).await.unwrap().as_deref(),
+        Some("{\"a\":1}")
+    );
+
+    listener.abort();
+}
+
+#[tokio::test]
+async fn delete_all_clears_the_cache() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let table = common::table("notifying");
+    let expires = common::now() + 3600;
+    let pool = NotifyingDbPool::new(DbPool::new(db));
+
+    let cache = Arc::new(MemoryPool::new());
+    let listener = pool.subscribe(cache.clone()).await.unwrap();
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+    cache
+        .store("a", "{\"a\":1}", expires, CACHED)
+        .await
+        .unwrap();
+    cache
+        .store("b", "{\"b\":1}", expires, CACHED)
+        .await
+        .unwrap();
+
+    pool.delete_all(&table).await.unwrap();
+    assert!(evicted(&cache, "a").await);
+    assert!(evicted(&cache, "b").await);
+
+    listener.abort();
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use axum_session::{DatabaseError, DatabasePool};
+use dxp_axum_session::{DbPool, MemoryPool};
+
+//a NUL, what an escape must not mix up with it, and a payload starting with the escape marker
+const PAYLOADS: [&str; 4] = [
+    "{\"token\":\"a\0b\"}",
+    "{\"path\":\"C:\\\\0\\\\x\",\"nul\":\"\0\"}",
+    "\u{1b}nul:looks escaped",
+    "{\"a\":1}",
+];
+
+#[tokio::test]
+async fn postgres_refuses_a_nul_byte() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let pool = DbPool::new(db);
+    let table = common::table("nul");
+
+    pool.initiate(&table).await.unwrap();
+    let result = pool
+        .store("a", PAYLOADS[0], common::now() + 60, &table)
+        .await;
+
+    let Err(DatabaseError::GenericInsertError(message)) = result else {
+        panic!("{result:?}");
+    };
+    assert!(
+        message.contains("session payload contains a NUL byte"),
+        "{message}"
+    );
+}
+
+#[tokio::test]
+async fn escaped_payloads_round_trip() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_nul_escape(true);
+        let table = common::table("nul");
+
+        pool.initiate(&table).await.unwrap();
+        for (n, payload) in PAYLOADS.into_iter().enumerate() {
+            let id = format!("id{n}");
+            pool.store(&id, payload, common::now() + 60, &table)
+                .await
+                .unwrap();
+            assert_eq!(
+                pool.load(&id, &table).await.unwrap().as_deref(),
+                Some(payload)
+            );
+        }
+    }
+}
+
+//SQLite TEXT holds NUL bytes, nothing is escaped there without the option
+#[tokio::test]
+async fn sqlite_stores_a_nul_byte_as_it_is() {
+    let pool = DbPool::new(common::sqlite().await);
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", PAYLOADS[0], common::now() + 60, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pool.load("a", "sessions").await.unwrap().as_deref(),
+        Some(PAYLOADS[0])
+    );
+}
+
+#[tokio::test]
+async fn memory_pool_keeps_payloads_unchanged() {
+    let pool = MemoryPool::new();
+
+    for (n, payload) in PAYLOADS.into_iter().enumerate() {
+        let id = format!("id{n}");
+        pool.store(&id, payload, common::now() + 60, "sessions")
+            .await
+            .unwrap();
+        assert_eq!(
+            pool.load(&id, "sessions").await.unwrap().as_deref(),
+            Some(payload)
+        );
+    }
+}
@@ -0,0 +1,212 @@
+#![cfg(feature = "postgres")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use chrono::{Duration, Utc};
+use dxp_axum_session::{DbPool, MockClock};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+//(id, partition) of every row, in id order
+async fn rows(db: &DatabaseConnection, table: &str) -> Vec<(String, String)> {
+    let rows = db
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                r#"SELECT tableoid::regclass::text AS partition, id FROM "{table}" ORDER BY id"#
+            ),
+        ))
+        .await
+        .unwrap();
+
+    rows.iter()
+        .map(|row| {
+            let partition: String = row.try_get("", "partition").unwrap();
+            (
+                row.try_get("", "id").unwrap(),
+                partition.trim_matches('"').to_owned(),
+            )
+        })
+        .collect()
+}
+
+async fn partitions(db: &DatabaseConnection, table: &str) -> Vec<String> {
+    let rows = db
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                r#"SELECT c.relname::text AS name FROM pg_inherits i JOIN pg_class c ON c.oid = i.inhrelid
+                   WHERE i.inhparent = '"{table}"'::regclass ORDER BY 1"#
+            ),
+        ))
+        .await
+        .unwrap();
+
+    rows.iter()
+        .map(|row| row.try_get("", "name").unwrap())
+        .collect()
+}
+
+fn day(table: &str, days: i64) -> String {
+    format!(
+        "{table}_p{}",
+        (Utc::now() + Duration::days(days)).format("%Y%m%d")
+    )
+}
+
+async fn partitioned_table(epoch: bool) {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let table = common::table("partitioned");
+    let now = Utc::now();
+    let at = |offset: Duration| (now + offset).timestamp();
+
+    //three days back, so there are partitions of past days to drop later
+    let clock = MockClock::new(now - Duration::days(3));
+    let pool = DbPool::new(db.clone())
+        .with_partitioning(true)
+        .with_epoch_expires(epoch)
+        .with_clock(clock.clone());
+    pool.initiate(&table).await.unwrap();
+    pool.initiate(&table).await.unwrap();
+    assert_eq!(
+        partitions(&db, &table).await,
+        vec![format!("{table}_default")]
+    );
+
+    //stored before their partitions exist, they wait in the default one
+    pool.store(
+        "a",
+        "A",
+        at(Duration::minutes(1) - Duration::days(3)),
+        &table,
+    )
+    .await
+    .unwrap();
+    pool.store("b", "B", at(Duration::hours(2)), &table)
+        .await
+        .unwrap();
+    assert!(rows(&db, &table)
+        .await
+        .iter()
+        .all(|(_, partition)| *partition == format!("{table}_default")));
+
+    assert_eq!(pool.ensure_partitions(5, &table).await.unwrap(), 6);
+    assert_eq!(pool.ensure_partitions(5, &table).await.unwrap(), 0);
+    assert_eq!(
+        rows(&db, &table).await,
+        vec![
+            ("a".to_owned(), day(&table, -3)),
+            ("b".to_owned(), day(&table, 0)),
+        ]
+    );
+
+    //loads route to whichever partition holds the row, a new expiry moves it
+    clock.set(now);
+    assert_eq!(pool.load("a", &table).await.unwrap(), None);
+    assert_eq!(pool.load("b", &table).await.unwrap().as_deref(), Some("B"));
+    pool.store(
+        "b",
+        "B2",
+        at(Duration::days(1) + Duration::minutes(1)),
+        &table,
+    )
+    .await
+    .unwrap();
+    pool.store("c", "C", at(Duration::days(30)), &table)
+        .await
+        .unwrap();
+    assert_eq!(
+        rows(&db, &table).await,
+        vec![
+            ("a".to_owned(), day(&table, -3)),
+            ("b".to_owned(), day(&table, 1)),
+            ("c".to_owned(), format!("{table}_default")),
+        ]
+    );
+    assert_eq!(pool.load("b", &table).await.unwrap().as_deref(), Some("B2"));
+    assert_eq!(pool.load("c", &table).await.unwrap().as_deref(), Some("C"));
+    assert_eq!(pool.count(&table).await.unwrap(), 2);
+
+    //the partitions of past days are dropped whole, their ids returned
+    assert_eq!(
+        pool.delete_by_expiry(&table).await.unwrap(),
+        vec!["a".to_owned()]
+    );
+    assert!(!partitions(&db, &table).await.contains(&day(&table, -1)));
+    assert!(partitions(&db, &table).await.contains(&day(&table, 0)));
+    assert_eq!(rows(&db, &table).await.len(), 2);
+}
+
+#[tokio::test]
+async fn partitions_are_created_routed_and_dropped() {
+    partitioned_table(false).await;
+}
+
+#[tokio::test]
+async fn epoch_partitions_are_created_routed_and_dropped() {
+    partitioned_table(true).await;
+}
+
+#[tokio::test]
+async fn ids_stay_unique_without_a_primary_key() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let table = common::table("partitioned_ids");
+    let pool = DbPool::new(db.clone()).with_partitioning(true);
+    pool.initiate(&table).await.unwrap();
+    let expires = common::now() + 3600;
+
+    let stores = (0..20).map(|i| {
+        let (pool, table) = (pool.clone(), table.clone());
+        tokio::spawn(async move { pool.store("same", &format!("{i}"), expires, &table).await })
+    });
+    for store in stores.collect::<Vec<_>>() {
+        store.await.unwrap().unwrap();
+    }
+    assert_eq!(rows(&db, &table).await.len(), 1);
+
+    pool.store("other", "O", expires, &table).await.unwrap();
+    assert!(pool.rotate("same", "other", &table).await.is_err());
+    assert!(pool.rotate("same", "fresh", &table).await.unwrap());
+    assert_eq!(
+        rows(&db, &table)
+            .await
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>(),
+        vec!["fresh".to_owned(), "other".to_owned()]
+    );
+}
+
+#[tokio::test]
+async fn an_unpartitioned_table_is_refused() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let table = common::table("unpartitioned");
+    DbPool::new(db.clone()).initiate(&table).await.unwrap();
+
+    assert!(DbPool::new(db)
+        .with_partitioning(true)
+        .initiate(&table)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn sqlite_ignores_partitioning() {
+    let pool = DbPool::new(common::sqlite().await).with_partitioning(true);
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "A", common::now() + 60, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pool.load("a", "sessions").await.unwrap().as_deref(),
+        Some("A")
+    );
+    assert!(pool.ensure_partitions(3, "sessions").await.is_err());
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "redis_pool")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use deadpool_redis::{Config, Runtime};
+use dxp_axum_session::RedisPool;
+
+//the redis tests only run with REDIS_URL set, e.g. redis://localhost:6379, see docker-compose.yml
+fn redis() -> Option<RedisPool> {
+    let url = std::env::var("REDIS_URL").ok()?;
+    let pool = Config::from_url(url)
+        .create_pool(Some(Runtime::Tokio1))
+        .expect("redis pool is created");
+
+    Some(RedisPool::new(pool))
+}
+
+#[tokio::test]
+async fn sessions_are_stored_and_deleted() {
+    let Some(pool) = redis() else {
+        return;
+    };
+    let table = common::table("redis");
+    let expires = common::now() + 3600;
+
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+    pool.store("b", "{\"b\":1}", i64::MAX, &table)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pool.load("a", &table).await.unwrap().as_deref(),
+        Some("{\"a\":1}")
+    );
+    assert!(pool.exists("b", &table).await.unwrap());
+    assert_eq!(pool.count(&table).await.unwrap(), 2);
+
+    let mut ids = pool.get_ids(&table).await.unwrap();
+    ids.sort();
+    assert_eq!(ids, ["a", "b"]);
+
+    pool.delete_one_by_id("a", &table).await.unwrap();
+    assert_eq!(pool.load("a", &table).await.unwrap(), None);
+    assert_eq!(pool.count(&table).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn an_expired_store_is_not_counted() {
+    let Some(pool) = redis() else {
+        return;
+    };
+    let table = common::table("redis");
+
+    pool.store("a", "{\"a\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":1}", common::now() - 10, &table)
+        .await
+        .unwrap();
+
+    assert_eq!(pool.load("a", &table).await.unwrap(), None);
+    assert_eq!(pool.count(&table).await.unwrap(), 0);
+    assert!(pool.get_ids(&table).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn delete_all_removes_sessions_and_index() {
+    let Some(pool) = redis() else {
+        return;
+    };
+    let table = common::table("redis");
+    let other = common::table("redis");
+    let expires = common::now() + 3600;
+
+    for i in 0..50 {
+        pool.store(&format!("id{i}"), "{\"a\":1}", expires, &table)
+            .await
+            .unwrap();
+    }
+    pool.store("kept", "{\"a\":1}", expires, &other)
+        .await
+        .unwrap();
+
+    pool.delete_all(&table).await.unwrap();
+
+    assert_eq!(pool.count(&table).await.unwrap(), 0);
+    assert!(pool.get_ids(&table).await.unwrap().is_empty());
+    assert_eq!(pool.load("id0", &table).await.unwrap(), None);
+    assert!(!pool.exists("id49", &table).await.unwrap());
+    assert!(pool.exists("kept", &other).await.unwrap());
+
+    //an empty table is fine as well
+    pool.delete_all(&table).await.unwrap();
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{remaining_ttl, DbPool, MemoryPool, MockClock};
+
+#[tokio::test]
+async fn db_pool_load_with_expiry() {
+    for db in common::databases().await {
+        let now = common::now();
+        let pool =
+            DbPool::new(db).with_clock(MockClock::new(DateTime::from_timestamp(now, 0).unwrap()));
+        let table = common::table("remaining_ttl");
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("live", "{\"a\":1}", now + 720, &table)
+            .await
+            .unwrap();
+        pool.store("never", "{\"a\":2}", i64::MAX, &table)
+            .await
+            .unwrap();
+        pool.store("expired", "{\"a\":3}", now - 1, &table)
+            .await
+            .unwrap();
+
+        let (session, expires) = pool
+            .load_with_expiry("live", &table)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session, "{\"a\":1}");
+        assert_eq!(expires.map(|expires| expires.timestamp()), Some(now + 720));
+        assert_eq!(
+            pool.load_with_expiry("never", &table).await.unwrap(),
+            Some(("{\"a\":2}".to_owned(), None))
+        );
+        assert_eq!(
+            pool.load_with_expiry("expired", &table).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            pool.load_with_expiry("unknown", &table).await.unwrap(),
+            None
+        );
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_load_with_expiry() {
+    let now = common::now();
+    let pool =
+        MemoryPool::new().with_clock(MockClock::new(DateTime::from_timestamp(now, 0).unwrap()));
+
+    pool.store("live", "{\"a\":1}", now + 720, "sessions")
+        .await
+        .unwrap();
+    pool.store("never", "{\"a\":2}", i64::MAX, "sessions")
+        .await
+        .unwrap();
+    pool.store("expired", "{\"a\":3}", now - 1, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pool.load_with_expiry("live", "sessions").await.unwrap(),
+        Some((
+            "{\"a\":1}".to_owned(),
+            DateTime::from_timestamp(now + 720, 0)
+        ))
+    );
+    assert_eq!(
+        pool.load_with_expiry("never", "sessions").await.unwrap(),
+        Some(("{\"a\":2}".to_owned(), None))
+    );
+    assert_eq!(
+        pool.load_with_expiry("expired", "sessions").await.unwrap(),
+        None
+    );
+}
+
+#[test]
+fn remaining_ttl_counts_down_to_zero() {
+    let now = DateTime::from_timestamp(common::now(), 0).unwrap();
+
+    //"your session expires in 12 minutes"
+    assert_eq!(
+        remaining_ttl(Some(now + chrono::Duration::minutes(12)), now),
+        Some(Duration::from_secs(720))
+    );
+    assert_eq!(remaining_ttl(Some(now), now), Some(Duration::ZERO));
+    assert_eq!(
+        remaining_ttl(Some(now - chrono::Duration::minutes(1)), now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(remaining_ttl(None, now), None);
+}
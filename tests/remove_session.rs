@@ -0,0 +1,47 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool};
+
+#[tokio::test]
+async fn db_pool_reports_what_it_removed() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("remove_session");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("live", "{\"a\":1}", now + 60, &table)
+            .await
+            .unwrap();
+        pool.store("expired", "{\"a\":2}", now - 60, &table)
+            .await
+            .unwrap();
+
+        assert!(pool.remove_session("live", &table).await.unwrap());
+        assert!(!pool.remove_session("live", &table).await.unwrap());
+        assert!(!pool.remove_session("missing", &table).await.unwrap());
+        //not swept yet, so the row was still there
+        assert!(pool.remove_session("expired", &table).await.unwrap());
+        assert_eq!(pool.count_total(&table).await.unwrap(), 0);
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_reports_what_it_removed() {
+    let pool = MemoryPool::new();
+    let now = common::now();
+
+    pool.store("live", "{\"a\":1}", now + 60, "sessions")
+        .await
+        .unwrap();
+    pool.store("expired", "{\"a\":2}", now - 60, "sessions")
+        .await
+        .unwrap();
+
+    assert!(pool.remove_session("live", "sessions").await.unwrap());
+    assert!(!pool.remove_session("live", "sessions").await.unwrap());
+    assert!(!pool.remove_session("missing", "sessions").await.unwrap());
+    assert!(pool.remove_session("expired", "sessions").await.unwrap());
+    assert_eq!(pool.count("sessions").await.unwrap(), 0);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+
+//two separate databases, so what a read sees tells which one it went to
+#[tokio::test]
+async fn reads_go_to_the_replica_and_writes_to_the_primary() {
+    let primary = common::sqlite().await;
+    let replica = common::sqlite().await;
+    let expires = common::now() + 60;
+
+    let on_primary = DbPool::new(primary.clone());
+    let on_replica = DbPool::new(replica.clone());
+    on_replica.initiate("sessions").await.unwrap();
+    on_replica
+        .store("replicated", "{\"r\":1}", expires, "sessions")
+        .await
+        .unwrap();
+
+    let pool = DbPool::with_replica(primary, replica);
+    pool.initiate("sessions").await.unwrap();
+    pool.store("written", "{\"w\":1}", expires, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        on_primary
+            .load("written", "sessions")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("{\"w\":1}")
+    );
+    assert_eq!(on_replica.load("written", "sessions").await.unwrap(), None);
+
+    assert_eq!(
+        pool.load("replicated", "sessions")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("{\"r\":1}")
+    );
+    assert_eq!(pool.load("written", "sessions").await.unwrap(), None);
+    assert!(pool.exists("replicated", "sessions").await.unwrap());
+    assert!(!pool.exists("written", "sessions").await.unwrap());
+    assert_eq!(pool.get_ids("sessions").await.unwrap(), ["replicated"]);
+    assert_eq!(pool.count("sessions").await.unwrap(), 1);
+
+    pool.delete_one_by_id("replicated", "sessions")
+        .await
+        .unwrap();
+    pool.delete_all("sessions").await.unwrap();
+    assert!(on_replica.exists("replicated", "sessions").await.unwrap());
+    assert_eq!(on_primary.count_total("sessions").await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn without_a_replica_reads_see_the_writes() {
+    let pool = DbPool::new(common::sqlite().await);
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", common::now() + 60, "sessions")
+        .await
+        .unwrap();
+
+    assert!(pool.exists("a", "sessions").await.unwrap());
+    assert_eq!(pool.get_ids("sessions").await.unwrap(), ["a"]);
+}
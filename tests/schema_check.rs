@@ -0,0 +1,89 @@
+mod common;
+
+use axum_session::{DatabaseError, DatabasePool};
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+
+#[tokio::test]
+async fn every_mismatched_column_is_named() {
+    for db in common::databases().await {
+        let table = common::table("schema_check");
+        db.execute_unprepared(&format!(
+            "CREATE TABLE {table} (id VARCHAR(128) PRIMARY KEY, expires BIGINT, \
+             session VARCHAR(255) NOT NULL)"
+        ))
+        .await
+        .unwrap();
+
+        let err = DbPool::new(db).initiate(&table).await.unwrap_err();
+        let DatabaseError::GenericCreateError(message) = err else {
+            panic!("{err:?}");
+        };
+        assert!(
+            message.contains(&format!("table {table} does not have the expected schema")),
+            "{message}"
+        );
+        assert!(
+            message.contains(
+                "column expires is bigint, expected a timestamp \
+                 (use with_epoch_expires(true) for unix seconds)"
+            ),
+            "{message}"
+        );
+        //varchar on SQLite, character varying on Postgres
+        assert!(message.contains("column session is "), "{message}");
+        assert!(message.contains(", expected TEXT"), "{message}");
+        assert!(!message.contains("column id "), "{message}");
+    }
+}
+
+#[tokio::test]
+async fn a_missing_column_is_named() {
+    let db = common::sqlite().await;
+    db.execute_unprepared("CREATE TABLE sessions (id VARCHAR(128) PRIMARY KEY, session TEXT)")
+        .await
+        .unwrap();
+
+    let err = DbPool::new(db).initiate("sessions").await.unwrap_err();
+    assert!(
+        format!("{err:?}").contains("column expires is missing, expected a timestamp"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn the_check_can_be_turned_off() {
+    let db = common::sqlite().await;
+    db.execute_unprepared(
+        "CREATE TABLE sessions (id VARCHAR(128) PRIMARY KEY, expires BIGINT, \
+         session VARCHAR(255) NOT NULL)",
+    )
+    .await
+    .unwrap();
+
+    DbPool::new(db)
+        .with_schema_check(false)
+        .initiate("sessions")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn the_table_initiate_creates_passes() {
+    for db in common::databases().await {
+        let table = common::table("schema_check");
+
+        DbPool::new(db.clone()).initiate(&table).await.unwrap();
+        DbPool::new(db).initiate(&table).await.unwrap();
+    }
+}
+
+//default() starts with the options of new(), the schema check included
+#[test]
+fn default_checks_the_schema_like_new() {
+    let default = format!("{:?}", DbPool::default());
+    let new = format!("{:?}", DbPool::new(DatabaseConnection::default()));
+
+    assert!(default.contains("schema_check: true"), "{default}");
+    assert_eq!(default, new);
+}
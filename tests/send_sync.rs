@@ -0,0 +1,36 @@
+//compiles only while the pools and the futures of their methods can be used in axum handlers,
+//nothing is run
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn pools_are_send_and_sync() {
+    assert_send_sync::<DbPool>();
+    assert_send_sync::<MemoryPool>();
+    #[cfg(feature = "cached_pool")]
+    assert_send_sync::<dxp_axum_session::CachedDbPool>();
+    #[cfg(feature = "encrypted_pool")]
+    assert_send_sync::<dxp_axum_session::EncryptedPool<MemoryPool>>();
+    #[cfg(feature = "signed_pool")]
+    assert_send_sync::<dxp_axum_session::SignedPool<MemoryPool>>();
+    #[cfg(feature = "test-utils")]
+    assert_send_sync::<dxp_axum_session::MockPool>();
+}
+
+//a handler's future is only Send if every future it awaits is
+const _: fn(&DbPool, &MemoryPool) = |db, memory| {
+    assert_send(&db.load("a", "sessions"));
+    assert_send(&db.load_with_expiry("a", "sessions"));
+    assert_send(&db.load_many(&["a"], "sessions"));
+    assert_send(&db.store_many(&[("a", "{}", 0)], "sessions"));
+    assert_send(&db.remove_session("a", "sessions"));
+    assert_send(&db.stream_ids("sessions"));
+    assert_send(&memory.load("a", "sessions"));
+    assert_send(&memory.load_with_expiry("a", "sessions"));
+    assert_send(&memory.remove_session("a", "sessions"));
+};
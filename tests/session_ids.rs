@@ -0,0 +1,112 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool, SessionId, SessionIdError};
+
+#[tokio::test]
+async fn ids_longer_than_the_column_are_rejected() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("ids");
+        let (fits, too_long) = ("a".repeat(128), "a".repeat(129));
+
+        pool.initiate(&table).await.unwrap();
+        pool.store(&fits, "{\"a\":1}", common::now() + 60, &table)
+            .await
+            .unwrap();
+        assert!(pool.exists(&fits, &table).await.unwrap());
+
+        let err = pool
+            .store(&too_long, "{\"a\":1}", common::now() + 60, &table)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("session id is 129 bytes long, at most 128 are allowed"),
+            "{err}"
+        );
+        assert!(pool.load(&too_long, &table).await.is_err());
+        assert!(pool.exists(&too_long, &table).await.is_err());
+        assert!(pool.delete_one_by_id(&too_long, &table).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn a_wider_column_takes_longer_ids() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_id_length(200);
+        let table = common::table("ids");
+        let id = "a".repeat(200);
+
+        pool.initiate(&table).await.unwrap();
+        pool.store(&id, "{\"a\":1}", common::now() + 60, &table)
+            .await
+            .unwrap();
+        assert_eq!(pool.get_ids(&table).await.unwrap(), [id]);
+        assert!(pool
+            .store(&"a".repeat(201), "{\"a\":1}", common::now() + 60, &table)
+            .await
+            .is_err());
+    }
+}
+
+#[test]
+fn parse_checks_length_and_characters() {
+    let fits = "a".repeat(128);
+    assert_eq!(SessionId::parse(&fits).unwrap().as_str(), fits);
+    assert_eq!(
+        SessionId::parse(&"a".repeat(129)),
+        Err(SessionIdError::TooLong {
+            length: 129,
+            max_length: 128
+        })
+    );
+    assert_eq!(SessionId::parse(""), Err(SessionIdError::Empty));
+    assert_eq!(
+        SessionId::parse("a\0b"),
+        Err(SessionIdError::InvalidCharacter('\0'))
+    );
+    assert_eq!(
+        SessionId::parse("a'; DROP TABLE sessions; --"),
+        Err(SessionIdError::InvalidCharacter('\''))
+    );
+    //ASCII only
+    assert_eq!(
+        SessionId::parse("é"),
+        Err(SessionIdError::InvalidCharacter('é'))
+    );
+    assert!(SessionId::parse("0b7c6d1e-58f2-4c5e-9c3b-1f0e2d3c4b5a").is_ok());
+    assert!(SessionId::parse("under_score").is_ok());
+}
+
+//every DatabasePool method taking an id refuses an invalid one before touching the store
+async fn check_rejected(pool: &impl DatabasePool, table: &str) {
+    let too_long = "a".repeat(129);
+
+    for id in ["", "a\0b", "a'b", too_long.as_str()] {
+        assert!(pool
+            .store(id, "{\"a\":1}", common::now() + 60, table)
+            .await
+            .is_err());
+        assert!(pool.load(id, table).await.is_err());
+        assert!(pool.exists(id, table).await.is_err());
+        assert!(pool.delete_one_by_id(id, table).await.is_err());
+    }
+    assert_eq!(pool.count(table).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn db_pool_rejects_invalid_ids() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("ids");
+
+        pool.initiate(&table).await.unwrap();
+        check_rejected(&pool, &table).await;
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_rejects_invalid_ids() {
+    check_rejected(&MemoryPool::new(), "sessions").await;
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "signed_pool")]
+
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{MemoryPool, SignedPool};
+
+const SECRET: &[u8] = b"a secret only the application knows";
+
+//the clone shares its entries, so a test can read and write the raw payloads
+fn pools() -> (SignedPool<MemoryPool>, MemoryPool) {
+    let storage = MemoryPool::new();
+    (SignedPool::new(storage.clone(), SECRET), storage)
+}
+
+#[tokio::test]
+async fn roundtrip_returns_the_original_data() {
+    let (pool, storage) = pools();
+    let expires = common::now() + 60;
+    let session = r#"{"user":"a|b","admin":false}"#;
+
+    pool.store("a", session, expires, "t").await.unwrap();
+
+    assert_eq!(pool.load("a", "t").await.unwrap().as_deref(), Some(session));
+    assert!(pool.exists("a", "t").await.unwrap());
+    assert_ne!(
+        storage.load("a", "t").await.unwrap().as_deref(),
+        Some(session)
+    );
+}
+
+#[tokio::test]
+async fn an_altered_blob_loads_as_none() {
+    let (pool, storage) = pools();
+    let expires = common::now() + 60;
+    pool.store("a", r#"{"admin":false}"#, expires, "t")
+        .await
+        .unwrap();
+
+    let stored = storage.load("a", "t").await.unwrap().unwrap();
+    let altered = stored.replace("false", "true");
+    storage.store("a", &altered, expires, "t").await.unwrap();
+
+    assert_eq!(pool.load("a", "t").await.unwrap(), None);
+    assert!(!pool.exists("a", "t").await.unwrap());
+}
+
+#[tokio::test]
+async fn a_blob_copied_to_another_session_loads_as_none() {
+    let (pool, storage) = pools();
+    let expires = common::now() + 60;
+    pool.store("admin", r#"{"admin":true}"#, expires, "t")
+        .await
+        .unwrap();
+    let stored = storage.load("admin", "t").await.unwrap().unwrap();
+
+    storage
+        .store("attacker", &stored, expires, "t")
+        .await
+        .unwrap();
+    storage
+        .store("admin", &stored, expires, "other")
+        .await
+        .unwrap();
+
+    assert_eq!(pool.load("attacker", "t").await.unwrap(), None);
+    assert!(!pool.exists("attacker", "t").await.unwrap());
+    assert_eq!(pool.load("admin", "other").await.unwrap(), None);
+    assert!(pool.load("admin", "t").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn another_secret_does_not_verify() {
+    let (pool, storage) = pools();
+    pool.store("a", "{}x", common::now() + 60, "t")
+        .await
+        .unwrap();
+
+    let other = SignedPool::new(storage, b"another secret");
+    assert_eq!(other.load("a", "t").await.unwrap(), None);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+
+#[tokio::test]
+async fn an_empty_store_deletes_the_row() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_skip_empty_sessions(true);
+        let table = common::table("skip_empty");
+        let expires = common::now() + 60;
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+        pool.store("b", "{\"b\":1}", expires, &table).await.unwrap();
+
+        pool.store("a", "{}", expires, &table).await.unwrap();
+        pool.store("b", "", expires, &table).await.unwrap();
+        //nothing stored yet, nothing written
+        pool.store("c", "{}", expires, &table).await.unwrap();
+
+        assert_eq!(pool.load("a", &table).await.unwrap(), None);
+        assert_eq!(pool.load("b", &table).await.unwrap(), None);
+        assert_eq!(pool.load("c", &table).await.unwrap(), None);
+        assert_eq!(pool.count_total(&table).await.unwrap(), 0);
+    }
+}
+
+#[tokio::test]
+async fn empty_sessions_are_stored_by_default() {
+    let pool = DbPool::new(common::sqlite().await);
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{}", common::now() + 60, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pool.load("a", "sessions").await.unwrap().as_deref(),
+        Some("{}")
+    );
+}
+
+#[tokio::test]
+async fn the_predicate_decides_what_is_empty() {
+    //an encrypted payload is never "{}", the application knows its own empty value
+    let pool = DbPool::new(common::sqlite().await)
+        .with_empty_session_predicate(|session| session == "sealed:empty");
+    let expires = common::now() + 60;
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "sealed:data", expires, "sessions")
+        .await
+        .unwrap();
+    pool.store("b", "{}", expires, "sessions").await.unwrap();
+    pool.store("a", "sealed:empty", expires, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(pool.load("a", "sessions").await.unwrap(), None);
+    assert_eq!(
+        pool.load("b", "sessions").await.unwrap().as_deref(),
+        Some("{}")
+    );
+}
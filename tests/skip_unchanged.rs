@@ -0,0 +1,158 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+
+const SLACK: Duration = Duration::from_secs(60);
+
+//changes the payload behind the pool's back
+async fn tamper(db: &DatabaseConnection, table: &str, id: &str) {
+    db.execute_unprepared(&format!(
+        "UPDATE {table} SET session = 'tampered' WHERE id = '{id}'"
+    ))
+    .await
+    .unwrap();
+}
+
+async fn stored(pool: &DbPool, table: &str, id: &str) -> Option<String> {
+    pool.load(id, table).await.unwrap()
+}
+
+#[tokio::test]
+async fn an_identical_store_is_skipped_and_counted() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db.clone()).with_skip_unchanged(100, SLACK);
+        let table = common::table("skip_unchanged");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", now + 600, &table)
+            .await
+            .unwrap();
+        assert_eq!(pool.skipped_writes(), 0);
+
+        //the tampered payload stays, so the second store never ran
+        tamper(&db, &table, "a").await;
+        pool.store("a", "{\"a\":1}", now + 630, &table)
+            .await
+            .unwrap();
+        assert_eq!(pool.skipped_writes(), 1);
+        assert_eq!(
+            stored(&pool, &table, "a").await.as_deref(),
+            Some("tampered")
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_changed_payload_is_always_written() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db.clone()).with_skip_unchanged(100, SLACK);
+        let table = common::table("skip_unchanged");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        for n in 0..10 {
+            let session = format!("{{\"a\":{n}}}");
+            pool.store("a", &session, now + 600, &table).await.unwrap();
+            assert_eq!(stored(&pool, &table, "a").await, Some(session));
+        }
+        assert_eq!(pool.skipped_writes(), 0);
+    }
+}
+
+#[tokio::test]
+async fn a_renewal_past_the_slack_is_written() {
+    let db = common::sqlite().await;
+    let pool = DbPool::new(db.clone()).with_skip_unchanged(100, SLACK);
+    let now = common::now();
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    tamper(&db, "sessions", "a").await;
+    pool.store("a", "{\"a\":1}", now + 661, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(pool.skipped_writes(), 0);
+    assert_eq!(
+        stored(&pool, "sessions", "a").await.as_deref(),
+        Some("{\"a\":1}")
+    );
+}
+
+#[tokio::test]
+async fn a_store_after_a_delete_is_written() {
+    let pool = DbPool::new(common::sqlite().await).with_skip_unchanged(100, SLACK);
+    let now = common::now();
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    pool.delete_one_by_id("a", "sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+
+    assert_eq!(pool.skipped_writes(), 0);
+    assert!(stored(&pool, "sessions", "a").await.is_some());
+}
+
+//only the last `capacity` ids are remembered
+#[tokio::test]
+async fn a_forgotten_id_is_written() {
+    let pool = DbPool::new(common::sqlite().await).with_skip_unchanged(1, SLACK);
+    let now = common::now();
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    pool.store("b", "{\"b\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    assert_eq!(pool.skipped_writes(), 0);
+
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    assert_eq!(pool.skipped_writes(), 1);
+}
+
+//keyed on the table the builder's name resolves to, whatever name axum_session passes in
+#[tokio::test]
+async fn a_table_override_is_one_table_for_the_filter() {
+    let table = common::table("skip_unchanged");
+    let pool = DbPool::builder(common::sqlite().await)
+        .table_name(table.clone())
+        .build()
+        .unwrap()
+        .with_skip_unchanged(100, SLACK);
+    let now = common::now();
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "other")
+        .await
+        .unwrap();
+    assert_eq!(pool.skipped_writes(), 1);
+
+    //a delete under either name forgets the write
+    pool.delete_one_by_id("a", "other").await.unwrap();
+    pool.store("a", "{\"a\":1}", now + 600, "sessions")
+        .await
+        .unwrap();
+    assert_eq!(pool.skipped_writes(), 1);
+    assert!(pool.exists("a", &table).await.unwrap());
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+
+async fn expiry(pool: &DbPool, id: &str, table: &str) -> Option<i64> {
+    let (_, expires) = pool.load_with_expiry(id, table).await.unwrap().unwrap();
+    expires.map(|expires| expires.timestamp())
+}
+
+//the second call of each runs the cached SQL with its own values
+#[tokio::test]
+async fn cached_statements_bind_the_values_of_each_call() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db);
+        let table = common::table("statement_cache");
+        let now = common::now();
+
+        pool.initiate(&table).await.unwrap();
+        for (id, expires) in [("a", now + 60), ("b", now + 120), ("c", now - 1)] {
+            pool.store(id, &format!("{{\"id\":\"{id}\"}}"), expires, &table)
+                .await
+                .unwrap();
+        }
+
+        for id in ["a", "b"] {
+            assert_eq!(
+                pool.load(id, &table).await.unwrap(),
+                Some(format!("{{\"id\":\"{id}\"}}"))
+            );
+            assert!(pool.exists(id, &table).await.unwrap());
+        }
+        assert_eq!(expiry(&pool, "a", &table).await, Some(now + 60));
+        assert_eq!(expiry(&pool, "b", &table).await, Some(now + 120));
+        assert_eq!(pool.load("c", &table).await.unwrap(), None);
+        assert_eq!(pool.load("unknown", &table).await.unwrap(), None);
+
+        pool.delete_one_by_id("a", &table).await.unwrap();
+        pool.delete_one_by_id("unknown", &table).await.unwrap();
+        assert_eq!(pool.get_ids(&table).await.unwrap(), ["b"]);
+    }
+}
+
+//clones share the cache, the SQL of one option set is not run for another
+#[tokio::test]
+async fn clones_with_other_options_get_their_own_sql() {
+    for db in common::databases().await {
+        let plain = DbPool::new(db);
+        let monotonic = plain.clone().with_monotonic_expiry(true);
+        let table = common::table("statement_cache");
+        let now = common::now();
+
+        plain.initiate(&table).await.unwrap();
+        plain
+            .store("a", "{\"a\":1}", now + 120, &table)
+            .await
+            .unwrap();
+        monotonic
+            .store("a", "{\"a\":2}", now + 60, &table)
+            .await
+            .unwrap();
+        assert_eq!(expiry(&plain, "a", &table).await, Some(now + 120));
+
+        plain
+            .store("a", "{\"a\":3}", now + 60, &table)
+            .await
+            .unwrap();
+        assert_eq!(expiry(&plain, "a", &table).await, Some(now + 60));
+    }
+}
+
+//one pool serving more tables than the cache keeps, built per call past that
+#[tokio::test]
+async fn many_tables_keep_working() {
+    let pool = DbPool::new(common::sqlite().await);
+    let expires = common::now() + 60;
+
+    let tables: Vec<String> = (0..80).map(|_| common::table("statement_cache")).collect();
+    for table in &tables {
+        pool.initiate(table).await.unwrap();
+        pool.store("a", &format!("{{\"table\":\"{table}\"}}"), expires, table)
+            .await
+            .unwrap();
+    }
+    for table in &tables {
+        assert_eq!(
+            pool.load("a", table).await.unwrap(),
+            Some(format!("{{\"table\":\"{table}\"}}"))
+        );
+    }
+}
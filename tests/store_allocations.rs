@@ -0,0 +1,77 @@
+//counts the bytes allocated while store() runs, alone in its binary so no other test allocates
+//in the meantime
+
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use axum_session::DatabasePool;
+use dxp_axum_session::{DbPool, MemoryPool};
+
+struct Counting;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.load(Ordering::Relaxed) {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+async fn allocated_by(
+    store: impl std::future::Future<Output = Result<(), axum_session::DatabaseError>>,
+) -> usize {
+    ALLOCATED.store(0, Ordering::Relaxed);
+    COUNTING.store(true, Ordering::Relaxed);
+    store.await.unwrap();
+    COUNTING.store(false, Ordering::Relaxed);
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+//the payload is copied once into the store or the bound statement, the driver makes its own
+//copy, any copy on top of that shows up here
+#[tokio::test(flavor = "current_thread")]
+async fn store_copies_the_payload_as_little_as_it_can() {
+    let payload = format!("{{\"data\":\"{}\"}}", "x".repeat(64 * 1024));
+    let expires = common::now() + 60;
+
+    let memory = MemoryPool::new();
+    let db = DbPool::new(common::sqlite().await);
+    db.initiate("sessions").await.unwrap();
+    //the first store sets up what later ones reuse
+    memory
+        .store("warm", &payload, expires, "sessions")
+        .await
+        .unwrap();
+    db.store("warm", &payload, expires, "sessions")
+        .await
+        .unwrap();
+
+    let allocated = allocated_by(memory.store("a", &payload, expires, "sessions")).await;
+    assert!(
+        allocated < payload.len() * 3 / 2,
+        "{allocated} bytes for {}",
+        payload.len()
+    );
+
+    let allocated = allocated_by(db.store("a", &payload, expires, "sessions")).await;
+    assert!(
+        allocated < payload.len() * 5 / 2,
+        "{allocated} bytes for {}",
+        payload.len()
+    );
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use futures_util::{StreamExt, TryStreamExt};
+
+const LIVE: usize = 1_500;
+
+async fn seeded(table: &str) -> (DbPool, i64) {
+    let pool = DbPool::new(common::sqlite().await);
+    let now = common::now();
+
+    let ids: Vec<String> = (0..LIVE + 100).map(|n| format!("id{n:04}")).collect();
+    let sessions: Vec<(&str, &str, i64)> = ids
+        .iter()
+        .enumerate()
+        .map(|(n, id)| {
+            //the last 100 are expired
+            let expires = if n < LIVE { now + 60 } else { now - 60 };
+            (id.as_str(), "{\"a\":1}", expires)
+        })
+        .collect();
+
+    pool.initiate(table).await.unwrap();
+    pool.store_many(&sessions, table).await.unwrap();
+    (pool, now)
+}
+
+#[tokio::test]
+async fn stream_ids_yields_every_live_id_once() {
+    let (pool, _) = seeded("sessions").await;
+
+    let ids: Vec<String> = pool.stream_ids("sessions").try_collect().await.unwrap();
+    assert_eq!(ids.len(), LIVE);
+    assert_eq!(ids.iter().collect::<BTreeSet<_>>().len(), LIVE);
+    assert_eq!(ids.len(), pool.get_ids("sessions").await.unwrap().len());
+}
+
+#[tokio::test]
+async fn stream_sessions_yields_payload_and_expiry() {
+    let (pool, now) = seeded("sessions").await;
+
+    let mut count = 0;
+    let mut sessions = Box::pin(pool.stream_sessions("sessions"));
+    while let Some(session) = sessions.next().await {
+        let (_, session, expires) = session.unwrap();
+        assert_eq!(session, "{\"a\":1}");
+        assert_eq!(expires.map(|expires| expires.timestamp()), Some(now + 60));
+        count += 1;
+    }
+    assert_eq!(count, LIVE);
+}
+
+//the error is an item of the stream
+#[tokio::test]
+async fn a_query_error_is_yielded() {
+    let pool = DbPool::new(common::sqlite().await);
+
+    let items: Vec<_> = pool.stream_ids("missing").collect().await;
+    assert_eq!(items.len(), 1);
+    assert!(items[0].is_err());
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use chrono::DateTime;
+use dxp_axum_session::{DbPool, MemoryPool, MockClock};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+const GRACE: Duration = Duration::from_secs(60);
+
+fn frozen() -> (MockClock, i64) {
+    let now = common::now();
+    (
+        MockClock::new(DateTime::from_timestamp(now, 0).unwrap()),
+        now,
+    )
+}
+
+//a request still in flight after the logout writes the session it read before it
+async fn check_logout_sticks(pool: &impl DatabasePool, clock: &MockClock, table: &str, now: i64) {
+    pool.initiate(table).await.unwrap();
+    pool.store("a", "{\"user\":1}", now + 3600, table)
+        .await
+        .unwrap();
+    pool.store("b", "{\"user\":2}", now + 3600, table)
+        .await
+        .unwrap();
+
+    pool.delete_one_by_id("a", table).await.unwrap();
+    pool.store("a", "{\"user\":1}", now + 3600, table)
+        .await
+        .unwrap();
+
+    assert_eq!(pool.load("a", table).await.unwrap(), None);
+    assert!(!pool.exists("a", table).await.unwrap());
+    assert_eq!(
+        pool.load("b", table).await.unwrap().as_deref(),
+        Some("{\"user\":2}")
+    );
+
+    //after the grace period the id is free again
+    clock.advance(chrono::Duration::seconds(61));
+    pool.store("a", "{\"user\":3}", now + 3600, table)
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.load("a", table).await.unwrap().as_deref(),
+        Some("{\"user\":3}")
+    );
+}
+
+//records the id of every row inserted into or updated in `table` from now on
+async fn audit_writes(db: &DatabaseConnection, table: &str) {
+    let backend = db.get_database_backend();
+    let statements = match backend {
+        DbBackend::Postgres => vec![
+            format!(r#"CREATE TABLE "{table}_writes" (id TEXT)"#),
+            format!(
+                r#"CREATE FUNCTION "{table}_audit"() RETURNS trigger AS $$
+                   BEGIN INSERT INTO "{table}_writes" VALUES (NEW.id); RETURN NEW; END
+                   $$ LANGUAGE plpgsql"#
+            ),
+            format!(
+                r#"CREATE TRIGGER "{table}_audit" AFTER INSERT OR UPDATE ON "{table}"
+                   FOR EACH ROW EXECUTE FUNCTION "{table}_audit"()"#
+            ),
+        ],
+        _ => vec![
+            format!(r#"CREATE TABLE "{table}_writes" (id TEXT)"#),
+            format!(
+                r#"CREATE TRIGGER "{table}_inserts" AFTER INSERT ON "{table}"
+                   BEGIN INSERT INTO "{table}_writes" VALUES (NEW.id); END"#
+            ),
+            format!(
+                r#"CREATE TRIGGER "{table}_updates" AFTER UPDATE ON "{table}"
+                   BEGIN INSERT INTO "{table}_writes" VALUES (NEW.id); END"#
+            ),
+        ],
+    };
+
+    for sql in statements {
+        db.execute(Statement::from_string(backend, sql))
+            .await
+            .unwrap();
+    }
+}
+
+async fn writes(db: &DatabaseConnection, table: &str, id: &str) -> i64 {
+    db.query_one(Statement::from_string(
+        db.get_database_backend(),
+        format!(r#"SELECT COUNT(*) AS count FROM "{table}_writes" WHERE id = '{id}'"#),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get::<i64>("", "count")
+    .unwrap()
+}
+
+//the row must not come back even for a moment, a concurrent load() would see it
+async fn check_buried_id_is_never_written(db: DatabaseConnection, pool: DbPool, table: &str) {
+    let now = common::now();
+
+    pool.initiate(table).await.unwrap();
+    pool.store("a", "{\"user\":1}", now + 3600, table)
+        .await
+        .unwrap();
+    pool.delete_one_by_id("a", table).await.unwrap();
+    audit_writes(&db, table).await;
+
+    pool.store("a", "{\"user\":1}", now + 3600, table)
+        .await
+        .unwrap();
+    pool.store_with_user("a", "{\"user\":1}", now + 3600, "1", table)
+        .await
+        .unwrap();
+    pool.store_many(
+        &[
+            ("a", "{\"user\":1}", now + 3600),
+            ("b", "{\"user\":2}", now + 3600),
+        ],
+        table,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(writes(&db, table, "a").await, 0);
+    assert_eq!(writes(&db, table, "b").await, 1);
+    assert_eq!(pool.load("a", table).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn a_buried_id_is_never_written() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db.clone()).with_tombstones(GRACE);
+        check_buried_id_is_never_written(db, pool, &common::table("buried")).await;
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn a_buried_id_is_never_written_to_a_partitioned_table() {
+    let Some(db) = common::postgres().await else {
+        return;
+    };
+    let pool = DbPool::new(db.clone())
+        .with_tombstones(GRACE)
+        .with_partitioning(true);
+    check_buried_id_is_never_written(db, pool, &common::table("buried")).await;
+}
+
+#[tokio::test]
+async fn db_pool_keeps_a_deleted_session_dead() {
+    for db in common::databases().await {
+        let (clock, now) = frozen();
+        let pool = DbPool::new(db)
+            .with_tombstones(GRACE)
+            .with_clock(clock.clone());
+        check_logout_sticks(&pool, &clock, &common::table("tombstones"), now).await;
+    }
+}
+
+#[tokio::test]
+async fn memory_pool_keeps_a_deleted_session_dead() {
+    let (clock, now) = frozen();
+    let pool = MemoryPool::new()
+        .with_tombstones(GRACE)
+        .with_clock(clock.clone());
+    check_logout_sticks(&pool, &clock, "sessions", now).await;
+}
+
+#[tokio::test]
+async fn without_tombstones_a_store_brings_the_session_back() {
+    let pool = DbPool::new(common::sqlite().await);
+    let expires = common::now() + 3600;
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"user\":1}", expires, "sessions")
+        .await
+        .unwrap();
+    pool.delete_one_by_id("a", "sessions").await.unwrap();
+    pool.store("a", "{\"user\":1}", expires, "sessions")
+        .await
+        .unwrap();
+
+    assert!(pool.load("a", "sessions").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn delete_by_expiry_drops_old_tombstones() {
+    let (clock, now) = frozen();
+    let db = common::sqlite().await;
+    let pool = DbPool::new(db.clone())
+        .with_tombstones(GRACE)
+        .with_clock(clock.clone());
+    let tombstones = || async {
+        db.query_one(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT COUNT(*) AS count FROM sessions_tombstones",
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get::<i64>("", "count")
+        .unwrap()
+    };
+
+    pool.initiate("sessions").await.unwrap();
+    pool.store("a", "{\"user\":1}", now + 3600, "sessions")
+        .await
+        .unwrap();
+    pool.delete_one_by_id("a", "sessions").await.unwrap();
+    pool.delete_by_expiry("sessions").await.unwrap();
+    assert_eq!(tombstones().await, 1);
+
+    clock.advance(chrono::Duration::seconds(61));
+    pool.delete_by_expiry("sessions").await.unwrap();
+    assert_eq!(tombstones().await, 0);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+
+//TRUNCATE on Postgres, DELETE on SQLite
+#[tokio::test]
+async fn delete_all_with_truncate_empties_the_table() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_truncate_on_clear(true);
+        let table = common::table("truncate");
+        let expires = common::now() + 60;
+
+        pool.initiate(&table).await.unwrap();
+        for id in ["a", "b", "c"] {
+            pool.store(id, "{\"a\":1}", expires, &table).await.unwrap();
+        }
+        pool.store("expired", "{\"a\":1}", expires - 120, &table)
+            .await
+            .unwrap();
+
+        pool.delete_all(&table).await.unwrap();
+        assert_eq!(pool.count_total(&table).await.unwrap(), 0);
+
+        //the table and its constraints are still there
+        pool.store("a", "{\"a\":2}", expires, &table).await.unwrap();
+        pool.store("a", "{\"a\":3}", expires, &table).await.unwrap();
+        assert_eq!(
+            pool.load("a", &table).await.unwrap().as_deref(),
+            Some("{\"a\":3}")
+        );
+        assert_eq!(pool.count_total(&table).await.unwrap(), 1);
+    }
+}
+
+#[tokio::test]
+async fn a_missing_table_is_an_error() {
+    for db in common::databases().await {
+        let pool = DbPool::new(db).with_truncate_on_clear(true);
+
+        assert!(pool.delete_all(&common::table("missing")).await.is_err());
+    }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_session::DatabasePool;
+use dxp_axum_session::DbPool;
+use futures_util::TryStreamExt;
+
+//long enough for the background task to stay idle during a test
+const INTERVAL: Duration = Duration::from_secs(3600);
+
+async fn ids(pool: &DbPool, table: &str) -> Vec<String> {
+    let mut ids = pool.get_ids(table).await.unwrap();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn queued_sessions_are_read_back() {
+    let pool = DbPool::new(common::sqlite().await).with_write_behind(INTERVAL, 100, 100);
+    let table = common::table("write_behind");
+    let expires = common::now() + 3600;
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+    pool.store("b", "{\"b\":2}", expires, &table).await.unwrap();
+
+    assert_eq!(
+        pool.load("a", &table).await.unwrap().as_deref(),
+        Some("{\"a\":1}")
+    );
+    assert!(pool.exists("b", &table).await.unwrap());
+    assert_eq!(pool.count(&table).await.unwrap(), 2);
+    assert_eq!(ids(&pool, &table).await, ["a", "b"]);
+}
+
+#[tokio::test]
+async fn counts_and_streams_see_queued_sessions() {
+    let pool = DbPool::new(common::sqlite().await).with_write_behind(INTERVAL, 100, 100);
+    let table = common::table("write_behind");
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+    assert_eq!(pool.count_total(&table).await.unwrap(), 1);
+
+    pool.store("b", "{\"b\":2}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+    let mut streamed: Vec<String> = pool.stream_ids(&table).try_collect().await.unwrap();
+    streamed.sort();
+    assert_eq!(streamed, ["a", "b"]);
+}
+
+#[tokio::test]
+async fn flush_writes_the_queue() {
+    let db = common::sqlite().await;
+    let plain = DbPool::new(db.clone());
+    let pool = DbPool::new(db).with_write_behind(INTERVAL, 100, 100);
+    let table = common::table("write_behind");
+    let expires = common::now() + 3600;
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", expires, &table).await.unwrap();
+    pool.store("b", "{\"b\":2}", expires, &table).await.unwrap();
+    assert_eq!(plain.load("a", &table).await.unwrap(), None);
+
+    pool.flush().await.unwrap();
+    drop(pool);
+
+    assert_eq!(ids(&plain, &table).await, ["a", "b"]);
+}
+
+#[tokio::test]
+async fn the_task_writes_with_the_final_options() {
+    let db = common::sqlite().await;
+    let plain = DbPool::new(db.clone());
+    let table = common::table("write_behind");
+
+    //set after with_write_behind(), the background task still has to honour it
+    let pool = DbPool::new(db)
+        .with_write_behind(Duration::from_millis(10), 100, 100)
+        .with_max_expires(common::now() + 60);
+
+    pool.initiate(&table).await.unwrap();
+    pool.store("a", "{\"a\":1}", common::now() + 3600, &table)
+        .await
+        .unwrap();
+
+    let mut written = None;
+    for _ in 0..200 {
+        written = plain.load_with_expiry("a", &table).await.unwrap();
+        if written.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    //past max_expires, so stored as never expiring
+    assert_eq!(written, Some(("{\"a\":1}".to_owned(), None)));
+}
+
+#[tokio::test]
+async fn a_full_queue_writes_right_away() {
+    let db = common::sqlite().await;
+    let plain = DbPool::new(db.clone());
+    let pool = DbPool::new(db).with_write_behind(INTERVAL, 100, 2);
+    let table = common::table("write_behind");
+    let expires = common::now() + 3600;
+
+    pool.initiate(&table).await.unwrap();
+    for id in ["a", "b", "c", "d", "e"] {
+        pool.store(id, "{\"a\":1}", expires, &table).await.unwrap();
+    }
+
+    //two stay queued, the others were written by store() itself
+    assert_eq!(ids(&plain, &table).await, ["c", "d", "e"]);
+
+    //a queued id is replaced in the queue, not written
+    pool.store("a", "{\"a\":2}", expires, &table).await.unwrap();
+    assert_eq!(plain.count(&table).await.unwrap(), 3);
+
+    pool.flush().await.unwrap();
+    assert_eq!(ids(&plain, &table).await, ["a", "b", "c", "d", "e"]);
+    assert_eq!(
+        plain.load("a", &table).await.unwrap().as_deref(),
+        Some("{\"a\":2}")
+    );
+}
+
+#[test]
+fn no_runtime_is_needed_to_build_the_pool() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let db = runtime.block_on(common::sqlite());
+
+    let pool = DbPool::new(db).with_write_behind(INTERVAL, 100, 100);
+    let table = common::table("write_behind");
+
+    runtime.block_on(async {
+        pool.initiate(&table).await.unwrap();
+        pool.store("a", "{\"a\":1}", common::now() + 3600, &table)
+            .await
+            .unwrap();
+        assert_eq!(pool.count(&table).await.unwrap(), 1);
+    });
+}